@@ -1,6 +1,5 @@
 
 
-use reqwest;
 use std::io::Write;
 
 
@@ -22,7 +21,7 @@ fn main() {
     
     let filename = "coinbase_products.json";
     let mut file = std::fs::File::create(filename).unwrap_or_else(|error| panic!("failed to create file {filename}: {error}"));
-    file.write(response_text.as_bytes()).expect("failed to write data to file");
+    file.write_all(response_text.as_bytes()).expect("failed to write data to file");
 
     println!("Program ends")
 }
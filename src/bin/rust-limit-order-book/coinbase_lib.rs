@@ -1,28 +1,33 @@
 
-use std::io::Write;
-use std::str::FromStr;
 use std::fmt;
 use std::fmt::Display;
 
 use serde::Serialize;
 use serde::Deserialize;
-use serde::Deserializer;
-use serde::de;
 
-use reqwest;
-use reqwest::header::USER_AGENT;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
 
-use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
-use crate::limit_order_book_lib::OrderSide;
-use crate::limit_order_book_lib::Order;
+use crate::exchange_adapter_lib::ExchangeAdapter;
+use crate::exchange_adapter_lib::ExchangeAdapterError;
+use crate::exchange_adapter_lib::Snapshot;
+use crate::exchange_adapter_lib::de_from_str;
+use crate::exchange_adapter_lib::de_option_from_str;
+
+use crate::instrument_lib::InstrumentRegistry;
+use crate::instrument_lib::InstrumentSpec;
+use crate::instrument_lib::InstrumentStatus;
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CoinbaseBidAskL2 {
     #[serde(deserialize_with="de_from_str")]
-    pub price: f64,
+    pub price: Price,
     #[serde(deserialize_with="de_from_str")]
-    pub volume: f64,
+    pub volume: Quantity,
     pub count: u64,
 }
 
@@ -37,13 +42,6 @@ impl Display for CoinbaseBidAskL2 {
     }
 }
 
-fn de_from_str<'de, D>(deserializer: D) -> Result<f64, D::Error>
-    where D: Deserializer<'de>
-{
-    let s = <&str>::deserialize(deserializer)?;
-    f64::from_str(s).map_err(de::Error::custom)
-}
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CoinbaseBookL2 {
     pub asks: Vec<CoinbaseBidAskL2>,
@@ -62,72 +60,131 @@ impl Display for CoinbaseBookL2 {
     }
 }
 
-pub fn get_api_coinbase(
-    client: &reqwest::blocking::Client,
-    user_agent: &str,
-) -> String {
-
-    // Coinbase
-    let product_id = "BTC-USD";
-    let url_coinbase = format!("https://api.exchange.coinbase.com/products/{product_id}/book?level=2");
-    println!("Coinbase URL: {url_coinbase}");
-
-    // The behaviour I want here is for the program to panic if this call fails, hence `expect`
-    let response = client
-        .get(url_coinbase)
-        .header("Content-Type", "application/json")
-        .header(USER_AGENT, user_agent)
-        .send()
-        .expect("client failed to send request");
-    println!("Coinbase Response Status Code: {}", response.status());
-    
-    let response_text = response.text().expect("failed to convert response to text");
-    response_text
+/// One product of the `/products` catalog, which `get_coinbase_products`
+/// saves to `coinbase_products.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CoinbaseProduct {
+    pub id: String,
+    pub base_currency: String,
+    pub quote_currency: String,
+    #[serde(deserialize_with="de_from_str")]
+    pub quote_increment: Price,
+    #[serde(deserialize_with="de_from_str")]
+    pub base_increment: Quantity,
+    /// Coinbase no longer sends the order size limits, but older catalogs have them.
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub base_min_size: Option<Quantity>,
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub base_max_size: Option<Quantity>,
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub min_market_funds: Option<Notional>,
+    pub status: String,
+    #[serde(default)]
+    pub trading_disabled: bool,
+    #[serde(default)]
+    pub post_only: bool,
+    #[serde(default)]
+    pub limit_only: bool,
+    #[serde(default)]
+    pub cancel_only: bool,
 }
 
-pub fn dump_coinbase_response_text_to_file(response_text: &str) {
-    let filename = "coinbase_BTC-USD.json";
-    let mut file = std::fs::File::create(filename).unwrap_or_else(|error| panic!("failed to create file {filename}: {error}"));
-    file.write(response_text.as_bytes()).expect("failed to write data to file");
+impl CoinbaseProduct {
+    pub fn instrument_spec(&self, source_exchange: ExchangeId) -> InstrumentSpec {
+        let status = match InstrumentStatus::from_venue_status(&self.status) {
+            InstrumentStatus::Online if self.cancel_only => InstrumentStatus::CancelOnly,
+            InstrumentStatus::Online if self.post_only => InstrumentStatus::PostOnly,
+            InstrumentStatus::Online if self.limit_only => InstrumentStatus::LimitOnly,
+            status => status,
+        };
+        InstrumentSpec {
+            source_exchange,
+            symbol: self.id.clone(),
+            instrument: Instrument::intern(&self.base_currency, &self.quote_currency),
+            status,
+            trading_disabled: self.trading_disabled,
+            quote_increment: self.quote_increment,
+            base_increment: self.base_increment,
+            minimum_order_size: self.base_min_size,
+            maximum_order_size: self.base_max_size,
+            minimum_notional: self.min_market_funds,
+        }
+    }
 }
 
-pub fn load_coinbase_book<'s>(
-    response_text: String,
-    ticker: &'s str,
-    source_exchange: &'s str,
-    limit_order_book: &mut MultiTickerLimitOrderBook<'s>,
-) {
-    let coinbase_book =
-        serde_json::from_str::<CoinbaseBookL2>(&response_text)
-        .expect("failed to deserialize CoinbaseBookL2");
-
-    let filename = "tmp_coinbase_BTC-USE.json";
-    let mut file = std::fs::File::create(filename).unwrap();
-    file.write(format!("{coinbase_book}").as_bytes()).expect("failed to write data to file");
-
-    for bid in coinbase_book.bids {
-        let price = bid.price;
-        let volume = bid.volume;
-        let order = Order::new(
-            ticker,
-            OrderSide::BUY,
-            price,
-            volume,
+/// Parse the `/products` catalog into the spec of each product.
+pub fn parse_coinbase_products(response_text: &str, source_exchange: ExchangeId) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError> {
+    let coinbase_products = serde_json::from_str::<Vec<CoinbaseProduct>>(response_text)?;
+    Ok(
+        coinbase_products
+            .iter()
+            .map(|coinbase_product| coinbase_product.instrument_spec(source_exchange))
+            .collect()
+    )
+}
+
+pub const COINBASE_ENDPOINT: &str = "https://api.exchange.coinbase.com";
+
+/// The level 2 REST book of one Coinbase product, for example `BTC-USD`.
+#[derive(Debug)]
+pub struct CoinbaseAdapter {
+    product_id: String,
+    source_exchange: ExchangeId,
+    endpoint: String,
+}
+
+impl CoinbaseAdapter {
+    pub fn new(product_id: &str, source_exchange: ExchangeId) -> CoinbaseAdapter {
+        CoinbaseAdapter {
+            product_id: String::from(product_id),
             source_exchange,
-        ).expect("failed to construct Order");
-        limit_order_book.add_order(order);
+            endpoint: String::from(COINBASE_ENDPOINT),
+        }
     }
 
-    for ask in coinbase_book.asks {
-        let price = ask.price;
-        let volume = ask.volume;
-        let order = Order::new(
-            ticker,
-            OrderSide::SELL,
-            price,
-            volume,
-            source_exchange,
-        ).expect("failed to construct Order");
-        limit_order_book.add_order(order);
+    /// Use a different base URL for the REST API, for example a sandbox.
+    pub fn with_endpoint(mut self, endpoint: &str) -> CoinbaseAdapter {
+        self.endpoint = String::from(endpoint.trim_end_matches('/'));
+        self
+    }
+}
+
+impl ExchangeAdapter for CoinbaseAdapter {
+    fn source_exchange(&self) -> ExchangeId {
+        self.source_exchange
+    }
+
+    fn symbol(&self) -> String {
+        self.product_id.clone()
+    }
+
+    fn url(&self) -> String {
+        let endpoint = self.endpoint.as_str();
+        let product_id = self.product_id.as_str();
+        format!("{endpoint}/products/{product_id}/book?level=2")
+    }
+
+    fn dump_filename(&self) -> String {
+        let product_id = self.product_id.as_str();
+        format!("coinbase_{product_id}.json")
     }
-}
\ No newline at end of file
+
+    fn catalog_url(&self) -> String {
+        let endpoint = self.endpoint.as_str();
+        format!("{endpoint}/products")
+    }
+
+    fn parse_catalog(&self, response_text: &str) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError> {
+        parse_coinbase_products(response_text, self.source_exchange)
+    }
+
+    fn parse_snapshot(&self, response_text: &str, _instrument_registry: &InstrumentRegistry) -> Result<Snapshot, ExchangeAdapterError> {
+        let coinbase_book = serde_json::from_str::<CoinbaseBookL2>(response_text)?;
+        Ok(
+            Snapshot {
+                bids: coinbase_book.bids.iter().map(|bid| (bid.price, bid.volume)).collect(),
+                asks: coinbase_book.asks.iter().map(|ask| (ask.price, ask.volume)).collect(),
+            }
+        )
+    }
+}
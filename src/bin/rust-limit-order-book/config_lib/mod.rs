@@ -0,0 +1,373 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::coinbase_lib::CoinbaseAdapter;
+use crate::gemini_lib::GeminiAdapter;
+use crate::kraken_lib::KrakenAdapter;
+use crate::kraken_lib::KRAKEN_ASSET_CODES;
+
+use crate::exchange_adapter_lib::ExchangeAdapter;
+use crate::exchange_adapter_lib::de_from_str;
+
+use crate::instrument_lib::InstrumentCatalog;
+use crate::instrument_lib::InstrumentError;
+use crate::instrument_lib::InstrumentRegistry;
+
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::Quantity;
+
+use crate::recorder_lib::Compression;
+use crate::recorder_lib::RecorderConfig;
+
+use crate::websocket_feed_lib::CoinbaseFeedHandler;
+use crate::websocket_feed_lib::FeedHandler;
+use crate::websocket_feed_lib::GeminiFeedHandler;
+use crate::websocket_feed_lib::KrakenFeedHandler;
+
+
+/// The number of price levels on each side of the Kraken WebSocket book.
+const KRAKEN_FEED_DEPTH: usize = 10;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// A config which is valid JSON but which cannot be used.
+    Invalid(String),
+    Instrument(InstrumentError),
+}
+
+impl std::error::Error for ConfigError {
+
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => {
+                write!(f, "failed to read config: {error}")
+            },
+            ConfigError::Json(error) => {
+                write!(f, "failed to deserialize config: {error}")
+            },
+            ConfigError::Invalid(message) => {
+                write!(f, "invalid config: {message}")
+            },
+            ConfigError::Instrument(error) => {
+                write!(f, "invalid config: {error}")
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> ConfigError {
+        ConfigError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(error: serde_json::Error) -> ConfigError {
+        ConfigError::Json(error)
+    }
+}
+
+impl From<InstrumentError> for ConfigError {
+    fn from(error: InstrumentError) -> ConfigError {
+        ConfigError::Instrument(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Venue {
+    Coinbase,
+    Gemini,
+    Kraken,
+}
+
+impl Venue {
+    pub fn source_exchange(&self) -> ExchangeId {
+        match self {
+            Venue::Coinbase => {
+                ExchangeId::intern("COINBASE")
+            },
+            Venue::Gemini => {
+                ExchangeId::intern("GEMINI")
+            },
+            Venue::Kraken => {
+                ExchangeId::intern("KRAKEN")
+            },
+        }
+    }
+}
+
+/// How one instrument is traded on one venue.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct VenueConfig {
+    pub venue: Venue,
+    /// The symbol of the REST book, for example `BTC-USD` on Coinbase,
+    /// `btcusd` on Gemini and `XXBTZUSD` on Kraken.
+    pub symbol: String,
+    /// The symbol of the WebSocket feed, where it differs from `symbol`, for
+    /// example `BTCUSD` on Gemini and `XBT/USD` on Kraken. The venue is not
+    /// streamed without one.
+    #[serde(default)]
+    pub stream_symbol: Option<String>,
+    /// The base URL of the REST API. The production API of the venue is used
+    /// without one.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl VenueConfig {
+    pub fn exchange_adapter(&self) -> Box<dyn ExchangeAdapter> {
+        let source_exchange = self.venue.source_exchange();
+        let symbol = self.symbol.as_str();
+        match (self.venue, &self.endpoint) {
+            (Venue::Coinbase, Some(endpoint)) => {
+                Box::new(CoinbaseAdapter::new(symbol, source_exchange).with_endpoint(endpoint))
+            },
+            (Venue::Coinbase, None) => {
+                Box::new(CoinbaseAdapter::new(symbol, source_exchange))
+            },
+            (Venue::Gemini, Some(endpoint)) => {
+                Box::new(GeminiAdapter::new(symbol, source_exchange).with_endpoint(endpoint))
+            },
+            (Venue::Gemini, None) => {
+                Box::new(GeminiAdapter::new(symbol, source_exchange))
+            },
+            (Venue::Kraken, Some(endpoint)) => {
+                Box::new(KrakenAdapter::new(symbol, source_exchange).with_endpoint(endpoint))
+            },
+            (Venue::Kraken, None) => {
+                Box::new(KrakenAdapter::new(symbol, source_exchange))
+            },
+        }
+    }
+
+    /// The WebSocket feed handler of this venue, if it has a `stream_symbol`.
+    /// Coinbase uses the same symbol for both, so `symbol` is used when
+    /// `stream_symbol` is not given.
+    pub fn feed_handler(&self, instrument: Instrument) -> Option<Box<dyn FeedHandler + Send>> {
+        let source_exchange = self.venue.source_exchange();
+        match (self.venue, &self.stream_symbol) {
+            (Venue::Coinbase, stream_symbol) => {
+                let product_id = stream_symbol.as_ref().unwrap_or(&self.symbol);
+                Some(Box::new(CoinbaseFeedHandler::new(product_id, instrument, source_exchange)))
+            },
+            (Venue::Gemini, Some(stream_symbol)) => {
+                Some(Box::new(GeminiFeedHandler::new(stream_symbol, instrument, source_exchange)))
+            },
+            (Venue::Kraken, Some(stream_symbol)) => {
+                Some(Box::new(KrakenFeedHandler::new(stream_symbol, KRAKEN_FEED_DEPTH, instrument, source_exchange)))
+            },
+            (_, None) => {
+                None
+            },
+        }
+    }
+}
+
+/// One instrument, its sizes for the analysis, and the venues it is tracked on.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct InstrumentConfig {
+    /// The canonical code of the base asset, for example `BTC`.
+    pub base_asset: String,
+    /// The canonical code of the quote asset, for example `USD`.
+    pub quote_asset: String,
+    /// The volume to plan buying and selling, in the base asset.
+    #[serde(default = "default_target_volume", deserialize_with = "de_from_str")]
+    pub target_volume: Quantity,
+    /// The notional to plan buying and selling, in the quote asset.
+    #[serde(default = "default_target_notional", deserialize_with = "de_from_str")]
+    pub target_notional: Notional,
+    /// Snap the venue data of this instrument to the grid of its trading
+    /// rules. See `TradingRules::snap_venue_data`.
+    #[serde(default)]
+    pub snap_venue_data: bool,
+    pub venues: Vec<VenueConfig>,
+}
+
+impl InstrumentConfig {
+    pub fn instrument(&self) -> Instrument {
+        Instrument::intern(&self.base_asset, &self.quote_asset)
+    }
+
+    pub fn enabled_venues(&self) -> impl Iterator<Item = &VenueConfig> {
+        self.venues.iter().filter(|venue_config| venue_config.enabled)
+    }
+
+    pub fn exchange_adapters(&self) -> Vec<Box<dyn ExchangeAdapter>> {
+        self.enabled_venues().map(|venue_config| venue_config.exchange_adapter()).collect()
+    }
+
+    pub fn feed_handlers(&self) -> Vec<Box<dyn FeedHandler + Send>> {
+        self.enabled_venues().filter_map(|venue_config| venue_config.feed_handler(self.instrument())).collect()
+    }
+}
+
+/// Where and how `--record` stores captures.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct RecorderSettings {
+    pub directory: PathBuf,
+    #[serde(default)]
+    pub compression: Compression,
+    #[serde(default)]
+    pub maximum_captures: Option<usize>,
+    #[serde(default)]
+    pub maximum_age_seconds: Option<u64>,
+}
+
+impl RecorderSettings {
+    pub fn recorder_config(&self) -> RecorderConfig {
+        let mut recorder_config = RecorderConfig::new(&self.directory);
+        recorder_config.compression = self.compression;
+        recorder_config.maximum_captures = self.maximum_captures;
+        recorder_config.maximum_age = self.maximum_age_seconds.map(Duration::from_secs);
+        recorder_config
+    }
+}
+
+/// The instruments to track and the venues to track them on.
+///
+/// An example is in `config.json` at the root of the repository.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+pub struct Config {
+    pub instruments: Vec<InstrumentConfig>,
+    #[serde(default)]
+    pub recorder: Option<RecorderSettings>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let config_text = std::fs::read_to_string(path)?;
+        Config::parse(&config_text)
+    }
+
+    /// Parse and check a config. Instruments must be unique, each instrument
+    /// can list each venue at most once, and no venue symbol can name two
+    /// instruments.
+    pub fn parse(config_text: &str) -> Result<Config, ConfigError> {
+        let config: Config = serde_json::from_str(config_text)?;
+
+        let mut instruments = BTreeSet::new();
+        for instrument_config in &config.instruments {
+            if instrument_config.base_asset.is_empty() || instrument_config.quote_asset.is_empty() {
+                return Err(ConfigError::Invalid(String::from("an instrument has an empty asset")));
+            }
+            let instrument = instrument_config.instrument();
+            if !instruments.insert(instrument) {
+                return Err(ConfigError::Invalid(format!("instrument {instrument} is listed more than once")));
+            }
+            let mut venues = BTreeSet::new();
+            for venue_config in &instrument_config.venues {
+                if !venues.insert(venue_config.venue) {
+                    let venue = venue_config.venue;
+                    return Err(ConfigError::Invalid(format!("instrument {instrument} lists {venue:?} more than once")));
+                }
+            }
+        }
+        config.instrument_registry()?;
+        Ok(config)
+    }
+
+    /// The registry of the symbols of every venue of every instrument,
+    /// including disabled venues, and the asset codes of each venue.
+    pub fn instrument_registry(&self) -> Result<InstrumentRegistry, InstrumentError> {
+        let mut instrument_registry = InstrumentRegistry::new();
+        for instrument_config in &self.instruments {
+            let instrument = instrument_config.instrument();
+            for venue_config in &instrument_config.venues {
+                let source_exchange = venue_config.venue.source_exchange();
+                if venue_config.venue == Venue::Kraken {
+                    instrument_registry.add_asset_aliases(source_exchange, KRAKEN_ASSET_CODES);
+                }
+                instrument_registry.add_instrument(instrument, source_exchange, &venue_config.symbol)?;
+                if let Some(stream_symbol) = &venue_config.stream_symbol {
+                    instrument_registry.add_symbol_alias(instrument, source_exchange, stream_symbol)?;
+                }
+            }
+        }
+        Ok(instrument_registry)
+    }
+
+    /// Disable each enabled venue whose catalog lists its instrument as not
+    /// trading, or does not list it at all, and return the venues disabled.
+    /// Venues of exchanges with no instruments in `instrument_catalog` are
+    /// left enabled, since their catalog could not be fetched.
+    pub fn disable_untradable_venues(&mut self, instrument_catalog: &InstrumentCatalog) -> Vec<(Instrument, Venue)> {
+        let mut disabled_venues = Vec::new();
+        for instrument_config in &mut self.instruments {
+            let instrument = instrument_config.instrument();
+            for venue_config in &mut instrument_config.venues {
+                let source_exchange = venue_config.venue.source_exchange();
+                if venue_config.enabled
+                    && instrument_catalog.contains_source_exchange(source_exchange)
+                    && !instrument_catalog.is_trading(source_exchange, instrument)
+                {
+                    venue_config.enabled = false;
+                    disabled_venues.push((instrument, venue_config.venue));
+                }
+            }
+        }
+        disabled_venues
+    }
+}
+
+impl Default for Config {
+    /// BTC-USD on Coinbase, Gemini and Kraken.
+    fn default() -> Self {
+        let venue_config = |venue: Venue, symbol: &str, stream_symbol: &str| {
+            VenueConfig {
+                venue,
+                symbol: String::from(symbol),
+                stream_symbol: Some(String::from(stream_symbol)),
+                endpoint: None,
+                enabled: true,
+            }
+        };
+        Config {
+            instruments: vec![
+                InstrumentConfig {
+                    base_asset: String::from("BTC"),
+                    quote_asset: String::from("USD"),
+                    target_volume: default_target_volume(),
+                    target_notional: default_target_notional(),
+                    snap_venue_data: false,
+                    venues: vec![
+                        venue_config(Venue::Coinbase, "BTC-USD", "BTC-USD"),
+                        venue_config(Venue::Gemini, "btcusd", "BTCUSD"),
+                        venue_config(Venue::Kraken, "XXBTZUSD", "XBT/USD"),
+                    ],
+                },
+            ],
+            recorder: None,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_target_volume() -> Quantity {
+    Quantity::from(10)
+}
+
+fn default_target_notional() -> Notional {
+    Notional::from(1_000_000)
+}
+
+
+#[cfg(test)]
+mod tests;
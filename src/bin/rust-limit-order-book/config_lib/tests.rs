@@ -0,0 +1,175 @@
+
+use super::*;
+
+use crate::instrument_lib::InstrumentCatalog;
+use crate::instrument_lib::InstrumentSpec;
+use crate::instrument_lib::InstrumentStatus;
+
+use crate::test_support_lib::fixed_point;
+
+
+const CONFIG_TEXT: &str = r#"{
+    "instruments": [
+        {
+            "base_asset": "ETH",
+            "quote_asset": "USD",
+            "target_volume": "100",
+            "venues": [
+                { "venue": "COINBASE", "symbol": "ETH-USD", "endpoint": "http://localhost:8080/" },
+                { "venue": "GEMINI", "symbol": "ethusd" },
+                { "venue": "KRAKEN", "symbol": "XETHZUSD", "stream_symbol": "ETH/USD", "enabled": false }
+            ]
+        }
+    ],
+    "recorder": {
+        "directory": "captures",
+        "compression": "Gzip",
+        "maximum_captures": 5
+    }
+}"#;
+
+
+#[test]
+fn parse_config_test() {
+
+    let config = Config::parse(CONFIG_TEXT).unwrap();
+    assert_eq!(config.instruments.len(), 1);
+
+    let instrument_config = &config.instruments[0];
+    assert_eq!(instrument_config.instrument(), Instrument::intern("ETH", "USD"));
+    assert_eq!(instrument_config.target_volume, fixed_point("100"));
+    assert_eq!(instrument_config.target_notional, fixed_point("1000000"));
+    assert!(!instrument_config.snap_venue_data);
+    assert!(instrument_config.venues[1].enabled);
+    assert!(!instrument_config.venues[2].enabled);
+
+    // Disabled venues are neither fetched nor streamed, and Gemini is not
+    // streamed without a stream symbol
+    let exchange_adapters = instrument_config.exchange_adapters();
+    let source_exchanges: Vec<ExchangeId> = exchange_adapters
+        .iter()
+        .map(|exchange_adapter| exchange_adapter.source_exchange())
+        .collect();
+    assert_eq!(source_exchanges, vec![ExchangeId::intern("COINBASE"), ExchangeId::intern("GEMINI")]);
+    assert_eq!(exchange_adapters[0].url(), "http://localhost:8080/products/ETH-USD/book?level=2");
+    assert_eq!(exchange_adapters[1].url(), "https://api.gemini.com/v1/book/ethusd");
+    assert_eq!(instrument_config.feed_handlers().len(), 1);
+
+    // Every symbol of every venue resolves to the instrument, including the
+    // symbols of disabled venues
+    let instrument_registry = config.instrument_registry().unwrap();
+    let instrument = Instrument::intern("ETH", "USD");
+    assert_eq!(instrument_registry.resolve(ExchangeId::intern("COINBASE"), "ETH-USD"), Ok(instrument));
+    assert_eq!(instrument_registry.resolve(ExchangeId::intern("GEMINI"), "ETHUSD"), Ok(instrument));
+    assert_eq!(instrument_registry.resolve(ExchangeId::intern("KRAKEN"), "ETH/USD"), Ok(instrument));
+    assert_eq!(instrument_registry.resolve(ExchangeId::intern("KRAKEN"), "XETHZUSD"), Ok(instrument));
+
+    let recorder_config = config.recorder.unwrap().recorder_config();
+    assert_eq!(recorder_config.directory, PathBuf::from("captures"));
+    assert_eq!(recorder_config.compression, Compression::Gzip);
+    assert_eq!(recorder_config.maximum_captures, Some(5));
+    assert_eq!(recorder_config.maximum_age, None);
+}
+
+
+#[test]
+fn disable_untradable_venues_test() {
+
+    let mut config = Config::parse(CONFIG_TEXT).unwrap();
+    let instrument = Instrument::intern("ETH", "USD");
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+
+    // Venues of exchanges missing from the catalog are left enabled
+    assert!(config.disable_untradable_venues(&InstrumentCatalog::new()).is_empty());
+
+    let mut instrument_catalog = InstrumentCatalog::new();
+    instrument_catalog.insert(
+        InstrumentSpec {
+            source_exchange: source_exchange_coinbase,
+            symbol: String::from("ETH-USD"),
+            instrument,
+            status: InstrumentStatus::CancelOnly,
+            trading_disabled: false,
+            quote_increment: fixed_point("0.01"),
+            base_increment: fixed_point("0.00000001"),
+            minimum_order_size: None,
+            maximum_order_size: None,
+            minimum_notional: None,
+        }
+    );
+    assert_eq!(config.disable_untradable_venues(&instrument_catalog), vec![(instrument, Venue::Coinbase)]);
+
+    let source_exchanges: Vec<ExchangeId> = config.instruments[0]
+        .exchange_adapters()
+        .iter()
+        .map(|exchange_adapter| exchange_adapter.source_exchange())
+        .collect();
+    assert_eq!(source_exchanges, vec![ExchangeId::intern("GEMINI")]);
+}
+
+
+#[test]
+fn invalid_config_test() {
+
+    let duplicate_instrument = r#"{"instruments": [
+        {"base_asset": "BTC", "quote_asset": "USD", "venues": []},
+        {"base_asset": "BTC", "quote_asset": "USD", "venues": []}
+    ]}"#;
+    assert!(matches!(Config::parse(duplicate_instrument), Err(ConfigError::Invalid(_))));
+
+    let duplicate_symbol = r#"{"instruments": [
+        {"base_asset": "BTC", "quote_asset": "USD", "venues": [{"venue": "GEMINI", "symbol": "btcusd"}]},
+        {"base_asset": "BTC", "quote_asset": "USDT", "venues": [{"venue": "GEMINI", "symbol": "BTCUSD"}]}
+    ]}"#;
+    assert!(matches!(Config::parse(duplicate_symbol), Err(ConfigError::Instrument(InstrumentError::ConflictingSymbol { .. }))));
+
+    let duplicate_venue = r#"{"instruments": [
+        {"base_asset": "BTC", "quote_asset": "USD", "venues": [
+            {"venue": "KRAKEN", "symbol": "XXBTZUSD"},
+            {"venue": "KRAKEN", "symbol": "XBTUSD"}
+        ]}
+    ]}"#;
+    assert!(matches!(Config::parse(duplicate_venue), Err(ConfigError::Invalid(_))));
+
+    let unknown_venue = r#"{"instruments": [
+        {"base_asset": "BTC", "quote_asset": "USD", "venues": [
+            {"venue": "BINANCE", "symbol": "BTCUSDT"}
+        ]}
+    ]}"#;
+    assert!(matches!(Config::parse(unknown_venue), Err(ConfigError::Json(_))));
+
+    let invalid_target_volume = r#"{"instruments": [
+        {"base_asset": "BTC", "quote_asset": "USD", "target_volume": "ten", "venues": []}
+    ]}"#;
+    assert!(matches!(Config::parse(invalid_target_volume), Err(ConfigError::Json(_))));
+}
+
+
+#[test]
+fn example_config_test() {
+
+    // The example config in the root of the repository
+    let config = Config::load(Path::new("config.json")).unwrap();
+    let instruments: Vec<Instrument> = config.instruments.iter().map(|instrument_config| instrument_config.instrument()).collect();
+    let expected_instruments = vec![
+        Instrument::intern("BTC", "USD"),
+        Instrument::intern("ETH", "USD"),
+        Instrument::intern("SOL", "USD"),
+    ];
+    assert_eq!(instruments, expected_instruments);
+
+    // The default config is the BTC-USD instrument of the example config
+    let default_config = Config::default();
+    assert_eq!(default_config.instruments[0].instrument(), config.instruments[0].instrument());
+    let default_urls: Vec<String> = default_config.instruments[0]
+        .exchange_adapters()
+        .iter()
+        .map(|exchange_adapter| exchange_adapter.url())
+        .collect();
+    let urls: Vec<String> = config.instruments[0]
+        .exchange_adapters()
+        .iter()
+        .map(|exchange_adapter| exchange_adapter.url())
+        .collect();
+    assert_eq!(default_urls, urls);
+}
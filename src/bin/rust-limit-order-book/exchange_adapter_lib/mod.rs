@@ -0,0 +1,387 @@
+
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::de;
+
+use reqwest::header::USER_AGENT;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderBookError;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+
+use crate::instrument_lib::InstrumentError;
+use crate::instrument_lib::InstrumentRegistry;
+use crate::instrument_lib::InstrumentSpec;
+
+use crate::recorder_lib::MANIFEST_FILENAME;
+use crate::recorder_lib::RecorderError;
+use crate::recorder_lib::read_manifest;
+use crate::recorder_lib::read_response_text;
+
+
+/// The price levels of the book of one exchange for one instrument, in the form
+/// used by every exchange adapter.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Snapshot {
+    pub bids: Vec<(Price, Quantity)>,
+    pub asks: Vec<(Price, Quantity)>,
+}
+
+/// A response from the REST order book endpoint of one exchange, together
+/// with the details which are recorded alongside it.
+#[derive(Debug, Clone)]
+pub struct FetchedResponse {
+    pub source_exchange: ExchangeId,
+    pub symbol: String,
+    pub dump_filename: String,
+    pub fetch_time: chrono::DateTime<chrono::offset::Utc>,
+    pub http_status: u16,
+    /// The time from sending the request to receiving the whole body.
+    pub latency: Duration,
+    pub response_text: String,
+}
+
+#[derive(Debug)]
+pub enum ExchangeAdapterError {
+    Io(std::io::Error),
+    /// The request failed, or its response body could not be read.
+    Http(reqwest::Error),
+    Json(serde_json::Error),
+    /// A response which is valid JSON but which does not contain a book.
+    InvalidResponse(String),
+    OrderBook(OrderBookError),
+    Instrument(InstrumentError),
+}
+
+impl std::error::Error for ExchangeAdapterError {
+
+}
+
+impl fmt::Display for ExchangeAdapterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeAdapterError::Io(error) => {
+                write!(f, "failed to read response: {error}")
+            },
+            ExchangeAdapterError::Http(error) => {
+                write!(f, "request failed: {error}")
+            },
+            ExchangeAdapterError::Json(error) => {
+                write!(f, "failed to deserialize response: {error}")
+            },
+            ExchangeAdapterError::InvalidResponse(message) => {
+                write!(f, "invalid response: {message}")
+            },
+            ExchangeAdapterError::OrderBook(error) => {
+                write!(f, "order book error: {error}")
+            },
+            ExchangeAdapterError::Instrument(error) => {
+                write!(f, "instrument error: {error}")
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for ExchangeAdapterError {
+    fn from(error: std::io::Error) -> ExchangeAdapterError {
+        ExchangeAdapterError::Io(error)
+    }
+}
+
+impl From<reqwest::Error> for ExchangeAdapterError {
+    fn from(error: reqwest::Error) -> ExchangeAdapterError {
+        ExchangeAdapterError::Http(error)
+    }
+}
+
+impl From<serde_json::Error> for ExchangeAdapterError {
+    fn from(error: serde_json::Error) -> ExchangeAdapterError {
+        ExchangeAdapterError::Json(error)
+    }
+}
+
+impl From<RecorderError> for ExchangeAdapterError {
+    fn from(error: RecorderError) -> ExchangeAdapterError {
+        match error {
+            RecorderError::Io(error) => {
+                ExchangeAdapterError::Io(error)
+            },
+            RecorderError::Json(error) => {
+                ExchangeAdapterError::Json(error)
+            },
+        }
+    }
+}
+
+impl From<OrderBookError> for ExchangeAdapterError {
+    fn from(error: OrderBookError) -> ExchangeAdapterError {
+        ExchangeAdapterError::OrderBook(error)
+    }
+}
+
+impl From<InstrumentError> for ExchangeAdapterError {
+    fn from(error: InstrumentError) -> ExchangeAdapterError {
+        ExchangeAdapterError::Instrument(error)
+    }
+}
+
+/// Fetches the REST order book of one exchange and loads it into a book.
+///
+/// An implementation only has to say where the book is and how to parse it.
+pub trait ExchangeAdapter {
+    fn source_exchange(&self) -> ExchangeId;
+
+    /// The name of the instrument on this exchange, for example `BTC-USD`.
+    fn symbol(&self) -> String;
+
+    /// The URL of the REST order book endpoint.
+    fn url(&self) -> String;
+
+    /// The file which `dump_response_text_to_file` writes to.
+    fn dump_filename(&self) -> String;
+
+    /// The URL of the product catalog of the exchange, which lists the
+    /// metadata of the instrument of `symbol`.
+    fn catalog_url(&self) -> String;
+
+    /// Parse a response from `catalog_url`. The catalog of some exchanges
+    /// lists every instrument, not just the instrument of `symbol`.
+    fn parse_catalog(&self, response_text: &str) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError>;
+
+    /// Parse a response into the book of the instrument of `symbol`.
+    fn parse_snapshot(&self, response_text: &str, instrument_registry: &InstrumentRegistry) -> Result<Snapshot, ExchangeAdapterError>;
+
+    /// The canonical instrument of `symbol`.
+    fn instrument(&self, instrument_registry: &InstrumentRegistry) -> Result<Instrument, ExchangeAdapterError> {
+        Ok(instrument_registry.resolve(self.source_exchange(), &self.symbol())?)
+    }
+
+    fn fetch(&self, client: &reqwest::blocking::Client, user_agent: &str) -> Result<String, ExchangeAdapterError> {
+        Ok(self.fetch_response(client, user_agent)?.response_text)
+    }
+
+    /// Fetch the book. A response with an unsuccessful HTTP status is still
+    /// returned, so that it can be recorded, but a request which fails or
+    /// whose body cannot be read is an error.
+    fn fetch_response(&self, client: &reqwest::blocking::Client, user_agent: &str) -> Result<FetchedResponse, ExchangeAdapterError> {
+        let source_exchange = self.source_exchange();
+        let url = self.url();
+        println!("{source_exchange} URL: {url}");
+
+        let fetch_time = chrono::offset::Utc::now();
+        let start = Instant::now();
+
+        let response = client
+            .get(url)
+            .header("Content-Type", "application/json")
+            .header(USER_AGENT, user_agent)
+            .send()?;
+        let http_status = response.status();
+        println!("{source_exchange} Response Status Code: {http_status}");
+
+        let response_text = response.text()?;
+
+        Ok(
+            FetchedResponse {
+                source_exchange,
+                symbol: self.symbol(),
+                dump_filename: self.dump_filename(),
+                fetch_time,
+                http_status: http_status.as_u16(),
+                latency: start.elapsed(),
+                response_text,
+            }
+        )
+    }
+
+    fn fetch_catalog(&self, client: &reqwest::blocking::Client, user_agent: &str) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError> {
+        let source_exchange = self.source_exchange();
+        let url = self.catalog_url();
+        println!("{source_exchange} Catalog URL: {url}");
+
+        // Unlike `fetch_response`, an unsuccessful HTTP status is also an
+        // error, since there is nothing to record
+        let response = client
+            .get(url)
+            .header("Content-Type", "application/json")
+            .header(USER_AGENT, user_agent)
+            .send()?;
+        let http_status = response.status();
+        let response_text = response.text()?;
+        if !http_status.is_success() {
+            return Err(ExchangeAdapterError::InvalidResponse(format!("catalog request failed with status {http_status}")));
+        }
+        self.parse_catalog(&response_text)
+    }
+
+    fn dump_response_text_to_file(&self, response_text: &str) {
+        let filename = self.dump_filename();
+        let mut file = std::fs::File::create(&filename).unwrap_or_else(|error| panic!("failed to create file {filename}: {error}"));
+        file.write_all(response_text.as_bytes()).expect("failed to write data to file");
+    }
+
+    /// Parse a response and replace the volume of this exchange for its
+    /// instrument with it. The book is left unchanged if the response cannot be
+    /// parsed or the symbol is not in `instrument_registry`.
+    fn load_book(
+        &self,
+        response_text: &str,
+        instrument_registry: &InstrumentRegistry,
+        limit_order_book: &mut MultiTickerLimitOrderBook,
+    ) -> Result<(), ExchangeAdapterError> {
+        let instrument = self.instrument(instrument_registry)?;
+        let snapshot = self.parse_snapshot(response_text, instrument_registry)?;
+        limit_order_book.replace_exchange_snapshot(
+            instrument,
+            self.source_exchange(),
+            &snapshot.bids,
+            &snapshot.asks,
+        )?;
+        Ok(())
+    }
+
+    /// Load a response which was written by `dump_response_text_to_file` or
+    /// by a `Recorder`. Files ending in `.gz` are decompressed.
+    fn load_book_from_file(
+        &self,
+        path: &Path,
+        instrument_registry: &InstrumentRegistry,
+        limit_order_book: &mut MultiTickerLimitOrderBook,
+    ) -> Result<(), ExchangeAdapterError> {
+        let response_text = read_response_text(path)?;
+        self.load_book(&response_text, instrument_registry, limit_order_book)
+    }
+}
+
+/// The captures in `replay_directory`, oldest first.
+///
+/// A directory which holds dumped responses itself is a single capture.
+/// Otherwise each subdirectory is one capture. Subdirectories are named by
+/// the time of the capture, for example `20240801T120000Z`, so that sorting
+/// by name sorts by time.
+pub fn capture_directories(replay_directory: &Path) -> Result<Vec<PathBuf>, ExchangeAdapterError> {
+    let mut capture_directories = Vec::new();
+    for entry in std::fs::read_dir(replay_directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            capture_directories.push(path);
+        }
+    }
+    if capture_directories.is_empty() {
+        capture_directories.push(replay_directory.to_path_buf());
+    }
+    capture_directories.sort();
+    Ok(capture_directories)
+}
+
+/// Load every dumped response in `capture_directory` into `limit_order_book`,
+/// using the parser of each adapter. Adapters without a response in the
+/// capture are skipped.
+///
+/// A capture written by a `Recorder` is read through its manifest, and
+/// responses which were recorded with an unsuccessful HTTP status are skipped.
+/// Otherwise the file of each adapter is named by its `dump_filename`.
+///
+/// Returns the exchanges which were loaded. Stops at the first response which
+/// cannot be read or parsed, leaving the exchanges before it loaded.
+pub fn load_capture(
+    capture_directory: &Path,
+    exchange_adapters: &[Box<dyn ExchangeAdapter>],
+    instrument_registry: &InstrumentRegistry,
+    limit_order_book: &mut MultiTickerLimitOrderBook,
+) -> Result<Vec<ExchangeId>, ExchangeAdapterError> {
+    let manifest = if capture_directory.join(MANIFEST_FILENAME).is_file() {
+        Some(read_manifest(capture_directory)?)
+    } else {
+        None
+    };
+
+    let mut source_exchanges = Vec::new();
+    for exchange_adapter in exchange_adapters {
+        let path = match &manifest {
+            Some(manifest) => {
+                let source_exchange = exchange_adapter.source_exchange();
+                let symbol = exchange_adapter.symbol();
+                let manifest_entry = manifest.entries.iter().find(
+                    |manifest_entry| {
+                        manifest_entry.source_exchange == source_exchange.name() && manifest_entry.symbol == symbol
+                    }
+                );
+                match manifest_entry {
+                    Some(manifest_entry) if manifest_entry.is_success() => {
+                        capture_directory.join(&manifest_entry.filename)
+                    },
+                    _ => {
+                        continue;
+                    },
+                }
+            },
+            None => {
+                capture_directory.join(exchange_adapter.dump_filename())
+            },
+        };
+        if !path.is_file() {
+            continue;
+        }
+        exchange_adapter.load_book_from_file(&path, instrument_registry, limit_order_book)?;
+        source_exchanges.push(exchange_adapter.source_exchange());
+    }
+    Ok(source_exchanges)
+}
+
+/// Deserialize a price or volume which an exchange sends as a string.
+pub fn de_from_str<'de, D>(deserializer: D) -> Result<FixedPoint, D::Error>
+    where D: Deserializer<'de>
+{
+    let s = <&str>::deserialize(deserializer)?;
+    FixedPoint::from_str(s).map_err(de::Error::custom)
+}
+
+/// Deserialize an optional price or volume which an exchange sends as a string.
+/// A missing field, `null` or an empty string is `None`.
+pub fn de_option_from_str<'de, D>(deserializer: D) -> Result<Option<FixedPoint>, D::Error>
+    where D: Deserializer<'de>
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("") => {
+            Ok(None)
+        },
+        Some(s) => {
+            FixedPoint::from_str(s).map(Some).map_err(de::Error::custom)
+        },
+    }
+}
+
+/// Deserialize a decimal which an exchange sends as a JSON number, or as a
+/// string, possibly in exponent notation such as `1E-8`.
+pub fn de_from_number<'de, D>(deserializer: D) -> Result<FixedPoint, D::Error>
+    where D: Deserializer<'de>
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(number) => {
+            FixedPoint::from_str_with_exponent(&number.to_string()).map_err(de::Error::custom)
+        },
+        serde_json::Value::String(s) => {
+            FixedPoint::from_str_with_exponent(&s).map_err(de::Error::custom)
+        },
+        value => {
+            Err(de::Error::custom(format!("{value} is not a decimal number")))
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests;
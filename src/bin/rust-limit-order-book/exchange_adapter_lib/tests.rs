@@ -0,0 +1,219 @@
+
+use super::*;
+
+use crate::coinbase_lib::CoinbaseAdapter;
+use crate::gemini_lib::GeminiAdapter;
+use crate::kraken_lib::KrakenAdapter;
+use crate::instrument_lib::InstrumentStatus;
+use crate::limit_order_book_lib::OrderSide;
+use crate::test_support_lib::fixed_point;
+use crate::test_support_lib::instrument_registry;
+
+use std::collections::BTreeMap;
+
+
+const COINBASE_RESPONSE: &str = r#"{"bids":[["64000.01","0.5",3],["63999.5","1.25",1]],"asks":[["64001.00","0.75",2]],"sequence":81234567890,"auction_mode":false,"auction":null,"time":"2024-08-01T12:00:00.123456Z"}"#;
+const GEMINI_RESPONSE: &str = r#"{"bids":[{"price":"63990.00","amount":"1.0","timestamp":"1722513600"}],"asks":[{"price":"64010.00","amount":"0.5","timestamp":"1722513600"},{"price":"64020.00","amount":"1.5","timestamp":"1722513600"}]}"#;
+const COINBASE_PRODUCTS_RESPONSE: &str = r#"[{"id":"BTC-USD","base_currency":"BTC","quote_currency":"USD","quote_increment":"0.01","base_increment":"0.00000001","display_name":"BTC-USD","min_market_funds":"1","margin_enabled":false,"post_only":false,"limit_only":false,"cancel_only":false,"status":"online","status_message":"","trading_disabled":false,"fx_stablecoin":false,"max_slippage_percentage":"0.02000000","auction_mode":false,"high_bid_limit_percentage":""},{"id":"ETH-BTC","base_currency":"ETH","quote_currency":"BTC","quote_increment":"0.00001","base_increment":"0.00000001","base_min_size":"0.01","base_max_size":"1000","min_market_funds":"","post_only":false,"limit_only":false,"cancel_only":true,"status":"online","trading_disabled":false}]"#;
+const GEMINI_SYMBOL_DETAILS_RESPONSE: &str = r#"{"symbol":"BTCUSD","base_currency":"BTC","quote_currency":"USD","tick_size":1E-8,"quote_increment":0.01,"min_order_size":"0.00001","status":"open","wrap_enabled":false,"product_type":"spot","contract_type":"vanilla","contract_price_currency":"USD"}"#;
+const KRAKEN_ASSET_PAIRS_RESPONSE: &str = r#"{"error":[],"result":{"XXBTZUSD":{"altname":"XBTUSD","wsname":"XBT/USD","aclass_base":"currency","base":"XXBT","aclass_quote":"currency","quote":"ZUSD","lot":"unit","cost_decimals":5,"pair_decimals":1,"lot_decimals":8,"lot_multiplier":1,"ordermin":"0.0001","costmin":"0.5","tick_size":"0.1","status":"online"}}}"#;
+const KRAKEN_RESPONSE: &str = r#"{"error":[],"result":{"XXBTZUSD":{"asks":[["64005.00000","0.500",1722513600]],"bids":[["64004.90000","0.250",1722513600],["64004.00000","1.500",1722513600]]}}}"#;
+
+
+
+#[test]
+fn parse_snapshot_test() {
+
+    let instrument_registry = instrument_registry();
+
+    let coinbase_adapter = CoinbaseAdapter::new("BTC-USD", ExchangeId::intern("COINBASE"));
+    let snapshot = coinbase_adapter.parse_snapshot(COINBASE_RESPONSE, &instrument_registry).unwrap();
+    let expected_snapshot = Snapshot {
+        bids: vec![(fixed_point("64000.01"), fixed_point("0.5")), (fixed_point("63999.5"), fixed_point("1.25"))],
+        asks: vec![(fixed_point("64001.00"), fixed_point("0.75"))],
+    };
+    assert_eq!(snapshot, expected_snapshot);
+
+    let gemini_adapter = GeminiAdapter::new("btcusd", ExchangeId::intern("GEMINI"));
+    let snapshot = gemini_adapter.parse_snapshot(GEMINI_RESPONSE, &instrument_registry).unwrap();
+    let expected_snapshot = Snapshot {
+        bids: vec![(fixed_point("63990.00"), fixed_point("1.0"))],
+        asks: vec![(fixed_point("64010.00"), fixed_point("0.5")), (fixed_point("64020.00"), fixed_point("1.5"))],
+    };
+    assert_eq!(snapshot, expected_snapshot);
+
+    // Kraken keys the result by the legacy name of the pair, whichever name
+    // was requested
+    let expected_snapshot = Snapshot {
+        bids: vec![(fixed_point("64004.9"), fixed_point("0.25")), (fixed_point("64004.0"), fixed_point("1.5"))],
+        asks: vec![(fixed_point("64005.0"), fixed_point("0.5"))],
+    };
+    for pair in ["XXBTZUSD", "XBTUSD"] {
+        let kraken_adapter = KrakenAdapter::new(pair, ExchangeId::intern("KRAKEN"));
+        assert_eq!(kraken_adapter.parse_snapshot(KRAKEN_RESPONSE, &instrument_registry).unwrap(), expected_snapshot);
+    }
+
+    let kraken_adapter = KrakenAdapter::new("XETHZUSD", ExchangeId::intern("KRAKEN"));
+    assert!(matches!(kraken_adapter.parse_snapshot(KRAKEN_RESPONSE, &instrument_registry), Err(ExchangeAdapterError::InvalidResponse(_))));
+    assert!(matches!(kraken_adapter.parse_snapshot(r#"{"error":["EQuery:Unknown asset pair"]}"#, &instrument_registry), Err(ExchangeAdapterError::Json(_))));
+    assert!(matches!(kraken_adapter.parse_snapshot(r#"{"error":["EQuery:Unknown asset pair"],"result":{}}"#, &instrument_registry), Err(ExchangeAdapterError::InvalidResponse(_))));
+
+    // A pair which is not in the registry is an error rather than being skipped
+    let kraken_response = KRAKEN_RESPONSE.replace("XXBTZUSD", "XXBTZGBP");
+    assert!(
+        matches!(
+            kraken_adapter.parse_snapshot(&kraken_response, &instrument_registry),
+            Err(ExchangeAdapterError::Instrument(InstrumentError::UnknownSymbol { .. }))
+        )
+    );
+    let kraken_adapter = KrakenAdapter::new("XXBTZGBP", ExchangeId::intern("KRAKEN"));
+    assert!(matches!(kraken_adapter.parse_snapshot(&kraken_response, &instrument_registry), Err(ExchangeAdapterError::Instrument(_))));
+}
+
+
+#[test]
+fn parse_catalog_test() {
+
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_gemini = ExchangeId::intern("GEMINI");
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
+    let btc_usd = Instrument::intern("BTC", "USD");
+
+    let coinbase_adapter = CoinbaseAdapter::new("BTC-USD", source_exchange_coinbase);
+    assert_eq!(coinbase_adapter.catalog_url(), "https://api.exchange.coinbase.com/products");
+    let instrument_specs = coinbase_adapter.parse_catalog(COINBASE_PRODUCTS_RESPONSE).unwrap();
+    assert_eq!(
+        instrument_specs[0],
+        InstrumentSpec {
+            source_exchange: source_exchange_coinbase,
+            symbol: String::from("BTC-USD"),
+            instrument: btc_usd,
+            status: InstrumentStatus::Online,
+            trading_disabled: false,
+            quote_increment: fixed_point("0.01"),
+            base_increment: fixed_point("0.00000001"),
+            minimum_order_size: None,
+            maximum_order_size: None,
+            minimum_notional: Some(fixed_point("1")),
+        }
+    );
+    // The flags of a product override its status
+    assert_eq!(instrument_specs[1].instrument, Instrument::intern("ETH", "BTC"));
+    assert_eq!(instrument_specs[1].status, InstrumentStatus::CancelOnly);
+    assert_eq!(instrument_specs[1].minimum_order_size, Some(fixed_point("0.01")));
+    assert_eq!(instrument_specs[1].maximum_order_size, Some(fixed_point("1000")));
+    assert_eq!(instrument_specs[1].minimum_notional, None);
+    assert!(!instrument_specs[1].is_trading());
+
+    // Gemini sends its increments as numbers in exponent notation
+    let gemini_adapter = GeminiAdapter::new("btcusd", source_exchange_gemini);
+    assert_eq!(gemini_adapter.catalog_url(), "https://api.gemini.com/v1/symbols/details/btcusd");
+    let instrument_specs = gemini_adapter.parse_catalog(GEMINI_SYMBOL_DETAILS_RESPONSE).unwrap();
+    assert_eq!(instrument_specs.len(), 1);
+    assert_eq!(instrument_specs[0].instrument, btc_usd);
+    assert_eq!(instrument_specs[0].status, InstrumentStatus::Online);
+    assert_eq!(instrument_specs[0].quote_increment, fixed_point("0.01"));
+    assert_eq!(instrument_specs[0].base_increment, fixed_point("0.00000001"));
+    assert_eq!(instrument_specs[0].minimum_order_size, Some(fixed_point("0.00001")));
+
+    // Kraken's asset codes are mapped to canonical assets
+    let kraken_adapter = KrakenAdapter::new("XXBTZUSD", source_exchange_kraken);
+    assert_eq!(kraken_adapter.catalog_url(), "https://api.kraken.com/0/public/AssetPairs?pair=XXBTZUSD");
+    let instrument_specs = kraken_adapter.parse_catalog(KRAKEN_ASSET_PAIRS_RESPONSE).unwrap();
+    assert_eq!(instrument_specs.len(), 1);
+    assert_eq!(instrument_specs[0].symbol, "XXBTZUSD");
+    assert_eq!(instrument_specs[0].instrument, btc_usd);
+    assert_eq!(instrument_specs[0].quote_increment, fixed_point("0.1"));
+    assert_eq!(instrument_specs[0].base_increment, fixed_point("0.00000001"));
+    assert_eq!(instrument_specs[0].minimum_order_size, Some(fixed_point("0.0001")));
+    assert_eq!(instrument_specs[0].minimum_notional, Some(fixed_point("0.5")));
+
+    assert!(kraken_adapter.parse_catalog(r#"{"error":["EQuery:Unknown asset pair"],"result":{}}"#).is_err());
+}
+
+
+#[test]
+fn load_book_test() {
+
+    let ticker = Instrument::intern("BTC", "USD");
+    let instrument_registry = instrument_registry();
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_gemini = ExchangeId::intern("GEMINI");
+
+    let exchange_adapters: Vec<(Box<dyn ExchangeAdapter>, &str)> = vec![
+        (Box::new(CoinbaseAdapter::new("BTC-USD", source_exchange_coinbase)), COINBASE_RESPONSE),
+        (Box::new(GeminiAdapter::new("btcusd", source_exchange_gemini)), GEMINI_RESPONSE),
+    ];
+
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+    for (exchange_adapter, response_text) in &exchange_adapters {
+        exchange_adapter.load_book(response_text, &instrument_registry, &mut limit_order_book).unwrap();
+    }
+    // Loading the same response again replaces the volume instead of adding to it
+    exchange_adapters[0].0.load_book(COINBASE_RESPONSE, &instrument_registry, &mut limit_order_book).unwrap();
+
+    let total_volume = limit_order_book.total_volume_by_source_exchange(ticker, &OrderSide::SELL).unwrap();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (source_exchange_coinbase, fixed_point("0.75")),
+            (source_exchange_gemini, fixed_point("2.0")),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+
+    // A response which cannot be parsed leaves the book unchanged
+    assert!(exchange_adapters[1].0.load_book("{}", &instrument_registry, &mut limit_order_book).is_err());
+    assert_eq!(limit_order_book.total_volume_by_source_exchange(ticker, &OrderSide::SELL), Ok(expected_total_volume));
+}
+
+
+#[test]
+fn replay_captures_test() {
+
+    let ticker = Instrument::intern("BTC", "USD");
+    let instrument_registry = instrument_registry();
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
+
+    let exchange_adapters: Vec<Box<dyn ExchangeAdapter>> = vec![
+        Box::new(CoinbaseAdapter::new("BTC-USD", source_exchange_coinbase)),
+        Box::new(KrakenAdapter::new("XXBTZUSD", source_exchange_kraken)),
+    ];
+
+    let replay_directory = std::env::temp_dir().join(format!("replay_captures_test_{}", std::process::id()));
+    let first_capture_directory = replay_directory.join("20240801T120000Z");
+    let second_capture_directory = replay_directory.join("20240801T120500Z");
+    std::fs::create_dir_all(&first_capture_directory).unwrap();
+    std::fs::create_dir_all(&second_capture_directory).unwrap();
+    std::fs::write(first_capture_directory.join(exchange_adapters[0].dump_filename()), COINBASE_RESPONSE).unwrap();
+    std::fs::write(first_capture_directory.join(exchange_adapters[1].dump_filename()), KRAKEN_RESPONSE).unwrap();
+    // The second capture only has a new Kraken book
+    let kraken_response = KRAKEN_RESPONSE.replace("\"0.500\"", "\"2.000\"");
+    std::fs::write(second_capture_directory.join(exchange_adapters[1].dump_filename()), kraken_response).unwrap();
+
+    // Captures are replayed oldest first
+    assert_eq!(capture_directories(&replay_directory).unwrap(), vec![first_capture_directory.clone(), second_capture_directory.clone()]);
+
+    // A directory of dumped responses is a single capture
+    assert_eq!(capture_directories(&first_capture_directory).unwrap(), vec![first_capture_directory.clone()]);
+
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+    let source_exchanges = load_capture(&first_capture_directory, &exchange_adapters, &instrument_registry, &mut limit_order_book).unwrap();
+    assert_eq!(source_exchanges, vec![source_exchange_coinbase, source_exchange_kraken]);
+    let source_exchanges = load_capture(&second_capture_directory, &exchange_adapters, &instrument_registry, &mut limit_order_book).unwrap();
+    assert_eq!(source_exchanges, vec![source_exchange_kraken]);
+
+    let expected_total_volume = BTreeMap::from(
+        [
+            (source_exchange_coinbase, fixed_point("0.75")),
+            (source_exchange_kraken, fixed_point("2.0")),
+        ]
+    );
+    assert_eq!(limit_order_book.total_volume_by_source_exchange(ticker, &OrderSide::SELL), Ok(expected_total_volume));
+
+    // A capture with a response which cannot be parsed is an error
+    std::fs::write(second_capture_directory.join(exchange_adapters[0].dump_filename()), "{}").unwrap();
+    assert!(load_capture(&second_capture_directory, &exchange_adapters, &instrument_registry, &mut limit_order_book).is_err());
+
+    std::fs::remove_dir_all(&replay_directory).unwrap();
+}
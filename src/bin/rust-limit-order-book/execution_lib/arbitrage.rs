@@ -0,0 +1,213 @@
+
+use std::collections::BTreeMap;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::OrderBookError;
+
+use super::FeeModel;
+use super::Liquidity;
+use super::NotionalAfterFees;
+use super::VenueConstraints;
+
+
+/// Buying on one exchange and selling on another, where the bids of the
+/// selling exchange are above the asks of the buying exchange.
+///
+/// The fees of both exchanges can outweigh the gross profit, so `net_profit`
+/// may be zero or negative. Callers which only want profitable trades filter
+/// on it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ArbitrageOpportunity {
+    pub buy_source_exchange: ExchangeId,
+    pub sell_source_exchange: ExchangeId,
+    /// The lowest ask of `buy_source_exchange`.
+    pub buy_price: Price,
+    /// The highest bid of `sell_source_exchange`.
+    pub sell_price: Price,
+    /// The volume which can be bought and sold while the bids are above the
+    /// asks, within the balances of each exchange. Zero if the balances do not
+    /// allow any volume.
+    pub volume: Quantity,
+    pub buy_notional_after_fees: NotionalAfterFees,
+    pub sell_notional_after_fees: NotionalAfterFees,
+    pub gross_profit: Notional,
+    /// The profit after the taker fees of both exchanges.
+    pub net_profit: Notional,
+}
+
+/// Find every pair of exchanges where the highest bid of one exchange is above
+/// the lowest ask of another, highest net profit first, including pairs whose
+/// net profit after taker fees is zero or negative.
+///
+/// The buy side of each pair is limited by the quote balance of the buying
+/// exchange, including fees, and the sell side by the base balance of the
+/// selling exchange. Volume bought with the quote balance is rounded down to
+/// the volume increment of `ticker`. Each pair is sized as if it was the only
+/// one executed. Returns an error if the calculation overflows.
+pub fn find_arbitrage_opportunities(
+    limit_order_book: &MultiTickerLimitOrderBook,
+    ticker: Instrument,
+    fee_model: &FeeModel,
+    venue_constraints: &VenueConstraints,
+) -> Result<Vec<ArbitrageOpportunity>, OrderBookError> {
+    let asks_by_source_exchange = price_levels_by_source_exchange(
+        &limit_order_book.total_volume_by_price_level_and_source_exchange(ticker, &OrderSide::SELL)?,
+        false,
+    );
+    let bids_by_source_exchange = price_levels_by_source_exchange(
+        &limit_order_book.total_volume_by_price_level_and_source_exchange(ticker, &OrderSide::BUY)?,
+        true,
+    );
+
+    let volume_increment = limit_order_book.volume_increment(ticker);
+    let overflow = || OrderBookError::NotionalOverflow { ticker };
+
+    let mut arbitrage_opportunities = Vec::new();
+
+    for (buy_source_exchange, asks) in &asks_by_source_exchange {
+        for (sell_source_exchange, bids) in &bids_by_source_exchange {
+            if buy_source_exchange == sell_source_exchange {
+                continue;
+            }
+            let is_crossed = match (asks.first(), bids.first()) {
+                (Some((buy_price, _)), Some((sell_price, _))) => sell_price > buy_price,
+                _ => false,
+            };
+            if !is_crossed {
+                continue;
+            }
+            let arbitrage_opportunity = arbitrage_opportunity(
+                *buy_source_exchange,
+                asks,
+                *sell_source_exchange,
+                bids,
+                fee_model,
+                venue_constraints,
+                volume_increment,
+            ).ok_or_else(overflow)?;
+            arbitrage_opportunities.push(arbitrage_opportunity);
+        }
+    }
+
+    arbitrage_opportunities.sort_by_key(|arbitrage_opportunity| std::cmp::Reverse(arbitrage_opportunity.net_profit));
+    Ok(arbitrage_opportunities)
+}
+
+/// The price levels of each exchange, best price first.
+fn price_levels_by_source_exchange(
+    total_volume_by_price_level_and_source_exchange: &BTreeMap<Price, BTreeMap<ExchangeId, Quantity>>,
+    highest_price_first: bool,
+) -> BTreeMap<ExchangeId, Vec<(Price, Quantity)>> {
+    let mut price_levels_by_source_exchange: BTreeMap<ExchangeId, Vec<(Price, Quantity)>> = BTreeMap::new();
+    for (price, volume_by_source_exchange) in total_volume_by_price_level_and_source_exchange {
+        for (source_exchange, volume) in volume_by_source_exchange {
+            if volume.is_positive() {
+                price_levels_by_source_exchange.entry(*source_exchange).or_default().push((*price, *volume));
+            }
+        }
+    }
+    if highest_price_first {
+        for price_levels in price_levels_by_source_exchange.values_mut() {
+            price_levels.reverse();
+        }
+    }
+    price_levels_by_source_exchange
+}
+
+/// The opportunity of a pair of exchanges whose best bid and ask cross. `None`
+/// if the calculation overflows.
+fn arbitrage_opportunity(
+    buy_source_exchange: ExchangeId,
+    asks: &[(Price, Quantity)],
+    sell_source_exchange: ExchangeId,
+    bids: &[(Price, Quantity)],
+    fee_model: &FeeModel,
+    venue_constraints: &VenueConstraints,
+    volume_increment: Quantity,
+) -> Option<ArbitrageOpportunity> {
+    let (buy_price, _) = *asks.first()?;
+    let (sell_price, _) = *bids.first()?;
+
+    let buy_fee_rate = fee_model.fee_rate(buy_source_exchange, Liquidity::Taker);
+    let mut remaining_quote_balance = match venue_constraints.quote_balance(buy_source_exchange) {
+        Some(quote_balance) => Some(quote_balance.checked_sub(fee_model.fixed_fee(buy_source_exchange))?),
+        None => None,
+    };
+    let mut remaining_base_balance = venue_constraints.base_balance(sell_source_exchange);
+
+    let mut volume = Quantity::ZERO;
+    let mut buy_notional = Notional::ZERO;
+    let mut sell_notional = Notional::ZERO;
+
+    let mut asks = asks.iter().copied();
+    let mut bids = bids.iter().copied();
+    let mut ask = asks.next();
+    let mut bid = bids.next();
+
+    while let (Some((ask_price, ask_volume)), Some((bid_price, bid_volume))) = (ask, bid) {
+        if bid_price <= ask_price {
+            break;
+        }
+        let ask_price_after_fees = ask_price.checked_mul(FixedPoint::from(1).checked_add(buy_fee_rate)?)?;
+        let mut volume_to_take = std::cmp::min(ask_volume, bid_volume);
+        if let Some(remaining_quote_balance) = remaining_quote_balance {
+            let affordable_volume = if remaining_quote_balance.is_positive() {
+                remaining_quote_balance
+                    .checked_div_with_scale(ask_price_after_fees, volume_increment.scale())?
+                    .checked_floor_to_multiple_of(volume_increment)?
+            } else {
+                Quantity::ZERO
+            };
+            volume_to_take = std::cmp::min(volume_to_take, affordable_volume);
+        }
+        if let Some(remaining_base_balance) = remaining_base_balance {
+            volume_to_take = std::cmp::min(volume_to_take, remaining_base_balance);
+        }
+        if !volume_to_take.is_positive() {
+            break;
+        }
+
+        volume = volume.checked_add(volume_to_take)?;
+        buy_notional = buy_notional.checked_add(volume_to_take.checked_mul(ask_price)?)?;
+        sell_notional = sell_notional.checked_add(volume_to_take.checked_mul(bid_price)?)?;
+        if let Some(remaining_quote_balance) = remaining_quote_balance.as_mut() {
+            *remaining_quote_balance = remaining_quote_balance.checked_sub(volume_to_take.checked_mul(ask_price_after_fees)?)?;
+        }
+        if let Some(remaining_base_balance) = remaining_base_balance.as_mut() {
+            *remaining_base_balance = remaining_base_balance.checked_sub(volume_to_take)?;
+        }
+
+        ask = if volume_to_take == ask_volume { asks.next() } else { Some((ask_price, ask_volume.checked_sub(volume_to_take)?)) };
+        bid = if volume_to_take == bid_volume { bids.next() } else { Some((bid_price, bid_volume.checked_sub(volume_to_take)?)) };
+    }
+
+    // Fees are only charged on volume which is traded
+    let buy_fee = fee_model.fee(buy_source_exchange, buy_notional, Liquidity::Taker);
+    let sell_fee = fee_model.fee(sell_source_exchange, sell_notional, Liquidity::Taker);
+    let buy_notional_after_fees = NotionalAfterFees::new(OrderSide::BUY, buy_notional, buy_fee);
+    let sell_notional_after_fees = NotionalAfterFees::new(OrderSide::SELL, sell_notional, sell_fee);
+
+    let gross_profit = sell_notional.checked_sub(buy_notional)?;
+    let net_profit = sell_notional_after_fees.net.checked_sub(buy_notional_after_fees.net)?;
+
+    Some(
+        ArbitrageOpportunity {
+            buy_source_exchange,
+            sell_source_exchange,
+            buy_price,
+            sell_price,
+            volume,
+            buy_notional_after_fees,
+            sell_notional_after_fees,
+            gross_profit,
+            net_profit,
+        }
+    )
+}
@@ -0,0 +1,170 @@
+
+use std::collections::BTreeMap;
+
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::OrderSide;
+
+
+/// Whether an order adds volume to the book or takes volume from it.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// The fee rates which apply once the trailing volume traded on an exchange
+/// reaches `minimum_trailing_volume`. Rates are fractions of the notional, so
+/// `0.006` is 0.6%.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FeeTier {
+    pub minimum_trailing_volume: Notional,
+    pub maker_fee_rate: FixedPoint,
+    pub taker_fee_rate: FixedPoint,
+}
+
+/// The fees charged by one exchange.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FeeSchedule {
+    fee_tiers: Vec<FeeTier>,
+    fixed_fee: Notional,
+}
+
+impl FeeSchedule {
+    /// A schedule with a volume tier for each element of `fee_tiers`, and a
+    /// fixed fee which is charged once per order.
+    pub fn new(mut fee_tiers: Vec<FeeTier>, fixed_fee: Notional) -> FeeSchedule {
+        fee_tiers.sort_by_key(|fee_tier| fee_tier.minimum_trailing_volume);
+        FeeSchedule {
+            fee_tiers,
+            fixed_fee,
+        }
+    }
+
+    /// A schedule with the same rates for any volume and no fixed fee.
+    pub fn flat(maker_fee_rate: FixedPoint, taker_fee_rate: FixedPoint) -> FeeSchedule {
+        let fee_tier = FeeTier {
+            minimum_trailing_volume: Notional::ZERO,
+            maker_fee_rate,
+            taker_fee_rate,
+        };
+        FeeSchedule::new(vec![fee_tier], Notional::ZERO)
+    }
+
+    /// The rate of the highest tier which `trailing_volume` reaches. Zero if
+    /// it does not reach any tier.
+    pub fn fee_rate(&self, liquidity: Liquidity, trailing_volume: Notional) -> FixedPoint {
+        let fee_tier = self.fee_tiers
+            .iter()
+            .rfind(|fee_tier| fee_tier.minimum_trailing_volume <= trailing_volume);
+        match (fee_tier, liquidity) {
+            (Some(fee_tier), Liquidity::Maker) => {
+                fee_tier.maker_fee_rate
+            },
+            (Some(fee_tier), Liquidity::Taker) => {
+                fee_tier.taker_fee_rate
+            },
+            (None, _) => {
+                FixedPoint::ZERO
+            },
+        }
+    }
+
+    pub fn fixed_fee(&self) -> Notional {
+        self.fixed_fee
+    }
+
+    /// The fee for one order with a total notional of `notional`.
+    pub fn fee(&self, notional: Notional, liquidity: Liquidity, trailing_volume: Notional) -> Notional {
+        if notional.is_zero() {
+            return Notional::ZERO;
+        }
+        notional * self.fee_rate(liquidity, trailing_volume) + self.fixed_fee
+    }
+}
+
+/// The fee schedule of each exchange, together with the trailing volume of
+/// the account on each exchange which decides the tier.
+///
+/// Exchanges without a fee schedule are treated as charging no fees.
+#[derive(Debug, Default, Clone)]
+pub struct FeeModel {
+    fee_schedules: BTreeMap<ExchangeId, FeeSchedule>,
+    trailing_volumes: BTreeMap<ExchangeId, Notional>,
+}
+
+impl FeeModel {
+    pub fn new() -> FeeModel {
+        FeeModel::default()
+    }
+
+    pub fn set_fee_schedule(&mut self, source_exchange: ExchangeId, fee_schedule: FeeSchedule) {
+        self.fee_schedules.insert(source_exchange, fee_schedule);
+    }
+
+    pub fn set_trailing_volume(&mut self, source_exchange: ExchangeId, trailing_volume: Notional) {
+        self.trailing_volumes.insert(source_exchange, trailing_volume);
+    }
+
+    pub fn fee_rate(&self, source_exchange: ExchangeId, liquidity: Liquidity) -> FixedPoint {
+        match self.fee_schedules.get(&source_exchange) {
+            Some(fee_schedule) => {
+                fee_schedule.fee_rate(liquidity, self.trailing_volume(source_exchange))
+            },
+            None => {
+                FixedPoint::ZERO
+            },
+        }
+    }
+
+    pub fn fixed_fee(&self, source_exchange: ExchangeId) -> Notional {
+        match self.fee_schedules.get(&source_exchange) {
+            Some(fee_schedule) => {
+                fee_schedule.fixed_fee()
+            },
+            None => {
+                Notional::ZERO
+            },
+        }
+    }
+
+    /// The fee for one order on `source_exchange` with a total notional of `notional`.
+    pub fn fee(&self, source_exchange: ExchangeId, notional: Notional, liquidity: Liquidity) -> Notional {
+        match self.fee_schedules.get(&source_exchange) {
+            Some(fee_schedule) => {
+                fee_schedule.fee(notional, liquidity, self.trailing_volume(source_exchange))
+            },
+            None => {
+                Notional::ZERO
+            },
+        }
+    }
+
+    fn trailing_volume(&self, source_exchange: ExchangeId) -> Notional {
+        self.trailing_volumes.get(&source_exchange).copied().unwrap_or(Notional::ZERO)
+    }
+}
+
+/// A notional before and after fees. For a BUY, `net` is the gross cost plus
+/// fees. For a SELL, `net` is the gross proceeds minus fees.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct NotionalAfterFees {
+    pub gross: Notional,
+    pub fee: Notional,
+    pub net: Notional,
+}
+
+impl NotionalAfterFees {
+    pub fn new(order_side: OrderSide, gross: Notional, fee: Notional) -> NotionalAfterFees {
+        let net = match order_side {
+            OrderSide::BUY => gross + fee,
+            OrderSide::SELL => gross - fee,
+        };
+        NotionalAfterFees {
+            gross,
+            fee,
+            net,
+        }
+    }
+}
@@ -0,0 +1,331 @@
+
+use std::collections::BTreeMap;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::OrderBookError;
+
+use super::FeeModel;
+use super::Liquidity;
+use super::NotionalAfterFees;
+
+
+/// The volume taken from one exchange at one price level.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlannedFill {
+    pub price: Price,
+    pub source_exchange: ExchangeId,
+    pub volume: Quantity,
+    pub notional: Notional,
+}
+
+/// How much of an order is wanted, either in the base currency or in the
+/// quote currency.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FillTarget {
+    /// For example, 10 BTC.
+    Volume(Quantity),
+    /// For example, $1M of BTC.
+    Notional(Notional),
+}
+
+/// The fills which an order would receive if it was executed against the book,
+/// best price first.
+///
+/// Every total is derived from the list of fills, so totals and breakdowns by
+/// exchange always agree with each other.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FillPlan {
+    pub order_side: OrderSide,
+    pub target: FillTarget,
+    pub fills: Vec<PlannedFill>,
+    /// True if the book ran out of volume before the target was reached. A
+    /// notional target whose remainder buys less than one volume increment at
+    /// the next price is not exhausted.
+    pub book_exhausted: bool,
+}
+
+/// The number of decimal places of `FillSummary::average_price`.
+pub const AVERAGE_PRICE_SCALE: u32 = 8;
+
+/// The totals of a `FillPlan`.
+///
+/// If the book does not have enough volume, `book_exhausted` is true and the
+/// other fields describe only the part of the order which can be filled. The
+/// caller decides whether a partial fill is acceptable.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FillSummary {
+    pub order_side: OrderSide,
+    pub target: FillTarget,
+    pub filled_volume: Quantity,
+    pub total_notional: Notional,
+    pub total_fee: Notional,
+    /// For a BUY, the total cost including fees. For a SELL, the total
+    /// proceeds after fees.
+    pub net_notional: Notional,
+    pub unfilled: FillTarget,
+    pub book_exhausted: bool,
+    /// `None` if nothing can be filled.
+    pub average_price: Option<Price>,
+    /// The last price level which is reached. `None` if nothing can be filled.
+    pub worst_price: Option<Price>,
+}
+
+impl FillSummary {
+    pub fn is_completely_filled(&self) -> bool {
+        !self.book_exhausted
+    }
+}
+
+impl FillPlan {
+    pub fn filled_volume(&self) -> Quantity {
+        self.fills.iter().map(|fill| fill.volume).sum()
+    }
+
+    /// The part of the target which is not filled, in the units of the target.
+    ///
+    /// A notional target can leave a small amount unfilled even when the book
+    /// has enough volume, because volume is only taken in whole multiples of
+    /// the volume increment of the instrument.
+    pub fn unfilled(&self) -> FillTarget {
+        match self.target {
+            FillTarget::Volume(target_volume) => {
+                FillTarget::Volume(std::cmp::max(target_volume - self.filled_volume(), Quantity::ZERO))
+            },
+            FillTarget::Notional(target_notional) => {
+                FillTarget::Notional(std::cmp::max(target_notional - self.total_notional(), Notional::ZERO))
+            },
+        }
+    }
+
+    pub fn is_completely_filled(&self) -> bool {
+        !self.book_exhausted
+    }
+
+    /// For a BUY, the total cost. For a SELL, the total proceeds.
+    pub fn total_notional(&self) -> Notional {
+        self.fills.iter().map(|fill| fill.notional).sum()
+    }
+
+    /// The volume weighted average price of the fills, truncated to
+    /// `AVERAGE_PRICE_SCALE` decimal places.
+    pub fn average_price(&self) -> Option<Price> {
+        self.total_notional().checked_div_with_scale(self.filled_volume(), AVERAGE_PRICE_SCALE)
+    }
+
+    /// For a BUY, the highest price paid. For a SELL, the lowest price received.
+    pub fn worst_price(&self) -> Option<Price> {
+        self.fills.last().map(|fill| fill.price)
+    }
+
+    pub fn summary(&self, fee_model: &FeeModel) -> FillSummary {
+        let notional_after_fees = self.notional_after_fees(fee_model);
+        FillSummary {
+            order_side: self.order_side,
+            target: self.target,
+            filled_volume: self.filled_volume(),
+            total_notional: notional_after_fees.gross,
+            total_fee: notional_after_fees.fee,
+            net_notional: notional_after_fees.net,
+            unfilled: self.unfilled(),
+            book_exhausted: self.book_exhausted,
+            average_price: self.average_price(),
+            worst_price: self.worst_price(),
+        }
+    }
+
+    pub fn volume_by_source_exchange(&self) -> BTreeMap<ExchangeId, Quantity> {
+        let mut volume_by_source_exchange = BTreeMap::new();
+        for fill in &self.fills {
+            let volume = volume_by_source_exchange.entry(fill.source_exchange).or_insert(Quantity::ZERO);
+            *volume += fill.volume;
+        }
+        volume_by_source_exchange
+    }
+
+    pub fn notional_by_source_exchange(&self) -> BTreeMap<ExchangeId, Notional> {
+        let mut notional_by_source_exchange = BTreeMap::new();
+        for fill in &self.fills {
+            let notional = notional_by_source_exchange.entry(fill.source_exchange).or_insert(Notional::ZERO);
+            *notional += fill.notional;
+        }
+        notional_by_source_exchange
+    }
+
+    /// The notional of the fills on each exchange before and after fees. The
+    /// fills on each exchange are charged as one taker order.
+    pub fn notional_after_fees_by_source_exchange(&self, fee_model: &FeeModel)
+        -> BTreeMap<ExchangeId, NotionalAfterFees>
+    {
+        self.notional_by_source_exchange()
+            .into_iter()
+            .map(
+                |(source_exchange, gross)| {
+                    let fee = fee_model.fee(source_exchange, gross, Liquidity::Taker);
+                    (source_exchange, NotionalAfterFees::new(self.order_side, gross, fee))
+                }
+            )
+            .collect()
+    }
+
+    pub fn notional_after_fees(&self, fee_model: &FeeModel) -> NotionalAfterFees {
+        let notional_after_fees_by_source_exchange = self.notional_after_fees_by_source_exchange(fee_model);
+        let gross = notional_after_fees_by_source_exchange.values().map(|notional_after_fees| notional_after_fees.gross).sum();
+        let fee = notional_after_fees_by_source_exchange.values().map(|notional_after_fees| notional_after_fees.fee).sum();
+        NotionalAfterFees::new(self.order_side, gross, fee)
+    }
+}
+
+/// Plan the execution of an order to BUY or SELL `target_volume` of `ticker`
+/// against the volume of every exchange in the book.
+///
+/// A BUY takes from the SELL side, lowest price first. A SELL takes from the
+/// BUY side, highest price first. Within a price level the volume of each
+/// exchange is taken in `ExchangeId` order. The book is not changed. Returns
+/// an error if `ticker` is not in the book.
+pub fn plan_fills(
+    limit_order_book: &MultiTickerLimitOrderBook,
+    ticker: Instrument,
+    order_side: OrderSide,
+    target_volume: Quantity,
+) -> Result<FillPlan, OrderBookError> {
+    plan_fills_for_target(limit_order_book, ticker, order_side, FillTarget::Volume(target_volume))
+}
+
+/// Plan the execution of an order to BUY `ticker` with a budget of
+/// `target_notional`, or to SELL `ticker` until the proceeds reach
+/// `target_notional`. Price levels are walked in the same order as `plan_fills`.
+///
+/// Volume is taken in multiples of the volume increment of the trading rules
+/// of `ticker`, see `MultiTickerLimitOrderBook::volume_increment`. A BUY never
+/// spends more than the budget, and stops when the rest of the budget buys
+/// less than one increment. A SELL takes the smallest volume whose proceeds
+/// reach the target, so the proceeds can exceed it slightly. Returns an error
+/// if converting the target to a volume overflows.
+pub fn plan_fills_for_notional(
+    limit_order_book: &MultiTickerLimitOrderBook,
+    ticker: Instrument,
+    order_side: OrderSide,
+    target_notional: Notional,
+) -> Result<FillPlan, OrderBookError> {
+    plan_fills_for_target(limit_order_book, ticker, order_side, FillTarget::Notional(target_notional))
+}
+
+fn plan_fills_for_target(
+    limit_order_book: &MultiTickerLimitOrderBook,
+    ticker: Instrument,
+    order_side: OrderSide,
+    target: FillTarget,
+) -> Result<FillPlan, OrderBookError> {
+    let opposite_order_side = match order_side {
+        OrderSide::BUY => OrderSide::SELL,
+        OrderSide::SELL => OrderSide::BUY,
+    };
+    let total_volume_by_price_level_and_source_exchange =
+        limit_order_book.total_volume_by_price_level_and_source_exchange(ticker, &opposite_order_side)?;
+
+    let price_levels: Box<dyn Iterator<Item = (&Price, &BTreeMap<ExchangeId, Quantity>)>> = match order_side {
+        OrderSide::BUY => Box::new(total_volume_by_price_level_and_source_exchange.iter()),
+        OrderSide::SELL => Box::new(total_volume_by_price_level_and_source_exchange.iter().rev()),
+    };
+
+    let volume_increment = limit_order_book.volume_increment(ticker);
+    let overflow = || OrderBookError::NotionalOverflow { ticker };
+
+    let mut fills = Vec::new();
+    let mut remaining = target;
+    // Set when the walk stops at a level which still has volume. The rest of a
+    // notional target can then be less than one volume increment, without the
+    // book having run out of volume
+    let mut volume_left_in_book = false;
+
+    'price_levels: for (price, volume_by_source_exchange) in price_levels {
+        for (source_exchange, volume) in volume_by_source_exchange {
+            if !volume.is_positive() {
+                continue;
+            }
+            let volume_to_take = match remaining {
+                FillTarget::Volume(remaining_volume) => {
+                    std::cmp::min(*volume, remaining_volume)
+                },
+                FillTarget::Notional(remaining_notional) => {
+                    let volume_for_notional = volume_for_notional(order_side, remaining_notional, *price, volume_increment)
+                        .ok_or_else(overflow)?;
+                    std::cmp::min(*volume, volume_for_notional)
+                },
+            };
+            if !volume_to_take.is_positive() {
+                volume_left_in_book = true;
+                break 'price_levels;
+            }
+            let notional = volume_to_take.checked_mul(*price).ok_or_else(overflow)?;
+            fills.push(
+                PlannedFill {
+                    price: *price,
+                    source_exchange: *source_exchange,
+                    volume: volume_to_take,
+                    notional,
+                }
+            );
+            remaining = match remaining {
+                FillTarget::Volume(remaining_volume) => {
+                    FillTarget::Volume(remaining_volume.checked_sub(volume_to_take).ok_or_else(overflow)?)
+                },
+                FillTarget::Notional(remaining_notional) => {
+                    FillTarget::Notional(remaining_notional.checked_sub(notional).ok_or_else(overflow)?)
+                },
+            };
+            if volume_to_take < *volume {
+                // The target is reached, or the rest of a notional target buys
+                // less than one increment here and later price levels are no cheaper
+                volume_left_in_book = true;
+                break 'price_levels;
+            }
+        }
+    }
+
+    let remaining_is_positive = match remaining {
+        FillTarget::Volume(remaining_volume) => remaining_volume.is_positive(),
+        FillTarget::Notional(remaining_notional) => remaining_notional.is_positive(),
+    };
+
+    Ok(
+        FillPlan {
+            order_side,
+            target,
+            fills,
+            book_exhausted: remaining_is_positive && !volume_left_in_book,
+        }
+    )
+}
+
+/// The volume, in multiples of `volume_increment`, which a BUY can afford with
+/// `notional`, rounded down, or which a SELL needs to reach `notional`, rounded
+/// up. `None` if the calculation overflows.
+fn volume_for_notional(order_side: OrderSide, notional: Notional, price: Price, volume_increment: Quantity)
+    -> Option<Quantity>
+{
+    if !notional.is_positive() {
+        return Some(Quantity::ZERO);
+    }
+    let volume = notional
+        .checked_div_with_scale(price, volume_increment.scale())?
+        .checked_floor_to_multiple_of(volume_increment)?;
+    match order_side {
+        OrderSide::BUY => {
+            Some(volume)
+        },
+        OrderSide::SELL => {
+            if volume.checked_mul(price)? < notional {
+                volume.checked_add(volume_increment)
+            } else {
+                Some(volume)
+            }
+        },
+    }
+}
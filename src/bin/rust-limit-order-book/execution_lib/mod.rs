@@ -0,0 +1,28 @@
+
+mod arbitrage;
+mod fee_model;
+mod fill_plan;
+mod order_router;
+
+pub use arbitrage::ArbitrageOpportunity;
+pub use arbitrage::find_arbitrage_opportunities;
+pub use fee_model::FeeModel;
+pub use fee_model::FeeSchedule;
+pub use fee_model::FeeTier;
+pub use fee_model::Liquidity;
+pub use fee_model::NotionalAfterFees;
+pub use fill_plan::FillPlan;
+pub use fill_plan::FillSummary;
+pub use fill_plan::FillTarget;
+pub use fill_plan::PlannedFill;
+pub use fill_plan::plan_fills;
+pub use fill_plan::plan_fills_for_notional;
+pub use order_router::ChildOrder;
+pub use order_router::ParentOrder;
+pub use order_router::RoutingPlan;
+pub use order_router::VenueConstraints;
+pub use order_router::route_order;
+
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,355 @@
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::OrderBookError;
+
+use super::FeeModel;
+use super::Liquidity;
+use super::NotionalAfterFees;
+
+
+/// An order to be split across exchanges.
+///
+/// As with market orders in the book, a BUY with no limit can use
+/// `FixedPoint::MAX` as the limit price, and a SELL can use `FixedPoint::MIN`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParentOrder {
+    pub order_side: OrderSide,
+    pub volume: Quantity,
+    pub limit_price: Price,
+    pub excluded_source_exchanges: BTreeSet<ExchangeId>,
+}
+
+impl ParentOrder {
+    pub fn new(order_side: OrderSide, volume: Quantity, limit_price: Price) -> ParentOrder {
+        ParentOrder {
+            order_side,
+            volume,
+            limit_price,
+            excluded_source_exchanges: BTreeSet::new(),
+        }
+    }
+}
+
+/// The minimum order volume, the lot size and the balances of the account on
+/// each exchange.
+///
+/// An exchange without a minimum order volume accepts any volume, an exchange
+/// without a lot size uses the volume increment of the instrument, and an
+/// exchange without a balance is treated as having an unlimited balance.
+#[derive(Debug, Default, Clone)]
+pub struct VenueConstraints {
+    minimum_order_volumes: BTreeMap<ExchangeId, Quantity>,
+    lot_sizes: BTreeMap<ExchangeId, Quantity>,
+    base_balances: BTreeMap<ExchangeId, Quantity>,
+    quote_balances: BTreeMap<ExchangeId, Notional>,
+}
+
+impl VenueConstraints {
+    pub fn new() -> VenueConstraints {
+        VenueConstraints::default()
+    }
+
+    pub fn set_minimum_order_volume(&mut self, source_exchange: ExchangeId, minimum_order_volume: Quantity) {
+        self.minimum_order_volumes.insert(source_exchange, minimum_order_volume);
+    }
+
+    pub fn set_lot_size(&mut self, source_exchange: ExchangeId, lot_size: Quantity) {
+        self.lot_sizes.insert(source_exchange, lot_size);
+    }
+
+    /// The balance available to SELL, for example BTC.
+    pub fn set_base_balance(&mut self, source_exchange: ExchangeId, base_balance: Quantity) {
+        self.base_balances.insert(source_exchange, base_balance);
+    }
+
+    /// The balance available to BUY with, including fees, for example USD.
+    pub fn set_quote_balance(&mut self, source_exchange: ExchangeId, quote_balance: Notional) {
+        self.quote_balances.insert(source_exchange, quote_balance);
+    }
+
+    pub fn minimum_order_volume(&self, source_exchange: ExchangeId) -> Quantity {
+        self.minimum_order_volumes.get(&source_exchange).copied().unwrap_or(Quantity::ZERO)
+    }
+
+    pub fn lot_size(&self, source_exchange: ExchangeId) -> Option<Quantity> {
+        self.lot_sizes.get(&source_exchange).copied()
+    }
+
+    pub fn base_balance(&self, source_exchange: ExchangeId) -> Option<Quantity> {
+        self.base_balances.get(&source_exchange).copied()
+    }
+
+    pub fn quote_balance(&self, source_exchange: ExchangeId) -> Option<Notional> {
+        self.quote_balances.get(&source_exchange).copied()
+    }
+}
+
+/// The part of a parent order which is sent to one exchange, as a limit order
+/// at the worst price it is expected to fill at.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChildOrder {
+    pub source_exchange: ExchangeId,
+    pub order_side: OrderSide,
+    pub volume: Quantity,
+    pub limit_price: Price,
+    pub notional_after_fees: NotionalAfterFees,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoutingPlan {
+    pub parent_order: ParentOrder,
+    /// One child order per exchange, in `ExchangeId` order.
+    pub child_orders: Vec<ChildOrder>,
+}
+
+impl RoutingPlan {
+    pub fn routed_volume(&self) -> Quantity {
+        self.child_orders.iter().map(|child_order| child_order.volume).sum()
+    }
+
+    /// The part of the parent order which cannot be routed, because of the
+    /// limit price, the volume in the book, or the constraints of each exchange.
+    pub fn unrouted_volume(&self) -> Quantity {
+        self.parent_order.volume - self.routed_volume()
+    }
+
+    pub fn notional_after_fees(&self) -> NotionalAfterFees {
+        let gross = self.child_orders.iter().map(|child_order| child_order.notional_after_fees.gross).sum();
+        let fee = self.child_orders.iter().map(|child_order| child_order.notional_after_fees.fee).sum();
+        NotionalAfterFees::new(self.parent_order.order_side, gross, fee)
+    }
+}
+
+/// The volume of one exchange at one price level.
+struct RoutableVolume {
+    price: Price,
+    /// The price after taker fees. Higher than `price` for a BUY, and lower for a SELL.
+    price_after_fees: Price,
+    source_exchange: ExchangeId,
+    volume: Quantity,
+}
+
+/// Split `parent_order` into one child order per exchange, taking the volume
+/// with the best price after taker fees first.
+///
+/// The volume of each exchange is limited by the balance of the account on that
+/// exchange. An exchange whose child order would be smaller than its minimum
+/// order volume is dropped, and the volume is routed again without it. Volume
+/// limited by a balance is rounded down to the lot size of the exchange.
+///
+/// Fixed fees are charged once per child order. They are deducted from the
+/// quote balance but are not used to rank the volume, so the routing is not
+/// guaranteed to be optimal when fixed fees are large compared to the order.
+pub fn route_order(
+    limit_order_book: &MultiTickerLimitOrderBook,
+    ticker: Instrument,
+    parent_order: &ParentOrder,
+    fee_model: &FeeModel,
+    venue_constraints: &VenueConstraints,
+) -> Result<RoutingPlan, OrderBookError> {
+    let opposite_order_side = match parent_order.order_side {
+        OrderSide::BUY => OrderSide::SELL,
+        OrderSide::SELL => OrderSide::BUY,
+    };
+    let total_volume_by_price_level_and_source_exchange =
+        limit_order_book.total_volume_by_price_level_and_source_exchange(ticker, &opposite_order_side)?;
+
+    let volume_increment = limit_order_book.volume_increment(ticker);
+    let mut excluded_source_exchanges = parent_order.excluded_source_exchanges.clone();
+
+    loop {
+        let routable_volumes = routable_volumes(
+            &total_volume_by_price_level_and_source_exchange,
+            parent_order,
+            fee_model,
+            &excluded_source_exchanges,
+        );
+        let allocations = allocate(&routable_volumes, parent_order, fee_model, venue_constraints, volume_increment)
+            .ok_or(OrderBookError::NotionalOverflow { ticker })?;
+
+        let too_small_source_exchanges: Vec<ExchangeId> = allocations
+            .iter()
+            .filter(
+                |(source_exchange, allocation)| {
+                    allocation.volume < venue_constraints.minimum_order_volume(**source_exchange)
+                }
+            )
+            .map(|(source_exchange, _)| *source_exchange)
+            .collect();
+
+        if too_small_source_exchanges.is_empty() {
+            let child_orders = allocations
+                .into_iter()
+                .map(
+                    |(source_exchange, allocation)| {
+                        let fee = fee_model.fee(source_exchange, allocation.notional, Liquidity::Taker);
+                        ChildOrder {
+                            source_exchange,
+                            order_side: parent_order.order_side,
+                            volume: allocation.volume,
+                            limit_price: allocation.worst_price,
+                            notional_after_fees: NotionalAfterFees::new(parent_order.order_side, allocation.notional, fee),
+                        }
+                    }
+                )
+                .collect();
+
+            return Ok(
+                RoutingPlan {
+                    parent_order: parent_order.clone(),
+                    child_orders,
+                }
+            );
+        }
+
+        excluded_source_exchanges.extend(too_small_source_exchanges);
+    }
+}
+
+/// The volume within the limit price of the exchanges which are not excluded,
+/// best price after fees first.
+fn routable_volumes(
+    total_volume_by_price_level_and_source_exchange: &BTreeMap<Price, BTreeMap<ExchangeId, Quantity>>,
+    parent_order: &ParentOrder,
+    fee_model: &FeeModel,
+    excluded_source_exchanges: &BTreeSet<ExchangeId>,
+) -> Vec<RoutableVolume> {
+    let mut routable_volumes = Vec::new();
+
+    for (price, volume_by_source_exchange) in total_volume_by_price_level_and_source_exchange {
+        let within_limit_price = match parent_order.order_side {
+            OrderSide::BUY => *price <= parent_order.limit_price,
+            OrderSide::SELL => *price >= parent_order.limit_price,
+        };
+        if !within_limit_price {
+            continue;
+        }
+        for (source_exchange, volume) in volume_by_source_exchange {
+            if excluded_source_exchanges.contains(source_exchange) || !volume.is_positive() {
+                continue;
+            }
+            let fee_rate = fee_model.fee_rate(*source_exchange, Liquidity::Taker);
+            let price_after_fees = match parent_order.order_side {
+                OrderSide::BUY => *price * (FixedPoint::from(1) + fee_rate),
+                OrderSide::SELL => *price * (FixedPoint::from(1) - fee_rate),
+            };
+            routable_volumes.push(
+                RoutableVolume {
+                    price: *price,
+                    price_after_fees,
+                    source_exchange: *source_exchange,
+                    volume: *volume,
+                }
+            );
+        }
+    }
+
+    match parent_order.order_side {
+        OrderSide::BUY => {
+            routable_volumes.sort_by_key(|routable_volume| routable_volume.price_after_fees);
+        },
+        OrderSide::SELL => {
+            routable_volumes.sort_by_key(|routable_volume| std::cmp::Reverse(routable_volume.price_after_fees));
+        },
+    }
+
+    routable_volumes
+}
+
+/// The volume routed to one exchange.
+struct Allocation {
+    volume: Quantity,
+    notional: Notional,
+    worst_price: Price,
+}
+
+/// The volume routed to each exchange, or `None` if the volume affordable with
+/// a quote balance overflows.
+fn allocate(
+    routable_volumes: &[RoutableVolume],
+    parent_order: &ParentOrder,
+    fee_model: &FeeModel,
+    venue_constraints: &VenueConstraints,
+    volume_increment: Quantity,
+) -> Option<BTreeMap<ExchangeId, Allocation>> {
+    let mut allocations: BTreeMap<ExchangeId, Allocation> = BTreeMap::new();
+    let mut remaining_volume = parent_order.volume;
+
+    // The unused balance of each exchange which has a balance. Fixed fees are
+    // deducted from the quote balance when the first volume is routed.
+    let mut remaining_base_balances = venue_constraints.base_balances.clone();
+    let mut remaining_quote_balances = venue_constraints.quote_balances.clone();
+
+    for routable_volume in routable_volumes {
+        if !remaining_volume.is_positive() {
+            break;
+        }
+        let source_exchange = routable_volume.source_exchange;
+        let mut volume_to_take = std::cmp::min(routable_volume.volume, remaining_volume);
+        let lot_size = venue_constraints
+            .lot_size(source_exchange)
+            .filter(|lot_size| lot_size.is_positive())
+            .unwrap_or(volume_increment);
+
+        match parent_order.order_side {
+            OrderSide::BUY => {
+                if let Some(remaining_quote_balance) = remaining_quote_balances.get_mut(&source_exchange) {
+                    if !allocations.contains_key(&source_exchange) {
+                        *remaining_quote_balance -= fee_model.fixed_fee(source_exchange);
+                    }
+                    let affordable_volume = if remaining_quote_balance.is_positive() {
+                        remaining_quote_balance
+                            .checked_div_with_scale(routable_volume.price_after_fees, lot_size.scale())?
+                            .checked_floor_to_multiple_of(lot_size)?
+                    } else {
+                        Quantity::ZERO
+                    };
+                    volume_to_take = std::cmp::min(volume_to_take, affordable_volume);
+                    if volume_to_take.is_positive() {
+                        *remaining_quote_balance -= volume_to_take * routable_volume.price_after_fees;
+                    } else if !allocations.contains_key(&source_exchange) {
+                        *remaining_quote_balance += fee_model.fixed_fee(source_exchange);
+                    }
+                }
+            },
+            OrderSide::SELL => {
+                if let Some(remaining_base_balance) = remaining_base_balances.get_mut(&source_exchange) {
+                    let available_volume = std::cmp::max(*remaining_base_balance, Quantity::ZERO).checked_floor_to_multiple_of(lot_size)?;
+                    volume_to_take = std::cmp::min(volume_to_take, available_volume);
+                    *remaining_base_balance -= volume_to_take;
+                }
+            },
+        }
+
+        if !volume_to_take.is_positive() {
+            continue;
+        }
+
+        let allocation = allocations.entry(source_exchange).or_insert(
+            Allocation {
+                volume: Quantity::ZERO,
+                notional: Notional::ZERO,
+                worst_price: routable_volume.price,
+            }
+        );
+        allocation.volume += volume_to_take;
+        allocation.notional += volume_to_take * routable_volume.price;
+        allocation.worst_price = match parent_order.order_side {
+            OrderSide::BUY => std::cmp::max(allocation.worst_price, routable_volume.price),
+            OrderSide::SELL => std::cmp::min(allocation.worst_price, routable_volume.price),
+        };
+        remaining_volume -= volume_to_take;
+    }
+
+    Some(allocations)
+}
@@ -0,0 +1,605 @@
+
+use super::*;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::Order;
+use crate::limit_order_book_lib::OrderBookError;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::TradingRules;
+use crate::test_support_lib::fixed_point;
+use crate::test_support_lib::source_exchange_1;
+use crate::test_support_lib::source_exchange_2;
+use crate::test_support_lib::ticker_1;
+use crate::test_support_lib::ticker_2;
+
+
+fn add_order(
+    limit_order_book: &mut MultiTickerLimitOrderBook,
+    order_side: OrderSide,
+    price: &str,
+    volume: &str,
+    source_exchange: ExchangeId,
+) {
+    let order_id = limit_order_book.next_order_id();
+    let order = Order::new(order_id, ticker_1(), order_side, fixed_point(price), fixed_point(volume), source_exchange);
+    limit_order_book.add_order(order).unwrap();
+}
+
+fn example_limit_order_book() -> MultiTickerLimitOrderBook {
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+
+    add_order(&mut limit_order_book, OrderSide::SELL, "101.0",  "5.0", source_exchange_1());
+    add_order(&mut limit_order_book, OrderSide::SELL, "101.0",  "3.0", source_exchange_2());
+    add_order(&mut limit_order_book, OrderSide::SELL, "102.0", "10.0", source_exchange_1());
+    add_order(&mut limit_order_book, OrderSide::SELL, "103.5",  "4.0", source_exchange_2());
+
+    add_order(&mut limit_order_book, OrderSide::BUY , "100.0",  "2.0", source_exchange_1());
+    add_order(&mut limit_order_book, OrderSide::BUY , "100.0",  "6.0", source_exchange_2());
+    add_order(&mut limit_order_book, OrderSide::BUY ,  "99.0",  "5.0", source_exchange_2());
+    add_order(&mut limit_order_book, OrderSide::BUY ,  "98.0", "10.0", source_exchange_1());
+
+    limit_order_book
+}
+
+
+#[test]
+fn plan_fills_buy_test() {
+
+    let limit_order_book = example_limit_order_book();
+
+    let fill_plan = plan_fills(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("12.0")).unwrap();
+
+    let expected_fills = vec![
+        PlannedFill { price: fixed_point("101.0"), source_exchange: source_exchange_1(), volume: fixed_point("5.0"), notional: fixed_point("505.0") },
+        PlannedFill { price: fixed_point("101.0"), source_exchange: source_exchange_2(), volume: fixed_point("3.0"), notional: fixed_point("303.0") },
+        PlannedFill { price: fixed_point("102.0"), source_exchange: source_exchange_1(), volume: fixed_point("4.0"), notional: fixed_point("408.0") },
+    ];
+    assert_eq!(fill_plan.fills, expected_fills);
+    assert_eq!(fill_plan.filled_volume(), fixed_point("12.0"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Volume(FixedPoint::ZERO));
+    assert!(fill_plan.is_completely_filled());
+    assert_eq!(fill_plan.total_notional(), fixed_point("1216.0"));
+
+    // The breakdown by exchange stops at the target volume
+    let expected_volume_by_source_exchange = BTreeMap::from(
+        [
+            (source_exchange_1(), fixed_point("9.0")),
+            (source_exchange_2(), fixed_point("3.0")),
+        ]
+    );
+    assert_eq!(fill_plan.volume_by_source_exchange(), expected_volume_by_source_exchange);
+    let expected_notional_by_source_exchange = BTreeMap::from(
+        [
+            (source_exchange_1(), fixed_point("913.0")),
+            (source_exchange_2(), fixed_point("303.0")),
+        ]
+    );
+    assert_eq!(fill_plan.notional_by_source_exchange(), expected_notional_by_source_exchange);
+}
+
+
+#[test]
+fn plan_fills_sell_test() {
+
+    let limit_order_book = example_limit_order_book();
+
+    // A SELL takes from the BUY side, highest price first
+    let fill_plan = plan_fills(&limit_order_book, ticker_1(), OrderSide::SELL, fixed_point("12.0")).unwrap();
+
+    let expected_fills = vec![
+        PlannedFill { price: fixed_point("100.0"), source_exchange: source_exchange_1(), volume: fixed_point("2.0"), notional: fixed_point("200.0") },
+        PlannedFill { price: fixed_point("100.0"), source_exchange: source_exchange_2(), volume: fixed_point("6.0"), notional: fixed_point("600.0") },
+        PlannedFill { price: fixed_point("99.0"), source_exchange: source_exchange_2(), volume: fixed_point("4.0"), notional: fixed_point("396.0") },
+    ];
+    assert_eq!(fill_plan.fills, expected_fills);
+    assert_eq!(fill_plan.total_notional(), fixed_point("1196.0"));
+
+    let expected_notional_by_source_exchange = BTreeMap::from(
+        [
+            (source_exchange_1(), fixed_point("200.0")),
+            (source_exchange_2(), fixed_point("996.0")),
+        ]
+    );
+    assert_eq!(fill_plan.notional_by_source_exchange(), expected_notional_by_source_exchange);
+}
+
+
+#[test]
+fn plan_fills_insufficient_volume_test() {
+
+    let mut limit_order_book = example_limit_order_book();
+
+    let fill_plan = plan_fills(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("30.0")).unwrap();
+    assert_eq!(fill_plan.fills.len(), 4);
+    assert_eq!(fill_plan.filled_volume(), fixed_point("22.0"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Volume(fixed_point("8.0")));
+    assert!(fill_plan.book_exhausted);
+    assert!(!fill_plan.is_completely_filled());
+    assert_eq!(fill_plan.total_notional(), fixed_point("2242.0"));
+
+    // A ticker with no bids
+    let order_id = limit_order_book.next_order_id();
+    let order = Order::new(order_id, ticker_2(), OrderSide::SELL, fixed_point("100.0"), fixed_point("1.0"), source_exchange_1());
+    limit_order_book.add_order(order).unwrap();
+    let fill_plan = plan_fills(&limit_order_book, ticker_2(), OrderSide::SELL, fixed_point("1.0")).unwrap();
+    assert!(fill_plan.fills.is_empty());
+    assert_eq!(fill_plan.unfilled(), FillTarget::Volume(fixed_point("1.0")));
+
+    // A ticker which is not in the book is an error rather than an empty book
+    let fill_plan = plan_fills(&limit_order_book, Instrument::intern("EXAMPLE3", "USD"), OrderSide::SELL, fixed_point("1.0"));
+    assert_eq!(fill_plan.unwrap_err(), OrderBookError::UnknownTicker { ticker: Instrument::intern("EXAMPLE3", "USD") });
+    assert!(!limit_order_book.contains_ticker(Instrument::intern("EXAMPLE3", "USD")));
+}
+
+
+#[test]
+fn fill_summary_test() {
+
+    let mut limit_order_book = example_limit_order_book();
+
+    let fill_summary = plan_fills(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("12.0")).unwrap().summary(&FeeModel::new());
+    let expected_fill_summary = FillSummary {
+        order_side: OrderSide::BUY,
+        target: FillTarget::Volume(fixed_point("12.0")),
+        filled_volume: fixed_point("12.0"),
+        total_notional: fixed_point("1216.0"),
+        total_fee: FixedPoint::ZERO,
+        net_notional: fixed_point("1216.0"),
+        unfilled: FillTarget::Volume(FixedPoint::ZERO),
+        book_exhausted: false,
+        average_price: Some(fixed_point("101.33333333")),
+        worst_price: Some(fixed_point("102.0")),
+    };
+    assert_eq!(fill_summary, expected_fill_summary);
+    assert!(fill_summary.is_completely_filled());
+
+    let fill_summary = plan_fills(&limit_order_book, ticker_1(), OrderSide::SELL, fixed_point("12.0")).unwrap().summary(&FeeModel::new());
+    assert_eq!(fill_summary.average_price, Some(fixed_point("99.66666666")));
+    assert_eq!(fill_summary.worst_price, Some(fixed_point("99.0")));
+
+    // The caller is told how much of the order cannot be filled
+    let fill_summary = plan_fills(&limit_order_book, ticker_1(), OrderSide::SELL, fixed_point("25.0")).unwrap().summary(&FeeModel::new());
+    let expected_fill_summary = FillSummary {
+        order_side: OrderSide::SELL,
+        target: FillTarget::Volume(fixed_point("25.0")),
+        filled_volume: fixed_point("23.0"),
+        total_notional: fixed_point("2275.0"),
+        total_fee: FixedPoint::ZERO,
+        net_notional: fixed_point("2275.0"),
+        unfilled: FillTarget::Volume(fixed_point("2.0")),
+        book_exhausted: true,
+        average_price: Some(fixed_point("98.91304347")),
+        worst_price: Some(fixed_point("98.0")),
+    };
+    assert_eq!(fill_summary, expected_fill_summary);
+    assert!(!fill_summary.is_completely_filled());
+
+    // A ticker with no asks
+    let order_id = limit_order_book.next_order_id();
+    let order = Order::new(order_id, ticker_2(), OrderSide::BUY, fixed_point("100.0"), fixed_point("1.0"), source_exchange_1());
+    limit_order_book.add_order(order).unwrap();
+    let fill_summary = plan_fills(&limit_order_book, ticker_2(), OrderSide::BUY, fixed_point("1.0")).unwrap().summary(&FeeModel::new());
+    assert_eq!(fill_summary.filled_volume, FixedPoint::ZERO);
+    assert_eq!(fill_summary.average_price, None);
+    assert_eq!(fill_summary.worst_price, None);
+}
+
+
+#[test]
+fn plan_fills_for_notional_test() {
+
+    let mut limit_order_book = example_limit_order_book();
+    let mut trading_rules = TradingRules::new();
+    trading_rules.lot_size = Some(fixed_point("0.1"));
+    limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+
+    // A BUY spends at most the budget, in multiples of the lot size
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("1000.0")).unwrap();
+    let expected_fills = vec![
+        PlannedFill { price: fixed_point("101.0"), source_exchange: source_exchange_1(), volume: fixed_point("5.0"), notional: fixed_point("505.0") },
+        PlannedFill { price: fixed_point("101.0"), source_exchange: source_exchange_2(), volume: fixed_point("3.0"), notional: fixed_point("303.0") },
+        PlannedFill { price: fixed_point("102.0"), source_exchange: source_exchange_1(), volume: fixed_point("1.8"), notional: fixed_point("183.6") },
+    ];
+    assert_eq!(fill_plan.fills, expected_fills);
+    assert_eq!(fill_plan.filled_volume(), fixed_point("9.8"));
+    assert_eq!(fill_plan.total_notional(), fixed_point("991.6"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(fixed_point("8.4")));
+    assert!(fill_plan.is_completely_filled());
+
+    let expected_volume_by_source_exchange = BTreeMap::from(
+        [
+            (source_exchange_1(), fixed_point("6.8")),
+            (source_exchange_2(), fixed_point("3.0")),
+        ]
+    );
+    assert_eq!(fill_plan.volume_by_source_exchange(), expected_volume_by_source_exchange);
+
+    // A SELL continues until the proceeds reach the target
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::SELL, fixed_point("1000.0")).unwrap();
+    let expected_fills = vec![
+        PlannedFill { price: fixed_point("100.0"), source_exchange: source_exchange_1(), volume: fixed_point("2.0"), notional: fixed_point("200.0") },
+        PlannedFill { price: fixed_point("100.0"), source_exchange: source_exchange_2(), volume: fixed_point("6.0"), notional: fixed_point("600.0") },
+        PlannedFill { price: fixed_point("99.0"), source_exchange: source_exchange_2(), volume: fixed_point("2.1"), notional: fixed_point("207.9") },
+    ];
+    assert_eq!(fill_plan.fills, expected_fills);
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(FixedPoint::ZERO));
+    assert!(fill_plan.is_completely_filled());
+
+    let expected_notional_by_source_exchange = BTreeMap::from(
+        [
+            (source_exchange_1(), fixed_point("200.0")),
+            (source_exchange_2(), fixed_point("807.9")),
+        ]
+    );
+    assert_eq!(fill_plan.notional_by_source_exchange(), expected_notional_by_source_exchange);
+
+    // An exact fill of the whole book is not reported as insufficient volume
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("2242.0")).unwrap();
+    assert_eq!(fill_plan.filled_volume(), fixed_point("22.0"));
+    assert!(fill_plan.is_completely_filled());
+
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("5000.0")).unwrap();
+    assert_eq!(fill_plan.filled_volume(), fixed_point("22.0"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(fixed_point("2758.0")));
+    assert!(fill_plan.book_exhausted);
+}
+
+
+#[test]
+fn plan_fills_for_notional_volume_increment_test() {
+
+    // A level quoted as "2" is still bought in multiples of the lot size, not
+    // in whole units of the string the venue sent
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+    add_order(&mut limit_order_book, OrderSide::SELL, "64000.00", "2", source_exchange_1());
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("50000")).unwrap();
+    assert_eq!(fill_plan.filled_volume(), fixed_point("0.78125"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(FixedPoint::ZERO));
+
+    let mut trading_rules = TradingRules::new();
+    trading_rules.lot_size = Some(fixed_point("0.0001"));
+    limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("50000")).unwrap();
+    let expected_fills = vec![
+        PlannedFill { price: fixed_point("64000.00"), source_exchange: source_exchange_1(), volume: fixed_point("0.7812"), notional: fixed_point("49996.8") },
+    ];
+    assert_eq!(fill_plan.fills, expected_fills);
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(fixed_point("3.2")));
+    assert!(!fill_plan.book_exhausted);
+
+    // A remainder which buys less than one lot at the next price is not an
+    // exhausted book, but running out of levels is
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+    add_order(&mut limit_order_book, OrderSide::SELL, "100.0", "1", source_exchange_1());
+    add_order(&mut limit_order_book, OrderSide::SELL, "150.0", "1", source_exchange_2());
+    let mut trading_rules = TradingRules::new();
+    trading_rules.lot_size = Some(fixed_point("1"));
+    limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("120.0")).unwrap();
+    assert_eq!(fill_plan.filled_volume(), fixed_point("1"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(fixed_point("20.0")));
+    assert!(!fill_plan.book_exhausted);
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("1000.0")).unwrap();
+    assert_eq!(fill_plan.filled_volume(), fixed_point("2"));
+    assert_eq!(fill_plan.unfilled(), FillTarget::Notional(fixed_point("750.0")));
+    assert!(fill_plan.book_exhausted);
+
+    // A SELL rounds up to the lot size
+    add_order(&mut limit_order_book, OrderSide::BUY, "90.0", "5", source_exchange_1());
+    let fill_plan = plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::SELL, fixed_point("100.0")).unwrap();
+    assert_eq!(fill_plan.filled_volume(), fixed_point("2"));
+
+    // Overflow is an error rather than a panic
+    assert_eq!(
+        plan_fills_for_notional(&limit_order_book, ticker_1(), OrderSide::BUY, FixedPoint::MAX),
+        Err(OrderBookError::NotionalOverflow { ticker: ticker_1() }),
+    );
+}
+
+
+#[test]
+fn fee_schedule_test() {
+
+    let fee_schedule = FeeSchedule::new(
+        vec![
+            FeeTier { minimum_trailing_volume: fixed_point("10000.0"), maker_fee_rate: fixed_point("0.0025"), taker_fee_rate: fixed_point("0.004") },
+            FeeTier { minimum_trailing_volume: FixedPoint::ZERO, maker_fee_rate: fixed_point("0.004"), taker_fee_rate: fixed_point("0.006") },
+        ],
+        fixed_point("0.5"),
+    );
+
+    assert_eq!(fee_schedule.fee_rate(Liquidity::Taker, FixedPoint::ZERO), fixed_point("0.006"));
+    assert_eq!(fee_schedule.fee_rate(Liquidity::Maker, fixed_point("9999.99")), fixed_point("0.004"));
+    assert_eq!(fee_schedule.fee_rate(Liquidity::Taker, fixed_point("10000.0")), fixed_point("0.004"));
+    assert_eq!(fee_schedule.fee_rate(Liquidity::Maker, fixed_point("50000.0")), fixed_point("0.0025"));
+
+    assert_eq!(fee_schedule.fee(fixed_point("1000.0"), Liquidity::Taker, FixedPoint::ZERO), fixed_point("6.5"));
+    assert_eq!(fee_schedule.fee(fixed_point("1000.0"), Liquidity::Maker, fixed_point("10000.0")), fixed_point("3.0"));
+    assert_eq!(fee_schedule.fee(FixedPoint::ZERO, Liquidity::Taker, FixedPoint::ZERO), FixedPoint::ZERO);
+
+    let mut fee_model = FeeModel::new();
+    fee_model.set_fee_schedule(source_exchange_1(), fee_schedule);
+    fee_model.set_trailing_volume(source_exchange_1(), fixed_point("20000.0"));
+    assert_eq!(fee_model.fee_rate(source_exchange_1(), Liquidity::Taker), fixed_point("0.004"));
+    // An exchange without a fee schedule charges no fees
+    assert_eq!(fee_model.fee(source_exchange_2(), fixed_point("1000.0"), Liquidity::Taker), FixedPoint::ZERO);
+}
+
+
+#[test]
+fn fill_plan_fees_test() {
+
+    let limit_order_book = example_limit_order_book();
+
+    let mut fee_model = FeeModel::new();
+    fee_model.set_fee_schedule(source_exchange_1(), FeeSchedule::flat(fixed_point("0.001"), fixed_point("0.002")));
+    fee_model.set_fee_schedule(source_exchange_2(), FeeSchedule::flat(fixed_point("0.0"), fixed_point("0.01")));
+
+    // The gross cost is 913.0 on source_exchange_1() and 303.0 on source_exchange_2()
+    let fill_plan = plan_fills(&limit_order_book, ticker_1(), OrderSide::BUY, fixed_point("12.0")).unwrap();
+    let expected_notional_after_fees_by_source_exchange = BTreeMap::from(
+        [
+            (source_exchange_1(), NotionalAfterFees { gross: fixed_point("913.0"), fee: fixed_point("1.826"), net: fixed_point("914.826") }),
+            (source_exchange_2(), NotionalAfterFees { gross: fixed_point("303.0"), fee: fixed_point("3.03"), net: fixed_point("306.03") }),
+        ]
+    );
+    assert_eq!(fill_plan.notional_after_fees_by_source_exchange(&fee_model), expected_notional_after_fees_by_source_exchange);
+
+    let fill_summary = fill_plan.summary(&fee_model);
+    assert_eq!(fill_summary.total_notional, fixed_point("1216.0"));
+    assert_eq!(fill_summary.total_fee, fixed_point("4.856"));
+    assert_eq!(fill_summary.net_notional, fixed_point("1220.856"));
+
+    // Fees reduce the proceeds of a SELL
+    let fill_plan = plan_fills(&limit_order_book, ticker_1(), OrderSide::SELL, fixed_point("12.0")).unwrap();
+    let expected_notional_after_fees = NotionalAfterFees {
+        gross: fixed_point("1196.0"),
+        fee: fixed_point("10.36"),
+        net: fixed_point("1185.64"),
+    };
+    assert_eq!(fill_plan.notional_after_fees(&fee_model), expected_notional_after_fees);
+}
+
+
+fn example_fee_model() -> FeeModel {
+    let mut fee_model = FeeModel::new();
+    fee_model.set_fee_schedule(source_exchange_1(), FeeSchedule::flat(fixed_point("0.01"), fixed_point("0.02")));
+    fee_model.set_fee_schedule(source_exchange_2(), FeeSchedule::flat(FixedPoint::ZERO, FixedPoint::ZERO));
+    fee_model
+}
+
+
+#[test]
+fn route_order_test() {
+
+    let limit_order_book = example_limit_order_book();
+    let fee_model = example_fee_model();
+    let venue_constraints = VenueConstraints::new();
+
+    // After fees, SELL 101.0 on source_exchange_1() costs 103.02, which is
+    // cheaper than SELL 103.5 on source_exchange_2() but more than SELL 101.0
+    // on source_exchange_2()
+    let parent_order = ParentOrder::new(OrderSide::BUY, fixed_point("10.0"), FixedPoint::MAX);
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    let expected_child_orders = vec![
+        ChildOrder {
+            source_exchange: source_exchange_1(),
+            order_side: OrderSide::BUY,
+            volume: fixed_point("5.0"),
+            limit_price: fixed_point("101.0"),
+            notional_after_fees: NotionalAfterFees { gross: fixed_point("505.0"), fee: fixed_point("10.1"), net: fixed_point("515.1") },
+        },
+        ChildOrder {
+            source_exchange: source_exchange_2(),
+            order_side: OrderSide::BUY,
+            volume: fixed_point("5.0"),
+            limit_price: fixed_point("103.5"),
+            notional_after_fees: NotionalAfterFees { gross: fixed_point("510.0"), fee: FixedPoint::ZERO, net: fixed_point("510.0") },
+        },
+    ];
+    assert_eq!(routing_plan.child_orders, expected_child_orders);
+    assert_eq!(routing_plan.unrouted_volume(), FixedPoint::ZERO);
+    assert_eq!(routing_plan.notional_after_fees().net, fixed_point("1025.1"));
+
+    // The limit price excludes SELL 103.5
+    let parent_order = ParentOrder::new(OrderSide::BUY, fixed_point("10.0"), fixed_point("102.0"));
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    let volume_by_source_exchange: Vec<(ExchangeId, FixedPoint, FixedPoint)> = routing_plan.child_orders
+        .iter()
+        .map(|child_order| (child_order.source_exchange, child_order.volume, child_order.limit_price))
+        .collect();
+    assert_eq!(
+        volume_by_source_exchange,
+        vec![
+            (source_exchange_1(), fixed_point("7.0"), fixed_point("102.0")),
+            (source_exchange_2(), fixed_point("3.0"), fixed_point("101.0")),
+        ],
+    );
+
+    // An excluded exchange is not used
+    let mut parent_order = ParentOrder::new(OrderSide::BUY, fixed_point("20.0"), FixedPoint::MAX);
+    parent_order.excluded_source_exchanges = BTreeSet::from([source_exchange_2()]);
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    assert_eq!(routing_plan.child_orders.len(), 1);
+    assert_eq!(routing_plan.child_orders[0].source_exchange, source_exchange_1());
+    assert_eq!(routing_plan.routed_volume(), fixed_point("15.0"));
+    assert_eq!(routing_plan.unrouted_volume(), fixed_point("5.0"));
+}
+
+
+#[test]
+fn route_order_venue_constraints_test() {
+
+    let limit_order_book = example_limit_order_book();
+    let fee_model = example_fee_model();
+
+    // source_exchange_2() would get 5.0, which is less than its minimum, so
+    // everything is routed to source_exchange_1()
+    let mut venue_constraints = VenueConstraints::new();
+    venue_constraints.set_minimum_order_volume(source_exchange_2(), fixed_point("6.0"));
+    let parent_order = ParentOrder::new(OrderSide::BUY, fixed_point("10.0"), FixedPoint::MAX);
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    assert_eq!(routing_plan.child_orders.len(), 1);
+    assert_eq!(routing_plan.child_orders[0].source_exchange, source_exchange_1());
+    assert_eq!(routing_plan.child_orders[0].volume, fixed_point("10.0"));
+    assert_eq!(routing_plan.child_orders[0].limit_price, fixed_point("102.0"));
+
+    // 300.0 buys 2.9 lots of 0.1 on source_exchange_1() at 103.02 after fees,
+    // leaving too little to buy a lot at 102.0
+    let mut venue_constraints = VenueConstraints::new();
+    venue_constraints.set_lot_size(source_exchange_1(), fixed_point("0.1"));
+    venue_constraints.set_quote_balance(source_exchange_1(), fixed_point("300.0"));
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    let volume_by_source_exchange: Vec<(ExchangeId, FixedPoint)> = routing_plan.child_orders
+        .iter()
+        .map(|child_order| (child_order.source_exchange, child_order.volume))
+        .collect();
+    assert_eq!(
+        volume_by_source_exchange,
+        vec![
+            (source_exchange_1(), fixed_point("2.9")),
+            (source_exchange_2(), fixed_point("7.0")),
+        ],
+    );
+    assert_eq!(routing_plan.unrouted_volume(), fixed_point("0.1"));
+    assert!(routing_plan.child_orders[0].notional_after_fees.net <= fixed_point("300.0"));
+
+    // With a lot size of 0.5 on source_exchange_1(), 300.0 only buys 2.5
+    venue_constraints.set_lot_size(source_exchange_1(), fixed_point("0.5"));
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    assert_eq!(routing_plan.child_orders[0].source_exchange, source_exchange_1());
+    assert_eq!(routing_plan.child_orders[0].volume, fixed_point("2.5"));
+    assert_eq!(routing_plan.unrouted_volume(), fixed_point("0.5"));
+
+    // Without a lot size, the volume increment of the instrument is used
+    let mut limit_order_book = example_limit_order_book();
+    let mut trading_rules = TradingRules::new();
+    trading_rules.lot_size = Some(fixed_point("1.0"));
+    limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+    let mut venue_constraints = VenueConstraints::new();
+    venue_constraints.set_quote_balance(source_exchange_1(), fixed_point("300.0"));
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    assert_eq!(routing_plan.child_orders[0].source_exchange, source_exchange_1());
+    assert_eq!(routing_plan.child_orders[0].volume, fixed_point("2.0"));
+    let limit_order_book = example_limit_order_book();
+
+    // A SELL is limited by the base balance
+    let mut venue_constraints = VenueConstraints::new();
+    venue_constraints.set_base_balance(source_exchange_2(), fixed_point("7.0"));
+    let parent_order = ParentOrder::new(OrderSide::SELL, fixed_point("10.0"), FixedPoint::MIN);
+    let routing_plan = route_order(&limit_order_book, ticker_1(), &parent_order, &fee_model, &venue_constraints).unwrap();
+    let expected_child_orders = vec![
+        ChildOrder {
+            source_exchange: source_exchange_1(),
+            order_side: OrderSide::SELL,
+            volume: fixed_point("3.0"),
+            limit_price: fixed_point("98.0"),
+            notional_after_fees: NotionalAfterFees { gross: fixed_point("298.0"), fee: fixed_point("5.96"), net: fixed_point("292.04") },
+        },
+        ChildOrder {
+            source_exchange: source_exchange_2(),
+            order_side: OrderSide::SELL,
+            volume: fixed_point("7.0"),
+            limit_price: fixed_point("99.0"),
+            notional_after_fees: NotionalAfterFees { gross: fixed_point("699.0"), fee: FixedPoint::ZERO, net: fixed_point("699.0") },
+        },
+    ];
+    assert_eq!(routing_plan.child_orders, expected_child_orders);
+}
+
+
+fn crossed_limit_order_book() -> MultiTickerLimitOrderBook {
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+    let mut trading_rules = TradingRules::new();
+    trading_rules.lot_size = Some(fixed_point("0.1"));
+    limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+
+    add_order(&mut limit_order_book, OrderSide::SELL, "100.0", "2.0", source_exchange_1());
+    add_order(&mut limit_order_book, OrderSide::SELL, "101.0", "3.0", source_exchange_1());
+    add_order(&mut limit_order_book, OrderSide::BUY ,  "99.0", "1.0", source_exchange_1());
+
+    add_order(&mut limit_order_book, OrderSide::BUY , "102.0", "1.0", source_exchange_2());
+    add_order(&mut limit_order_book, OrderSide::BUY , "100.5", "4.0", source_exchange_2());
+    add_order(&mut limit_order_book, OrderSide::SELL, "103.0", "1.0", source_exchange_2());
+
+    limit_order_book
+}
+
+
+#[test]
+fn find_arbitrage_opportunities_test() {
+
+    let limit_order_book = crossed_limit_order_book();
+
+    let mut fee_model = FeeModel::new();
+    fee_model.set_fee_schedule(source_exchange_1(), FeeSchedule::flat(FixedPoint::ZERO, fixed_point("0.001")));
+    fee_model.set_fee_schedule(source_exchange_2(), FeeSchedule::flat(FixedPoint::ZERO, fixed_point("0.002")));
+
+    // Buy 1.0 at 100.0 and sell at 102.0, then buy 1.0 at 100.0 and sell at
+    // 100.5. The ask at 101.0 is above the remaining bid at 100.5.
+    let arbitrage_opportunities = find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &VenueConstraints::new()).unwrap();
+    let expected_arbitrage_opportunities = vec![
+        ArbitrageOpportunity {
+            buy_source_exchange: source_exchange_1(),
+            sell_source_exchange: source_exchange_2(),
+            buy_price: fixed_point("100.0"),
+            sell_price: fixed_point("102.0"),
+            volume: fixed_point("2.0"),
+            buy_notional_after_fees: NotionalAfterFees { gross: fixed_point("200.0"), fee: fixed_point("0.2"), net: fixed_point("200.2") },
+            sell_notional_after_fees: NotionalAfterFees { gross: fixed_point("202.5"), fee: fixed_point("0.405"), net: fixed_point("202.095") },
+            gross_profit: fixed_point("2.5"),
+            net_profit: fixed_point("1.895"),
+        },
+    ];
+    assert_eq!(arbitrage_opportunities, expected_arbitrage_opportunities);
+
+    // The base balance of the selling exchange limits the volume
+    let mut venue_constraints = VenueConstraints::new();
+    venue_constraints.set_base_balance(source_exchange_2(), fixed_point("1.5"));
+    let arbitrage_opportunities = find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &venue_constraints).unwrap();
+    assert_eq!(arbitrage_opportunities[0].volume, fixed_point("1.5"));
+    assert_eq!(arbitrage_opportunities[0].gross_profit, fixed_point("2.25"));
+
+    // So does the quote balance of the buying exchange, including fees
+    let mut venue_constraints = VenueConstraints::new();
+    venue_constraints.set_quote_balance(source_exchange_1(), fixed_point("150.0"));
+    let arbitrage_opportunities = find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &venue_constraints).unwrap();
+    assert_eq!(arbitrage_opportunities[0].volume, fixed_point("1.4"));
+    assert!(arbitrage_opportunities[0].buy_notional_after_fees.net <= fixed_point("150.0"));
+
+    // The volume bought with the quote balance is rounded down to the lot size
+    let mut limit_order_book = crossed_limit_order_book();
+    let mut trading_rules = TradingRules::new();
+    trading_rules.lot_size = Some(fixed_point("0.5"));
+    limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+    let arbitrage_opportunities = find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &venue_constraints).unwrap();
+    assert_eq!(arbitrage_opportunities[0].volume, fixed_point("1.0"));
+
+    // Every level which crosses before fees is taken, and the fees are only
+    // reflected in the net profit
+    let limit_order_book = crossed_limit_order_book();
+    fee_model.set_fee_schedule(source_exchange_2(), FeeSchedule::flat(FixedPoint::ZERO, fixed_point("0.005")));
+    let arbitrage_opportunities = find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &VenueConstraints::new()).unwrap();
+    assert_eq!(arbitrage_opportunities.len(), 1);
+    assert_eq!(arbitrage_opportunities[0].volume, fixed_point("2.0"));
+    assert_eq!(arbitrage_opportunities[0].gross_profit, fixed_point("2.5"));
+    assert_eq!(arbitrage_opportunities[0].net_profit, fixed_point("1.2875"));
+
+    // A crossed pair which fees make unprofitable is still reported, with a
+    // negative net profit
+    fee_model.set_fee_schedule(source_exchange_2(), FeeSchedule::flat(FixedPoint::ZERO, fixed_point("0.02")));
+    let arbitrage_opportunities = find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &VenueConstraints::new()).unwrap();
+    assert_eq!(arbitrage_opportunities.len(), 1);
+    assert_eq!(arbitrage_opportunities[0].gross_profit, fixed_point("2.5"));
+    assert_eq!(arbitrage_opportunities[0].net_profit, fixed_point("-1.75"));
+
+    // A book which is not crossed has no opportunities
+    let limit_order_book = example_limit_order_book();
+    assert!(find_arbitrage_opportunities(&limit_order_book, ticker_1(), &fee_model, &VenueConstraints::new()).unwrap().is_empty());
+}
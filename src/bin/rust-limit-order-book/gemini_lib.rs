@@ -1,5 +1,4 @@
 
-use std::io::Write;
 use std::str::FromStr;
 use std::fmt;
 use std::fmt::Display;
@@ -9,20 +8,29 @@ use serde::Deserialize;
 use serde::Deserializer;
 use serde::de;
 
-use reqwest;
-use reqwest::header::USER_AGENT;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
 
-use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
-use crate::limit_order_book_lib::OrderSide;
-use crate::limit_order_book_lib::Order;
+use crate::exchange_adapter_lib::ExchangeAdapter;
+use crate::exchange_adapter_lib::ExchangeAdapterError;
+use crate::exchange_adapter_lib::Snapshot;
+use crate::exchange_adapter_lib::de_from_number;
+use crate::exchange_adapter_lib::de_from_str;
+use crate::exchange_adapter_lib::de_option_from_str;
+
+use crate::instrument_lib::InstrumentRegistry;
+use crate::instrument_lib::InstrumentSpec;
+use crate::instrument_lib::InstrumentStatus;
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GeminiBidAsk {
     #[serde(deserialize_with="de_from_str")]
-    pub price: f64,
+    pub price: Price,
     #[serde(deserialize_with="de_from_str")]
-    pub amount: f64,
+    pub amount: Quantity,
     #[serde(deserialize_with="de_from_str_u64")]
     pub timestamp: u64,
 }
@@ -38,13 +46,6 @@ impl Display for GeminiBidAsk {
     }
 }
 
-fn de_from_str<'de, D>(deserializer: D) -> Result<f64, D::Error>
-    where D: Deserializer<'de>
-{
-    let s = <&str>::deserialize(deserializer)?;
-    f64::from_str(s).map_err(de::Error::custom)
-}
-
 fn de_from_str_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
     where D: Deserializer<'de>
 {
@@ -68,69 +69,111 @@ impl Display for GeminiBook {
     }
 }
 
-pub fn get_api_gemini(
-    client: &reqwest::blocking::Client,
-    user_agent: &str,
-) -> String {
-
-    // Gemini
-    let symbol = "btcusd";
-    let url_gemini = format!("https://api.gemini.com/v1/book/{symbol}");
-    println!("Gemini URL: {url_gemini}");
-
-    // The behaviour I want here is for the program to panic if this call fails, hence `expect`
-    let response = client
-        .get(url_gemini)
-        .header("Content-Type", "application/json")
-        .header(USER_AGENT, user_agent)
-        .send()
-        .expect("client failed to send request");
-    println!("Gemini Response Status Code: {}", response.status());
-    
-    let response_text = response.text().expect("failed to convert response to text");
-    response_text
+/// The response of `/v1/symbols/details/{symbol}`.
+///
+/// Gemini sends the increments as JSON numbers, for example `1E-8`. Its
+/// `tick_size` is the increment of the order size, and `quote_increment` the
+/// increment of the price.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GeminiSymbolDetails {
+    pub symbol: String,
+    pub base_currency: String,
+    pub quote_currency: String,
+    #[serde(deserialize_with="de_from_number")]
+    pub tick_size: Quantity,
+    #[serde(deserialize_with="de_from_number")]
+    pub quote_increment: Price,
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub min_order_size: Option<Quantity>,
+    pub status: String,
+}
+
+impl GeminiSymbolDetails {
+    pub fn instrument_spec(&self, source_exchange: ExchangeId) -> InstrumentSpec {
+        InstrumentSpec {
+            source_exchange,
+            symbol: self.symbol.clone(),
+            instrument: Instrument::intern(&self.base_currency, &self.quote_currency),
+            status: InstrumentStatus::from_venue_status(&self.status),
+            trading_disabled: false,
+            quote_increment: self.quote_increment,
+            base_increment: self.tick_size,
+            minimum_order_size: self.min_order_size,
+            maximum_order_size: None,
+            minimum_notional: None,
+        }
+    }
+}
+
+/// Parse the details of one symbol into its spec.
+pub fn parse_gemini_symbol_details(response_text: &str, source_exchange: ExchangeId) -> Result<InstrumentSpec, ExchangeAdapterError> {
+    let gemini_symbol_details = serde_json::from_str::<GeminiSymbolDetails>(response_text)?;
+    Ok(gemini_symbol_details.instrument_spec(source_exchange))
 }
 
-pub fn dump_gemini_response_text_to_file(response_text: &str) {
-    let filename = "gemini_btcusd.json";
-    let mut file = std::fs::File::create(filename).unwrap_or_else(|error| panic!("failed to create file {filename}: {error}"));
-    file.write(response_text.as_bytes()).expect("failed to write data to file");
+pub const GEMINI_ENDPOINT: &str = "https://api.gemini.com";
+
+/// The REST book of one Gemini symbol, for example `btcusd`.
+#[derive(Debug)]
+pub struct GeminiAdapter {
+    symbol: String,
+    source_exchange: ExchangeId,
+    endpoint: String,
 }
 
-pub fn load_gemini_book<'s>(
-    response_text: String,
-    ticker: &'s str,
-    source_exchange: &'s str,
-    limit_order_book: &mut MultiTickerLimitOrderBook<'s>,
-) {
-    let gemini_book =
-        serde_json::from_str::<GeminiBook>(&response_text)
-        .expect("failed to deserialize GeminiBook");
-
-    for bid in gemini_book.bids {
-        let price = bid.price;
-        let volume = bid.amount;
-        let order = Order::new(
-            ticker,
-            OrderSide::BUY,
-            price,
-            volume,
+impl GeminiAdapter {
+    pub fn new(symbol: &str, source_exchange: ExchangeId) -> GeminiAdapter {
+        GeminiAdapter {
+            symbol: String::from(symbol),
             source_exchange,
-        ).expect("failed to construct Order");
-        limit_order_book.add_order(order);
+            endpoint: String::from(GEMINI_ENDPOINT),
+        }
     }
 
-    for ask in gemini_book.asks {
-        let price = ask.price;
-        let volume = ask.amount;
-        //println!("adding ASK: {price} {volume}");
-        let order = Order::new(
-            ticker,
-            OrderSide::SELL,
-            price,
-            volume,
-            source_exchange,
-        ).expect("failed to construct Order");
-        limit_order_book.add_order(order);
+    /// Use a different base URL for the REST API, for example a sandbox.
+    pub fn with_endpoint(mut self, endpoint: &str) -> GeminiAdapter {
+        self.endpoint = String::from(endpoint.trim_end_matches('/'));
+        self
     }
-}
\ No newline at end of file
+}
+
+impl ExchangeAdapter for GeminiAdapter {
+    fn source_exchange(&self) -> ExchangeId {
+        self.source_exchange
+    }
+
+    fn symbol(&self) -> String {
+        self.symbol.clone()
+    }
+
+    fn url(&self) -> String {
+        let endpoint = self.endpoint.as_str();
+        let symbol = self.symbol.as_str();
+        format!("{endpoint}/v1/book/{symbol}")
+    }
+
+    fn dump_filename(&self) -> String {
+        let symbol = self.symbol.as_str();
+        format!("gemini_{symbol}.json")
+    }
+
+    fn catalog_url(&self) -> String {
+        let endpoint = self.endpoint.as_str();
+        let symbol = self.symbol.as_str();
+        format!("{endpoint}/v1/symbols/details/{symbol}")
+    }
+
+    fn parse_catalog(&self, response_text: &str) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError> {
+        Ok(vec![parse_gemini_symbol_details(response_text, self.source_exchange)?])
+    }
+
+    fn parse_snapshot(&self, response_text: &str, _instrument_registry: &InstrumentRegistry) -> Result<Snapshot, ExchangeAdapterError> {
+        let gemini_book = serde_json::from_str::<GeminiBook>(response_text)?;
+        Ok(
+            Snapshot {
+                bids: gemini_book.bids.iter().map(|bid| (bid.price, bid.amount)).collect(),
+                asks: gemini_book.asks.iter().map(|ask| (ask.price, ask.amount)).collect(),
+            }
+        )
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::OrderBookError;
+use crate::limit_order_book_lib::OrderId;
+use crate::limit_order_book_lib::OrderType;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::TradingRules;
+
+use super::InstrumentError;
+
+
+/// Whether a venue accepts orders for an instrument, and which kinds.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum InstrumentStatus {
+    Online,
+    /// Only orders which rest on the book are accepted.
+    PostOnly,
+    /// Only limit orders are accepted.
+    LimitOnly,
+    /// Orders can be cancelled, but no new orders are accepted.
+    CancelOnly,
+    Offline,
+    Delisted,
+}
+
+impl InstrumentStatus {
+    /// The status of a status string of any venue, for example `online` on
+    /// Coinbase and Kraken, or `open` on Gemini. Unknown statuses are `Offline`.
+    pub fn from_venue_status(status: &str) -> InstrumentStatus {
+        match status.to_lowercase().as_str() {
+            "online" | "open" => {
+                InstrumentStatus::Online
+            },
+            "post_only" => {
+                InstrumentStatus::PostOnly
+            },
+            "limit_only" => {
+                InstrumentStatus::LimitOnly
+            },
+            "cancel_only" | "reduce_only" => {
+                InstrumentStatus::CancelOnly
+            },
+            "delisted" => {
+                InstrumentStatus::Delisted
+            },
+            _ => {
+                InstrumentStatus::Offline
+            },
+        }
+    }
+
+    pub fn accepts_new_orders(&self) -> bool {
+        match self {
+            InstrumentStatus::Online | InstrumentStatus::PostOnly | InstrumentStatus::LimitOnly => {
+                true
+            },
+            InstrumentStatus::CancelOnly | InstrumentStatus::Offline | InstrumentStatus::Delisted => {
+                false
+            },
+        }
+    }
+}
+
+/// The metadata of one instrument on one venue, as listed in the product
+/// catalog of the venue.
+#[derive(Debug, PartialEq, Clone)]
+pub struct InstrumentSpec {
+    pub source_exchange: ExchangeId,
+    /// The name of the instrument on the venue, for example `BTC-USD`.
+    pub symbol: String,
+    pub instrument: Instrument,
+    pub status: InstrumentStatus,
+    pub trading_disabled: bool,
+    /// The tick size. Prices must be a multiple of it.
+    pub quote_increment: Price,
+    /// The lot size. Order sizes must be a multiple of it.
+    pub base_increment: Quantity,
+    pub minimum_order_size: Option<Quantity>,
+    pub maximum_order_size: Option<Quantity>,
+    /// The smallest value of an order in the quote asset.
+    pub minimum_notional: Option<Notional>,
+}
+
+impl InstrumentSpec {
+    pub fn is_trading(&self) -> bool {
+        !self.trading_disabled && self.status.accepts_new_orders()
+    }
+
+    /// The tick, lot and minimum notional of the venue, for a book which only
+    /// holds orders for this venue. The price and volume scales are the
+    /// decimal places of the tick and lot sizes.
+    pub fn trading_rules(&self) -> TradingRules {
+        let mut trading_rules = TradingRules::new();
+        trading_rules.tick_size = Some(self.quote_increment);
+        trading_rules.lot_size = Some(self.base_increment);
+        trading_rules.minimum_notional = self.minimum_notional;
+        trading_rules.price_scale = Some(self.quote_increment.significant_scale());
+        trading_rules.volume_scale = Some(self.base_increment.significant_scale());
+        trading_rules
+    }
+
+    /// Check an order of `volume` at `price` against the rules of the venue.
+    ///
+    /// The tick, lot and minimum notional are checked by `trading_rules`, the
+    /// same as in the book.
+    pub fn check_order(&self, price: Price, volume: Quantity) -> Result<(), InstrumentError> {
+        let source_exchange = self.source_exchange;
+        let instrument = self.instrument;
+        if !self.is_trading() {
+            return Err(
+                InstrumentError::NotTrading {
+                    source_exchange,
+                    instrument,
+                    status: self.status,
+                }
+            );
+        }
+        if !volume.is_positive() {
+            return Err(
+                InstrumentError::InvalidOrderSize {
+                    source_exchange,
+                    instrument,
+                    volume,
+                    base_increment: self.base_increment,
+                }
+            );
+        }
+        // The order is not in a book yet, and `next_order_id` starts from 1
+        self.trading_rules()
+            .check_order(OrderId(0), OrderType::Limit, price, volume, None)
+            .map_err(|error| self.instrument_error(error))?;
+        if let Some(minimum_order_size) = self.minimum_order_size.filter(|minimum_order_size| volume < *minimum_order_size) {
+            return Err(
+                InstrumentError::BelowMinimumOrderSize {
+                    source_exchange,
+                    instrument,
+                    volume,
+                    minimum_order_size,
+                }
+            );
+        }
+        if let Some(maximum_order_size) = self.maximum_order_size.filter(|maximum_order_size| volume > *maximum_order_size) {
+            return Err(
+                InstrumentError::AboveMaximumOrderSize {
+                    source_exchange,
+                    instrument,
+                    volume,
+                    maximum_order_size,
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn instrument_error(&self, error: OrderBookError) -> InstrumentError {
+        let source_exchange = self.source_exchange;
+        let instrument = self.instrument;
+        match error {
+            OrderBookError::NonPositivePrice { price, .. } | OrderBookError::PriceNotOnTick { price, .. } => {
+                InstrumentError::InvalidPrice {
+                    source_exchange,
+                    instrument,
+                    price,
+                    quote_increment: self.quote_increment,
+                }
+            },
+            OrderBookError::VolumeNotOnLot { volume, .. } => {
+                InstrumentError::InvalidOrderSize {
+                    source_exchange,
+                    instrument,
+                    volume,
+                    base_increment: self.base_increment,
+                }
+            },
+            OrderBookError::BelowMinimumNotional { notional, minimum_notional, .. } => {
+                InstrumentError::BelowMinimumNotional {
+                    source_exchange,
+                    instrument,
+                    notional,
+                    minimum_notional,
+                }
+            },
+            error => {
+                InstrumentError::TradingRules {
+                    source_exchange,
+                    instrument,
+                    error: Box::new(error),
+                }
+            },
+        }
+    }
+}
+
+/// The `InstrumentSpec` of each instrument on each venue.
+#[derive(Debug, Default, Clone)]
+pub struct InstrumentCatalog {
+    instrument_specs: BTreeMap<(ExchangeId, Instrument), InstrumentSpec>,
+}
+
+impl InstrumentCatalog {
+    pub fn new() -> InstrumentCatalog {
+        InstrumentCatalog::default()
+    }
+
+    /// Add `instrument_spec`, replacing any spec of the same instrument on the
+    /// same venue.
+    pub fn insert(&mut self, instrument_spec: InstrumentSpec) {
+        let key = (instrument_spec.source_exchange, instrument_spec.instrument);
+        self.instrument_specs.insert(key, instrument_spec);
+    }
+
+    pub fn get(&self, source_exchange: ExchangeId, instrument: Instrument) -> Option<&InstrumentSpec> {
+        self.instrument_specs.get(&(source_exchange, instrument))
+    }
+
+    /// Whether the catalog has any instrument of `source_exchange`. A venue
+    /// whose catalog could not be fetched has none.
+    pub fn contains_source_exchange(&self, source_exchange: ExchangeId) -> bool {
+        self.instrument_specs.keys().any(|(spec_source_exchange, _instrument)| *spec_source_exchange == source_exchange)
+    }
+
+    /// Whether `instrument` is listed on `source_exchange` and accepts new orders.
+    pub fn is_trading(&self, source_exchange: ExchangeId, instrument: Instrument) -> bool {
+        self.get(source_exchange, instrument).is_some_and(InstrumentSpec::is_trading)
+    }
+
+    /// Check an order against the spec of `instrument` on `source_exchange`.
+    pub fn check_order(
+        &self,
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        price: Price,
+        volume: Quantity,
+    ) -> Result<(), InstrumentError> {
+        match self.get(source_exchange, instrument) {
+            Some(instrument_spec) => {
+                instrument_spec.check_order(price, volume)
+            },
+            None => {
+                Err(
+                    InstrumentError::UnlistedInstrument {
+                        source_exchange,
+                        instrument,
+                    }
+                )
+            },
+        }
+    }
+
+    pub fn instrument_specs(&self) -> impl Iterator<Item = &InstrumentSpec> {
+        self.instrument_specs.values()
+    }
+}
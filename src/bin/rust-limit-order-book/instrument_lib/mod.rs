@@ -0,0 +1,265 @@
+mod instrument_catalog;
+
+pub use instrument_catalog::InstrumentCatalog;
+pub use instrument_catalog::InstrumentSpec;
+pub use instrument_catalog::InstrumentStatus;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::OrderBookError;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::Symbol;
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InstrumentError {
+    /// The symbol is not an alias of any instrument on this exchange, and is
+    /// not made of the asset codes of a registered instrument.
+    UnknownSymbol {
+        source_exchange: ExchangeId,
+        symbol: String,
+    },
+    /// The symbol is already an alias of a different instrument on this exchange.
+    ConflictingSymbol {
+        source_exchange: ExchangeId,
+        symbol: String,
+        registered: Instrument,
+        instrument: Instrument,
+    },
+    /// The instrument is not in the catalog of this exchange.
+    UnlistedInstrument {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+    },
+    /// The exchange does not accept new orders for the instrument.
+    NotTrading {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        status: InstrumentStatus,
+    },
+    /// The price is not positive, or not a multiple of the tick size.
+    InvalidPrice {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        price: Price,
+        quote_increment: Price,
+    },
+    /// The order size is not positive, or not a multiple of the lot size.
+    InvalidOrderSize {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        volume: Quantity,
+        base_increment: Quantity,
+    },
+    BelowMinimumOrderSize {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        volume: Quantity,
+        minimum_order_size: Quantity,
+    },
+    AboveMaximumOrderSize {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        volume: Quantity,
+        maximum_order_size: Quantity,
+    },
+    BelowMinimumNotional {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        notional: Notional,
+        minimum_notional: Notional,
+    },
+    /// Any other failure of the trading rules of the venue, for example an
+    /// order which overflows when it is checked.
+    TradingRules {
+        source_exchange: ExchangeId,
+        instrument: Instrument,
+        error: Box<OrderBookError>,
+    },
+}
+
+impl std::error::Error for InstrumentError {
+
+}
+
+impl fmt::Display for InstrumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstrumentError::UnknownSymbol { source_exchange, symbol } => {
+                write!(f, "{source_exchange} symbol {symbol} is not a registered instrument")
+            },
+            InstrumentError::ConflictingSymbol { source_exchange, symbol, registered, instrument } => {
+                write!(f, "{source_exchange} symbol {symbol} is registered to {registered}, not {instrument}")
+            },
+            InstrumentError::UnlistedInstrument { source_exchange, instrument } => {
+                write!(f, "{instrument} is not listed on {source_exchange}")
+            },
+            InstrumentError::NotTrading { source_exchange, instrument, status } => {
+                write!(f, "{instrument} is not trading on {source_exchange}, its status is {status:?}")
+            },
+            InstrumentError::InvalidPrice { source_exchange, instrument, price, quote_increment } => {
+                write!(f, "price {price} of {instrument} on {source_exchange} must be a positive multiple of the tick size {quote_increment}")
+            },
+            InstrumentError::InvalidOrderSize { source_exchange, instrument, volume, base_increment } => {
+                write!(f, "order size {volume} of {instrument} on {source_exchange} must be a positive multiple of the lot size {base_increment}")
+            },
+            InstrumentError::BelowMinimumOrderSize { source_exchange, instrument, volume, minimum_order_size } => {
+                write!(f, "order size {volume} of {instrument} on {source_exchange} is below the minimum {minimum_order_size}")
+            },
+            InstrumentError::AboveMaximumOrderSize { source_exchange, instrument, volume, maximum_order_size } => {
+                write!(f, "order size {volume} of {instrument} on {source_exchange} is above the maximum {maximum_order_size}")
+            },
+            InstrumentError::BelowMinimumNotional { source_exchange, instrument, notional, minimum_notional } => {
+                write!(f, "order value {notional} of {instrument} on {source_exchange} is below the minimum {minimum_notional}")
+            },
+            InstrumentError::TradingRules { source_exchange, instrument, error } => {
+                write!(f, "{instrument} on {source_exchange}: {error}")
+            },
+        }
+    }
+}
+
+/// Maps the symbols and asset codes of each exchange to canonical instruments
+/// and assets.
+///
+/// Symbols are matched without regard to case, so `btcusd` and `BTCUSD` are
+/// the same Gemini symbol. A symbol which is not an alias is resolved from
+/// its asset codes, either side of a `-` or `/` if it has one, for example
+/// `XBT/USD`, or otherwise at any split, for example `XXBTZUSD` as `XXBT` and
+/// `ZUSD`. Each asset code is mapped through the asset aliases of the exchange,
+/// and the result must be a registered instrument.
+#[derive(Debug, Default, Clone)]
+pub struct InstrumentRegistry {
+    instruments: BTreeSet<Instrument>,
+    asset_aliases: BTreeMap<(ExchangeId, String), Symbol>,
+    symbol_aliases: BTreeMap<(ExchangeId, String), Instrument>,
+    venue_symbols: BTreeMap<(Instrument, ExchangeId), String>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> InstrumentRegistry {
+        InstrumentRegistry::default()
+    }
+
+    /// Register `instrument`, and `symbol` as its name on `source_exchange`.
+    /// The first symbol registered for each exchange is the one returned by
+    /// `venue_symbol`.
+    pub fn add_instrument(
+        &mut self,
+        instrument: Instrument,
+        source_exchange: ExchangeId,
+        symbol: &str,
+    ) -> Result<(), InstrumentError> {
+        self.add_symbol_alias(instrument, source_exchange, symbol)?;
+        self.venue_symbols
+            .entry((instrument, source_exchange))
+            .or_insert_with(|| String::from(symbol));
+        Ok(())
+    }
+
+    /// Register another name of `instrument` on `source_exchange`, for example
+    /// the symbol of its WebSocket feed.
+    pub fn add_symbol_alias(
+        &mut self,
+        instrument: Instrument,
+        source_exchange: ExchangeId,
+        symbol: &str,
+    ) -> Result<(), InstrumentError> {
+        let key = (source_exchange, symbol.to_uppercase());
+        if let Some(registered) = self.symbol_aliases.get(&key).filter(|registered| **registered != instrument) {
+            return Err(
+                InstrumentError::ConflictingSymbol {
+                    source_exchange,
+                    symbol: String::from(symbol),
+                    registered: *registered,
+                    instrument,
+                }
+            );
+        }
+        self.instruments.insert(instrument);
+        self.symbol_aliases.insert(key, instrument);
+        Ok(())
+    }
+
+    /// Register the canonical asset of each asset code of `source_exchange`,
+    /// for example `("XXBT", "BTC")`.
+    pub fn add_asset_aliases(&mut self, source_exchange: ExchangeId, asset_aliases: &[(&str, &str)]) {
+        for (asset_code, asset) in asset_aliases {
+            self.asset_aliases.insert((source_exchange, asset_code.to_uppercase()), Symbol::intern(asset));
+        }
+    }
+
+    /// The canonical asset of an asset code of `source_exchange`, if it is
+    /// an alias or an asset which has been seen before.
+    pub fn canonical_asset(&self, source_exchange: ExchangeId, asset_code: &str) -> Option<Symbol> {
+        let asset_code = asset_code.to_uppercase();
+        match self.asset_aliases.get(&(source_exchange, asset_code.clone())) {
+            Some(asset) => {
+                Some(*asset)
+            },
+            None => {
+                Symbol::lookup(&asset_code)
+            },
+        }
+    }
+
+    pub fn resolve(&self, source_exchange: ExchangeId, symbol: &str) -> Result<Instrument, InstrumentError> {
+        let key = symbol.to_uppercase();
+        if let Some(instrument) = self.symbol_aliases.get(&(source_exchange, key.clone())) {
+            return Ok(*instrument);
+        }
+
+        let asset_code_pairs: Vec<(&str, &str)> = match key.split_once(['-', '/']) {
+            Some(asset_code_pair) => {
+                vec![asset_code_pair]
+            },
+            None => {
+                (1..key.len())
+                    .filter(|index| key.is_char_boundary(*index))
+                    .map(|index| key.split_at(index))
+                    .collect()
+            },
+        };
+        asset_code_pairs
+            .into_iter()
+            .filter_map(
+                |(base_asset_code, quote_asset_code)| {
+                    let base_asset = self.canonical_asset(source_exchange, base_asset_code)?;
+                    let quote_asset = self.canonical_asset(source_exchange, quote_asset_code)?;
+                    Some(Instrument::new(base_asset, quote_asset))
+                }
+            )
+            .find(|instrument| self.instruments.contains(instrument))
+            .ok_or_else(
+                || {
+                    InstrumentError::UnknownSymbol {
+                        source_exchange,
+                        symbol: String::from(symbol),
+                    }
+                }
+            )
+    }
+
+    /// The name of `instrument` on `source_exchange`.
+    pub fn venue_symbol(&self, instrument: Instrument, source_exchange: ExchangeId) -> Option<&str> {
+        self.venue_symbols.get(&(instrument, source_exchange)).map(String::as_str)
+    }
+
+    pub fn contains(&self, instrument: Instrument) -> bool {
+        self.instruments.contains(&instrument)
+    }
+
+    pub fn instruments(&self) -> Vec<Instrument> {
+        self.instruments.iter().copied().collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,245 @@
+
+use super::*;
+
+use std::str::FromStr;
+
+use crate::limit_order_book_lib::FixedPoint;
+use crate::test_support_lib::fixed_point;
+use crate::test_support_lib::instrument_registry;
+
+
+#[test]
+fn instrument_test() {
+
+    let instrument = Instrument::intern("BTC", "USD");
+    assert_eq!(instrument.base_asset, Symbol::intern("BTC"));
+    assert_eq!(instrument.quote_asset, Symbol::intern("USD"));
+    assert_eq!(instrument.to_string(), "BTC-USD");
+    assert_eq!(format!("{instrument:?}"), "\"BTC-USD\"");
+
+    assert_eq!(Instrument::from_str("BTC-USD"), Ok(instrument));
+    assert!(Instrument::from_str("BTCUSD").is_err());
+    assert!(Instrument::from_str("BTC-").is_err());
+    assert!(Instrument::from_str("BTC-USD-PERP").is_err());
+}
+
+
+#[test]
+fn resolve_test() {
+
+    let instrument_registry = instrument_registry();
+    let btc_usd = Instrument::intern("BTC", "USD");
+    let eth_btc = Instrument::intern("ETH", "BTC");
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_gemini = ExchangeId::intern("GEMINI");
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
+
+    // Each venue spells the same instrument differently
+    assert_eq!(instrument_registry.resolve(source_exchange_coinbase, "BTC-USD"), Ok(btc_usd));
+    assert_eq!(instrument_registry.resolve(source_exchange_gemini, "btcusd"), Ok(btc_usd));
+    assert_eq!(instrument_registry.resolve(source_exchange_gemini, "BTCUSD"), Ok(btc_usd));
+    assert_eq!(instrument_registry.resolve(source_exchange_kraken, "XXBTZUSD"), Ok(btc_usd));
+    assert_eq!(instrument_registry.resolve(source_exchange_kraken, "XBT/USD"), Ok(btc_usd));
+
+    // Symbols which are not aliases are resolved from their asset codes
+    assert_eq!(instrument_registry.resolve(source_exchange_kraken, "XBTUSD"), Ok(btc_usd));
+    assert_eq!(instrument_registry.resolve(source_exchange_kraken, "ETH/XBT"), Ok(eth_btc));
+    assert_eq!(instrument_registry.resolve(source_exchange_kraken, "XETHXXBT"), Ok(eth_btc));
+    assert_eq!(instrument_registry.resolve(source_exchange_coinbase, "ETH-BTC"), Ok(eth_btc));
+
+    // Kraken's asset codes only apply to Kraken
+    assert_eq!(
+        instrument_registry.resolve(source_exchange_coinbase, "XBT-USD"),
+        Err(
+            InstrumentError::UnknownSymbol {
+                source_exchange: source_exchange_coinbase,
+                symbol: String::from("XBT-USD"),
+            }
+        )
+    );
+    // Instruments which have not been registered are not resolved
+    assert!(instrument_registry.resolve(source_exchange_kraken, "XXBTZEUR").is_err());
+    assert!(instrument_registry.resolve(source_exchange_kraken, "USD/BTC").is_err());
+
+    assert_eq!(instrument_registry.canonical_asset(source_exchange_kraken, "XXDG"), Some(Symbol::intern("DOGE")));
+    assert_eq!(instrument_registry.canonical_asset(source_exchange_kraken, "usd"), Some(Symbol::intern("USD")));
+    // Instruments are ordered by when their assets were interned, which
+    // depends on the order the tests run in
+    let instruments = instrument_registry.instruments();
+    assert_eq!(instruments.len(), 3);
+    assert!(instruments.contains(&btc_usd) && instruments.contains(&eth_btc));
+    assert!(instruments.contains(&Instrument::intern("ETH", "USD")));
+    assert!(instrument_registry.contains(btc_usd));
+}
+
+
+#[test]
+fn add_instrument_test() {
+
+    let mut instrument_registry = instrument_registry();
+    let btc_usd = Instrument::intern("BTC", "USD");
+    let btc_usdt = Instrument::intern("BTC", "USDT");
+    let source_exchange_gemini = ExchangeId::intern("GEMINI");
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
+
+    // The first symbol registered on each venue is the name of the instrument there
+    instrument_registry.add_instrument(btc_usd, source_exchange_kraken, "XBTUSD").unwrap();
+    assert_eq!(instrument_registry.venue_symbol(btc_usd, source_exchange_kraken), Some("XXBTZUSD"));
+    assert_eq!(instrument_registry.venue_symbol(btc_usd, source_exchange_gemini), Some("btcusd"));
+    assert_eq!(instrument_registry.venue_symbol(btc_usd, ExchangeId::intern("BINANCE")), None);
+
+    // Registering the same alias twice is allowed, but not for two instruments
+    instrument_registry.add_instrument(btc_usd, source_exchange_gemini, "BTCUSD").unwrap();
+    assert_eq!(
+        instrument_registry.add_instrument(btc_usdt, source_exchange_gemini, "BTCUSD"),
+        Err(
+            InstrumentError::ConflictingSymbol {
+                source_exchange: source_exchange_gemini,
+                symbol: String::from("BTCUSD"),
+                registered: btc_usd,
+                instrument: btc_usdt,
+            }
+        )
+    );
+    assert!(!instrument_registry.contains(btc_usdt));
+}
+
+
+fn instrument_spec(source_exchange: ExchangeId, instrument: Instrument) -> InstrumentSpec {
+    InstrumentSpec {
+        source_exchange,
+        symbol: instrument.to_string(),
+        instrument,
+        status: InstrumentStatus::Online,
+        trading_disabled: false,
+        quote_increment: fixed_point("0.01"),
+        base_increment: fixed_point("0.0001"),
+        minimum_order_size: Some(fixed_point("0.001")),
+        maximum_order_size: Some(fixed_point("100")),
+        minimum_notional: Some(fixed_point("10")),
+    }
+}
+
+#[test]
+fn check_order_test() {
+
+    let source_exchange = ExchangeId::intern("COINBASE");
+    let instrument = Instrument::intern("BTC", "USD");
+    let mut instrument_spec = instrument_spec(source_exchange, instrument);
+
+    assert_eq!(instrument_spec.check_order(fixed_point("64000.01"), fixed_point("0.5")), Ok(()));
+    assert_eq!(
+        instrument_spec.check_order(fixed_point("64000.015"), fixed_point("0.5")),
+        Err(
+            InstrumentError::InvalidPrice {
+                source_exchange,
+                instrument,
+                price: fixed_point("64000.015"),
+                quote_increment: fixed_point("0.01"),
+            }
+        )
+    );
+    assert!(matches!(
+        instrument_spec.check_order(fixed_point("-1"), fixed_point("0.5")),
+        Err(InstrumentError::InvalidPrice { .. })
+    ));
+    assert!(matches!(
+        instrument_spec.check_order(fixed_point("64000"), fixed_point("0.00005")),
+        Err(InstrumentError::InvalidOrderSize { .. })
+    ));
+    assert!(matches!(
+        instrument_spec.check_order(fixed_point("64000"), fixed_point("0.0005")),
+        Err(InstrumentError::BelowMinimumOrderSize { .. })
+    ));
+    assert_eq!(
+        instrument_spec.check_order(fixed_point("1"), fixed_point("101")),
+        Err(
+            InstrumentError::AboveMaximumOrderSize {
+                source_exchange,
+                instrument,
+                volume: fixed_point("101"),
+                maximum_order_size: fixed_point("100"),
+            }
+        )
+    );
+    assert_eq!(
+        instrument_spec.check_order(fixed_point("1"), fixed_point("5")),
+        Err(
+            InstrumentError::BelowMinimumNotional {
+                source_exchange,
+                instrument,
+                notional: fixed_point("5"),
+                minimum_notional: fixed_point("10"),
+            }
+        )
+    );
+    assert!(matches!(
+        instrument_spec.check_order(FixedPoint::MAX, fixed_point("0.5")),
+        Err(InstrumentError::TradingRules { .. })
+    ));
+
+    // Post only instruments still accept new orders, cancel only instruments do not
+    instrument_spec.status = InstrumentStatus::PostOnly;
+    assert!(instrument_spec.is_trading());
+    instrument_spec.status = InstrumentStatus::CancelOnly;
+    assert_eq!(
+        instrument_spec.check_order(fixed_point("64000"), fixed_point("0.5")),
+        Err(
+            InstrumentError::NotTrading {
+                source_exchange,
+                instrument,
+                status: InstrumentStatus::CancelOnly,
+            }
+        )
+    );
+    instrument_spec.status = InstrumentStatus::Online;
+    instrument_spec.trading_disabled = true;
+    assert!(!instrument_spec.is_trading());
+
+    let trading_rules = instrument_spec.trading_rules();
+    assert_eq!(trading_rules.tick_size, Some(fixed_point("0.01")));
+    assert_eq!(trading_rules.lot_size, Some(fixed_point("0.0001")));
+    assert_eq!(trading_rules.minimum_notional, Some(fixed_point("10")));
+    assert_eq!(trading_rules.price_band_basis_points, None);
+    assert_eq!(trading_rules.price_scale, Some(2));
+    assert_eq!(trading_rules.volume_scale, Some(4));
+
+    assert_eq!(InstrumentStatus::from_venue_status("open"), InstrumentStatus::Online);
+    assert_eq!(InstrumentStatus::from_venue_status("reduce_only"), InstrumentStatus::CancelOnly);
+    assert_eq!(InstrumentStatus::from_venue_status("delisted"), InstrumentStatus::Delisted);
+    assert_eq!(InstrumentStatus::from_venue_status("closed"), InstrumentStatus::Offline);
+}
+
+
+#[test]
+fn instrument_catalog_test() {
+
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
+    let btc_usd = Instrument::intern("BTC", "USD");
+    let eth_usd = Instrument::intern("ETH", "USD");
+
+    let mut instrument_catalog = InstrumentCatalog::new();
+    instrument_catalog.insert(instrument_spec(source_exchange_coinbase, btc_usd));
+    let mut delisted_instrument_spec = instrument_spec(source_exchange_coinbase, eth_usd);
+    delisted_instrument_spec.status = InstrumentStatus::Delisted;
+    instrument_catalog.insert(delisted_instrument_spec);
+
+    assert!(instrument_catalog.contains_source_exchange(source_exchange_coinbase));
+    assert!(!instrument_catalog.contains_source_exchange(source_exchange_kraken));
+    assert!(instrument_catalog.is_trading(source_exchange_coinbase, btc_usd));
+    assert!(!instrument_catalog.is_trading(source_exchange_coinbase, eth_usd));
+    assert!(!instrument_catalog.is_trading(source_exchange_kraken, btc_usd));
+    assert_eq!(instrument_catalog.instrument_specs().count(), 2);
+
+    assert_eq!(instrument_catalog.check_order(source_exchange_coinbase, btc_usd, fixed_point("64000"), fixed_point("1")), Ok(()));
+    assert_eq!(
+        instrument_catalog.check_order(source_exchange_kraken, btc_usd, fixed_point("64000"), fixed_point("1")),
+        Err(
+            InstrumentError::UnlistedInstrument {
+                source_exchange: source_exchange_kraken,
+                instrument: btc_usd,
+            }
+        )
+    );
+}
@@ -1,6 +1,4 @@
 
-use std::io::Write;
-use std::str::FromStr;
 use std::fmt;
 use std::fmt::Display;
 
@@ -8,23 +6,31 @@ use std::collections::BTreeMap;
 
 use serde::Serialize;
 use serde::Deserialize;
-use serde::Deserializer;
-use serde::de;
 
-use reqwest;
-use reqwest::header::USER_AGENT;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Notional;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::Symbol;
 
-use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
-use crate::limit_order_book_lib::OrderSide;
-use crate::limit_order_book_lib::Order;
+use crate::exchange_adapter_lib::ExchangeAdapter;
+use crate::exchange_adapter_lib::ExchangeAdapterError;
+use crate::exchange_adapter_lib::Snapshot;
+use crate::exchange_adapter_lib::de_from_str;
+use crate::exchange_adapter_lib::de_option_from_str;
+
+use crate::instrument_lib::InstrumentRegistry;
+use crate::instrument_lib::InstrumentSpec;
+use crate::instrument_lib::InstrumentStatus;
 
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KrakenBidAsk {
     #[serde(deserialize_with="de_from_str")]
-    pub price: f64,
+    pub price: Price,
     #[serde(deserialize_with="de_from_str")]
-    pub volume: f64,
+    pub volume: Quantity,
     pub timestamp: u64,
 }
 
@@ -39,13 +45,6 @@ impl Display for KrakenBidAsk {
     }
 }
 
-fn de_from_str<'de, D>(deserializer: D) -> Result<f64, D::Error>
-    where D: Deserializer<'de>
-{
-    let s = <&str>::deserialize(deserializer)?;
-    f64::from_str(s).map_err(de::Error::custom)
-}
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KrakenBook {
     pub asks: Vec<KrakenBidAsk>,
@@ -76,75 +75,194 @@ impl Display for KrakenBookAPIData {
     }
 }
 
-pub fn get_api_kraken(
-    client: &reqwest::blocking::Client,
-    user_agent: &str,
-) -> String {
-
-    // Kraken
-    let kraken_pair = "BTCUSD"; // also XXBTZUSD
-    let url_kraken = format!("https://api.kraken.com/0/public/Depth?pair={kraken_pair}");
-
-    // The behaviour I want here is for the program to panic if this call fails, hence `expect`
-    let response =
-        client
-        .get(url_kraken)
-        .header("Content-Type", "application/json")
-        .header(USER_AGENT, user_agent)
-        .send()
-        .expect("client failed to send request");
-    println!("Kraken Response Status Code: {}", response.status());
-    
-    let response_text = response.text().expect("failed to convert response to text");
-    response_text
+pub const KRAKEN_ENDPOINT: &str = "https://api.kraken.com";
+
+/// The canonical asset of each of Kraken's legacy asset codes. Assets listed
+/// before 2018 have an `X` prefix, and fiat currencies a `Z` prefix, so that
+/// BTC-USD is `XXBTZUSD`. Kraken also calls BTC `XBT` and DOGE `XDG`, without
+/// the prefix, in newer pairs and on the WebSocket feed.
+pub const KRAKEN_ASSET_CODES: &[(&str, &str)] = &[
+    ("XXBT", "BTC"),
+    ("XBT", "BTC"),
+    ("XXDG", "DOGE"),
+    ("XDG", "DOGE"),
+    ("XETC", "ETC"),
+    ("XETH", "ETH"),
+    ("XLTC", "LTC"),
+    ("XMLN", "MLN"),
+    ("XREP", "REP"),
+    ("XXLM", "XLM"),
+    ("XXMR", "XMR"),
+    ("XXRP", "XRP"),
+    ("XZEC", "ZEC"),
+    ("ZAUD", "AUD"),
+    ("ZCAD", "CAD"),
+    ("ZEUR", "EUR"),
+    ("ZGBP", "GBP"),
+    ("ZJPY", "JPY"),
+    ("ZUSD", "USD"),
+];
+
+/// The canonical asset of a Kraken asset code, for example `BTC` for `XXBT`.
+/// Codes which are not legacy codes are already canonical.
+pub fn kraken_canonical_asset(asset_code: &str) -> Symbol {
+    let asset = KRAKEN_ASSET_CODES
+        .iter()
+        .find(|(kraken_asset_code, _asset)| *kraken_asset_code == asset_code)
+        .map_or(asset_code, |(_kraken_asset_code, asset)| *asset);
+    Symbol::intern(asset)
+}
+
+/// One pair of the `AssetPairs` catalog.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KrakenAssetPair {
+    pub altname: String,
+    #[serde(default)]
+    pub wsname: Option<String>,
+    /// The asset codes of the pair, for example `XXBT` and `ZUSD`.
+    pub base: String,
+    pub quote: String,
+    pub pair_decimals: u32,
+    pub lot_decimals: u32,
+    /// The price increment, where it is not `10^-pair_decimals`.
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub tick_size: Option<Price>,
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub ordermin: Option<Quantity>,
+    #[serde(default, deserialize_with="de_option_from_str")]
+    pub costmin: Option<Notional>,
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+impl KrakenAssetPair {
+    pub fn instrument_spec(&self, pair: &str, source_exchange: ExchangeId) -> InstrumentSpec {
+        let status = match &self.status {
+            Some(status) => InstrumentStatus::from_venue_status(status),
+            None => InstrumentStatus::Online,
+        };
+        InstrumentSpec {
+            source_exchange,
+            symbol: String::from(pair),
+            instrument: Instrument::new(kraken_canonical_asset(&self.base), kraken_canonical_asset(&self.quote)),
+            status,
+            trading_disabled: false,
+            quote_increment: self.tick_size.unwrap_or(Price::new(1, self.pair_decimals)),
+            base_increment: Quantity::new(1, self.lot_decimals),
+            minimum_order_size: self.ordermin,
+            maximum_order_size: None,
+            minimum_notional: self.costmin,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KrakenAssetPairsAPIData {
+    pub error: Vec<String>,
+    pub result: BTreeMap<String, KrakenAssetPair>,
+}
+
+/// Parse the `AssetPairs` catalog into the spec of each pair, named by the
+/// key of the pair in the result, for example `XXBTZUSD`.
+pub fn parse_kraken_asset_pairs(response_text: &str, source_exchange: ExchangeId) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError> {
+    let kraken_asset_pairs = serde_json::from_str::<KrakenAssetPairsAPIData>(response_text)?;
+    if !kraken_asset_pairs.error.is_empty() {
+        let error = kraken_asset_pairs.error.join(", ");
+        return Err(ExchangeAdapterError::InvalidResponse(error));
+    }
+    Ok(
+        kraken_asset_pairs
+            .result
+            .iter()
+            .map(|(pair, kraken_asset_pair)| kraken_asset_pair.instrument_spec(pair, source_exchange))
+            .collect()
+    )
 }
 
-pub fn dump_kraken_response_text_to_file(response_text: &str) {
-    let filename = "kraken-XXBTZUSD.json";
-    let mut file = std::fs::File::create(filename).unwrap_or_else(|error| panic!("failed to create file {filename}: {error}"));
-    file.write(response_text.as_bytes()).expect("failed to write data to file");
+/// The REST book of one Kraken pair, for example `XXBTZUSD` or `XBTUSD`.
+///
+/// Kraken may key the result by a different name of the pair than the one
+/// requested, so the book is found by resolving each key of the result to an
+/// instrument. The registry needs `KRAKEN_ASSET_CODES` for this.
+#[derive(Debug)]
+pub struct KrakenAdapter {
+    pair: String,
+    source_exchange: ExchangeId,
+    endpoint: String,
 }
 
-pub fn load_kraken_book<'s>(
-    response_text: String,
-    ticker: &'s str,
-    source_exchange: &'s str,
-    limit_order_book: &mut MultiTickerLimitOrderBook<'s>,
-) {
-    let kraken_book =
-        serde_json::from_str::<KrakenBookAPIData>(&response_text)
-        .expect("failed to deserialize KrakenBook");
-
-    for (pair, book) in kraken_book.result {
-        if pair != "XXBTZUSD" {
-            println!("skipping {pair}");
-            continue;
+impl KrakenAdapter {
+    pub fn new(pair: &str, source_exchange: ExchangeId) -> KrakenAdapter {
+        KrakenAdapter {
+            pair: String::from(pair),
+            source_exchange,
+            endpoint: String::from(KRAKEN_ENDPOINT),
         }
+    }
+
+    /// Use a different base URL for the REST API, for example a sandbox.
+    pub fn with_endpoint(mut self, endpoint: &str) -> KrakenAdapter {
+        self.endpoint = String::from(endpoint.trim_end_matches('/'));
+        self
+    }
+}
+
+impl ExchangeAdapter for KrakenAdapter {
+    fn source_exchange(&self) -> ExchangeId {
+        self.source_exchange
+    }
+
+    fn symbol(&self) -> String {
+        self.pair.clone()
+    }
 
-        for bid in book.bids {
-            let price = bid.price;
-            let volume = bid.volume;
-            let order = Order::new(
-                ticker,
-                OrderSide::BUY,
-                price,
-                volume,
-                source_exchange,
-            ).expect("failed to construct Order");
-            limit_order_book.add_order(order);
+    fn url(&self) -> String {
+        let endpoint = self.endpoint.as_str();
+        let pair = self.pair.as_str();
+        format!("{endpoint}/0/public/Depth?pair={pair}")
+    }
+
+    fn dump_filename(&self) -> String {
+        let pair = self.pair.as_str();
+        format!("kraken-{pair}.json")
+    }
+
+    fn catalog_url(&self) -> String {
+        let endpoint = self.endpoint.as_str();
+        let pair = self.pair.as_str();
+        format!("{endpoint}/0/public/AssetPairs?pair={pair}")
+    }
+
+    fn parse_catalog(&self, response_text: &str) -> Result<Vec<InstrumentSpec>, ExchangeAdapterError> {
+        parse_kraken_asset_pairs(response_text, self.source_exchange)
+    }
+
+    fn parse_snapshot(&self, response_text: &str, instrument_registry: &InstrumentRegistry) -> Result<Snapshot, ExchangeAdapterError> {
+        let kraken_book = serde_json::from_str::<KrakenBookAPIData>(response_text)?;
+        if !kraken_book.error.is_empty() {
+            let error = kraken_book.error.join(", ");
+            return Err(ExchangeAdapterError::InvalidResponse(error));
         }
 
-        for ask in book.asks {
-            let price = ask.price;
-            let volume = ask.volume;
-            let order = Order::new(
-                ticker,
-                OrderSide::SELL,
-                price,
-                volume,
-                source_exchange,
-            ).expect("failed to construct Order");
-            limit_order_book.add_order(order);
+        let instrument = instrument_registry.resolve(self.source_exchange, &self.pair)?;
+        let mut book = None;
+        for (pair, pair_book) in &kraken_book.result {
+            if instrument_registry.resolve(self.source_exchange, pair)? == instrument {
+                book = Some(pair_book);
+            }
         }
+
+        let pair = self.pair.as_str();
+        let book = book.ok_or_else(
+            || {
+                ExchangeAdapterError::InvalidResponse(format!("no book for {pair}"))
+            }
+        )?;
+        Ok(
+            Snapshot {
+                bids: book.bids.iter().map(|bid| (bid.price, bid.volume)).collect(),
+                asks: book.asks.iter().map(|ask| (ask.price, ask.volume)).collect(),
+            }
+        )
     }
 }
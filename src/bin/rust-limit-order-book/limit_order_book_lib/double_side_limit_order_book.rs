@@ -2,43 +2,294 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 
-use ordered_float::NotNan;
-
 use super::SingleSideLimitOrderBook;
 use super::OrderSide;
 use super::Order;
+use super::Fill;
+use super::MatchingMode;
+use super::OrderType;
+use super::OrderId;
+use super::FixedPoint;
+use super::Price;
+use super::Quantity;
+use super::ExchangeId;
+use super::Instrument;
+use super::OrderBookError;
+use super::Quote;
+use super::QuoteLevel;
 
 
 #[derive(Debug)]
-pub struct DoubleSideLimitOrderBook<'s> {
-    ticker: &'s str,
-    buy_side_limit_order_book: SingleSideLimitOrderBook<'s>,
-    sell_side_limit_order_book: SingleSideLimitOrderBook<'s>,
+pub struct DoubleSideLimitOrderBook {
+    ticker: Instrument,
+    matching_mode: MatchingMode,
+    buy_side_limit_order_book: SingleSideLimitOrderBook,
+    sell_side_limit_order_book: SingleSideLimitOrderBook,
+    order_sides: BTreeMap<OrderId, OrderSide>,
 }
 
-impl<'s> DoubleSideLimitOrderBook<'s> {
-    pub fn new(ticker: &'s str) -> DoubleSideLimitOrderBook {
+impl DoubleSideLimitOrderBook {
+    pub fn new(ticker: Instrument) -> DoubleSideLimitOrderBook {
+        DoubleSideLimitOrderBook::new_with_matching_mode(ticker, MatchingMode::default())
+    }
+
+    pub fn new_with_matching_mode(ticker: Instrument, matching_mode: MatchingMode) -> DoubleSideLimitOrderBook {
         DoubleSideLimitOrderBook {
             ticker,
+            matching_mode,
             buy_side_limit_order_book: SingleSideLimitOrderBook::new(OrderSide::BUY),
             sell_side_limit_order_book: SingleSideLimitOrderBook::new(OrderSide::SELL),
+            order_sides: BTreeMap::new(),
+        }
+    }
+
+    /// Add an order to the book.
+    ///
+    /// When the book is in `MatchingMode::PriceTimePriority`, or when the order
+    /// is not a plain `OrderType::Limit` order, the order is first matched
+    /// against the opposite side. Only limit and post only orders rest in the
+    /// book, and only with their remaining volume (if any). Returns the fills.
+    ///
+    /// A post only order which would cross is rejected with
+    /// `OrderBookError::CrossedBook`. A fill or kill order which cannot be
+    /// completely filled is cancelled without changing the book, and rejected
+    /// with `OrderBookError::OrderKilled`. In both cases the order does not rest.
+    pub fn add_order(&mut self, mut order: Order) -> Result<Vec<Fill>, OrderBookError> {
+        self.check_new_order(&order)?;
+        order.check_volume()?;
+
+        let opposite_order_side = match order.order_side {
+            OrderSide::BUY => OrderSide::SELL,
+            OrderSide::SELL => OrderSide::BUY,
+        };
+
+        let fills = match (order.order_type, self.matching_mode) {
+            (OrderType::Limit, MatchingMode::Aggregate) => {
+                Vec::new()
+            },
+            (OrderType::PostOnly, _) => {
+                if self.single_side_limit_order_book(opposite_order_side).crosses(&order) {
+                    return Err(
+                        OrderBookError::CrossedBook {
+                            order_id: order.order_id,
+                        }
+                    );
+                }
+                Vec::new()
+            },
+            (OrderType::FillOrKill, _) => {
+                let crossing_volume =
+                    self.single_side_limit_order_book(opposite_order_side).crossing_volume(&order);
+                if crossing_volume < order.volume {
+                    return Err(
+                        OrderBookError::OrderKilled {
+                            order_id: order.order_id,
+                        }
+                    );
+                }
+                self.match_order(&mut order, opposite_order_side)?
+            },
+            (OrderType::Limit, MatchingMode::PriceTimePriority) |
+            (OrderType::Market, _) |
+            (OrderType::ImmediateOrCancel, _) => {
+                self.match_order(&mut order, opposite_order_side)?
+            },
+        };
+
+        let rests = match order.order_type {
+            OrderType::Limit | OrderType::PostOnly => {
+                fills.is_empty() || order.volume.is_positive()
+            },
+            OrderType::Market | OrderType::ImmediateOrCancel | OrderType::FillOrKill => {
+                false
+            },
+        };
+        if rests {
+            let order_id = order.order_id;
+            let order_side = order.order_side;
+            self.single_side_limit_order_book_mut(order_side).add_order(order)?;
+            self.order_sides.insert(order_id, order_side);
+        }
+
+        Ok(fills)
+    }
+
+    /// Add an order to the book without matching it, whatever its order type
+    /// and the matching mode of the book.
+    ///
+    /// This is how market data from an exchange is applied. The volume quoted
+    /// by an exchange cannot trade with the volume quoted by another exchange,
+    /// even when the two cross.
+    pub fn add_resting_order(&mut self, order: Order) -> Result<(), OrderBookError> {
+        self.check_new_order(&order)?;
+        let order_id = order.order_id;
+        let order_side = order.order_side;
+        self.single_side_limit_order_book_mut(order_side).add_order(order)?;
+        self.order_sides.insert(order_id, order_side);
+        Ok(())
+    }
+
+    /// Remove every order from `source_exchange` at one price level. Returns the
+    /// removed orders.
+    pub fn cancel_orders_by_source_exchange_at_price_level(
+        &mut self,
+        order_side: OrderSide,
+        price: Price,
+        source_exchange: ExchangeId,
+    ) -> Result<Vec<Order>, OrderBookError> {
+        let cancelled_orders =
+            self.single_side_limit_order_book_mut(order_side)
+                .cancel_orders_by_source_exchange_at_price_level(price, source_exchange)?;
+        for order in &cancelled_orders {
+            self.order_sides.remove(&order.order_id);
+        }
+        Ok(cancelled_orders)
+    }
+
+    /// Remove every order from `source_exchange` on both sides of the book.
+    /// Returns the removed orders.
+    pub fn cancel_orders_by_source_exchange(&mut self, source_exchange: ExchangeId) -> Result<Vec<Order>, OrderBookError> {
+        let mut cancelled_orders = self.buy_side_limit_order_book.cancel_orders_by_source_exchange(source_exchange)?;
+        cancelled_orders.extend(self.sell_side_limit_order_book.cancel_orders_by_source_exchange(source_exchange)?);
+        for order in &cancelled_orders {
+            self.order_sides.remove(&order.order_id);
+        }
+        Ok(cancelled_orders)
+    }
+
+    fn check_new_order(&self, order: &Order) -> Result<(), OrderBookError> {
+        if order.ticker != self.ticker {
+            return Err(
+                OrderBookError::WrongTicker {
+                    order_id: order.order_id,
+                    expected: self.ticker,
+                    actual: order.ticker,
+                }
+            );
+        }
+        if self.order_sides.contains_key(&order.order_id) {
+            return Err(
+                OrderBookError::DuplicateOrderId {
+                    order_id: order.order_id,
+                }
+            );
+        }
+        Ok(())
+    }
+
+    fn match_order(&mut self, order: &mut Order, opposite_order_side: OrderSide) -> Result<Vec<Fill>, OrderBookError> {
+        let fills = self.single_side_limit_order_book_mut(opposite_order_side).match_order(order)?;
+        for fill in &fills {
+            if !fill.maker_remaining_volume.is_positive() {
+                self.order_sides.remove(&fill.maker_order_id);
+            }
+        }
+        Ok(fills)
+    }
+
+    pub fn contains_order(&self, order_id: OrderId) -> bool {
+        self.order_sides.contains_key(&order_id)
+    }
+
+    pub fn order(&self, order_id: OrderId) -> Result<&Order, OrderBookError> {
+        let order_side = self.order_side(order_id)?;
+        self.single_side_limit_order_book(order_side).order(order_id)
+    }
+
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Result<Order, OrderBookError> {
+        let order_side = self.order_side(order_id)?;
+        let order = self.single_side_limit_order_book_mut(order_side).cancel_order(order_id)?;
+        self.order_sides.remove(&order_id);
+        Ok(order)
+    }
+
+    pub fn reduce_order(&mut self, order_id: OrderId, volume: Quantity) -> Result<Quantity, OrderBookError> {
+        let order_side = self.order_side(order_id)?;
+        let remaining_volume =
+            self.single_side_limit_order_book_mut(order_side).reduce_order(order_id, volume)?;
+        if !remaining_volume.is_positive() {
+            self.order_sides.remove(&order_id);
+        }
+        Ok(remaining_volume)
+    }
+
+    /// Change the price and volume of an order. Returns the fills.
+    ///
+    /// An order which stays at the same price is replaced in place. An order
+    /// which moves to a new price is cancelled and added again through
+    /// `add_order`, so it is matched just like a new order. A post only order
+    /// which would cross is rejected with `OrderBookError::CrossedBook`, and
+    /// stays in the book unchanged.
+    pub fn replace_order(&mut self, order_id: OrderId, price: Price, volume: Quantity) -> Result<Vec<Fill>, OrderBookError> {
+        let order_side = self.order_side(order_id)?;
+        let order = self.single_side_limit_order_book(order_side).order(order_id)?;
+        if price == order.price {
+            self.single_side_limit_order_book_mut(order_side).replace_order(order_id, price, volume)?;
+            return Ok(Vec::new());
+        }
+
+        let replacement_order = Order::new_with_order_type(
+            order_id,
+            order.ticker,
+            order_side,
+            order.order_type,
+            price,
+            volume,
+            order.source_exchange,
+        );
+        replacement_order.check_volume()?;
+        let opposite_order_side = match order_side {
+            OrderSide::BUY => OrderSide::SELL,
+            OrderSide::SELL => OrderSide::BUY,
+        };
+        if replacement_order.order_type == OrderType::PostOnly
+            && self.single_side_limit_order_book(opposite_order_side).crosses(&replacement_order)
+        {
+            return Err(
+                OrderBookError::CrossedBook {
+                    order_id,
+                }
+            );
+        }
+        self.cancel_order(order_id)?;
+        self.add_order(replacement_order)
+    }
+
+    fn order_side(&self, order_id: OrderId) -> Result<OrderSide, OrderBookError> {
+        self.order_sides
+            .get(&order_id)
+            .copied()
+            .ok_or(OrderBookError::UnknownOrderId { order_id })
+    }
+
+    fn single_side_limit_order_book(&self, order_side: OrderSide)
+        -> &SingleSideLimitOrderBook
+    {
+        match order_side {
+            OrderSide::BUY => {
+                &self.buy_side_limit_order_book
+            },
+            OrderSide::SELL => {
+                &self.sell_side_limit_order_book
+            },
         }
     }
 
-    pub fn add_order(&mut self, order: Order<'s>) {
-        assert!(order.ticker == self.ticker);
-        match order.order_side {
+    fn single_side_limit_order_book_mut(&mut self, order_side: OrderSide)
+        -> &mut SingleSideLimitOrderBook
+    {
+        match order_side {
             OrderSide::BUY => {
-                self.buy_side_limit_order_book.add_order(order)
+                &mut self.buy_side_limit_order_book
             },
             OrderSide::SELL => {
-                self.sell_side_limit_order_book.add_order(order)
-            }
+                &mut self.sell_side_limit_order_book
+            },
         }
     }
 
     pub fn total_volume_by_price_level(&self, order_side: &OrderSide)
-        -> BTreeMap<NotNan<f64>, NotNan<f64>>
+        -> BTreeMap<Price, Quantity>
     {
         match *order_side {
             OrderSide::BUY => {
@@ -51,7 +302,7 @@ impl<'s> DoubleSideLimitOrderBook<'s> {
     }
 
     pub fn total_volume_by_source_exchange(&self, order_side: &OrderSide)
-        -> BTreeMap<&str, NotNan<f64>>
+        -> BTreeMap<ExchangeId, Quantity>
     {
         match *order_side {
             OrderSide::BUY => {
@@ -64,7 +315,7 @@ impl<'s> DoubleSideLimitOrderBook<'s> {
     }
 
     pub fn total_volume_by_price_level_and_source_exchange(&self, order_side: &OrderSide)
-        -> BTreeMap<NotNan<f64>, BTreeMap<&str, NotNan<f64>>>
+        -> BTreeMap<Price, BTreeMap<ExchangeId, Quantity>>
     {
         match *order_side {
             OrderSide::BUY => {
@@ -76,38 +327,156 @@ impl<'s> DoubleSideLimitOrderBook<'s> {
         }
     }
 
+    /// The total volume of `order_side` at `limit_price` or better. For the
+    /// SELL side, this is how much can be bought before the price moves above
+    /// `limit_price`.
+    pub fn cumulative_volume_to_price(&self, order_side: &OrderSide, limit_price: Price) -> Quantity {
+        self.single_side_limit_order_book(*order_side).cumulative_volume_to_price(limit_price)
+    }
+
+    pub fn cumulative_volume_to_price_by_source_exchange(&self, order_side: &OrderSide, limit_price: Price)
+        -> BTreeMap<ExchangeId, Quantity>
+    {
+        self.single_side_limit_order_book(*order_side).cumulative_volume_to_price_by_source_exchange(limit_price)
+    }
+
+    /// The price at which the volume of `order_side`, summed from the best
+    /// price, reaches `cumulative_volume`. For the SELL side, this is the price
+    /// paid for the last unit of a BUY of `cumulative_volume`.
+    pub fn price_at_cumulative_volume(&self, order_side: &OrderSide, cumulative_volume: Quantity) -> Option<Price> {
+        self.single_side_limit_order_book(*order_side).price_at_cumulative_volume(cumulative_volume)
+    }
+
+    pub fn price_at_cumulative_volume_by_source_exchange(&self, order_side: &OrderSide, cumulative_volume: Quantity)
+        -> BTreeMap<ExchangeId, Price>
+    {
+        self.single_side_limit_order_book(*order_side).price_at_cumulative_volume_by_source_exchange(cumulative_volume)
+    }
+
+    /// The price half way between the highest bid and the lowest ask. `None`
+    /// if either side is empty.
+    pub fn mid_price(&self) -> Result<Option<Price>, OrderBookError> {
+        let (buy_price, sell_price) = match (self.best_bid_price(), self.best_ask_price()) {
+            (Some(buy_price), Some(sell_price)) => {
+                (buy_price, sell_price)
+            },
+            _ => {
+                return Ok(None);
+            },
+        };
+        buy_price
+            .checked_add(sell_price)
+            .and_then(|total_price| total_price.checked_mul(FixedPoint::new(5, 1)))
+            .map(Some)
+            .ok_or(OrderBookError::PriceOverflow { ticker: self.ticker })
+    }
+
+    /// The total volume of `order_side` within `basis_points` of the mid price.
+    ///
+    /// Returns `None` if either side is empty, since there is no mid price.
+    pub fn depth_within_basis_points(&self, order_side: &OrderSide, basis_points: u32)
+        -> Result<Option<Quantity>, OrderBookError>
+    {
+        let limit_price = self.basis_points_from_mid_price(order_side, basis_points)?;
+        Ok(limit_price.map(|limit_price| self.cumulative_volume_to_price(order_side, limit_price)))
+    }
+
+    /// The same as `depth_within_basis_points` for each exchange. The mid price
+    /// is the mid price of all exchanges together.
+    pub fn depth_within_basis_points_by_source_exchange(&self, order_side: &OrderSide, basis_points: u32)
+        -> Result<BTreeMap<ExchangeId, Quantity>, OrderBookError>
+    {
+        match self.basis_points_from_mid_price(order_side, basis_points)? {
+            Some(limit_price) => {
+                Ok(self.cumulative_volume_to_price_by_source_exchange(order_side, limit_price))
+            },
+            None => {
+                Ok(BTreeMap::new())
+            },
+        }
+    }
+
+    /// The price `basis_points` below the mid price for the BUY side, or above
+    /// it for the SELL side.
+    fn basis_points_from_mid_price(&self, order_side: &OrderSide, basis_points: u32)
+        -> Result<Option<Price>, OrderBookError>
+    {
+        let mid_price = match self.mid_price()? {
+            Some(mid_price) => mid_price,
+            None => return Ok(None),
+        };
+        let basis_points = i128::from(basis_points);
+        let multiplier = match *order_side {
+            OrderSide::BUY => FixedPoint::new(10000 - basis_points, 4),
+            OrderSide::SELL => FixedPoint::new(10000 + basis_points, 4),
+        };
+        mid_price
+            .checked_mul(multiplier)
+            .map(Some)
+            .ok_or(OrderBookError::PriceOverflow { ticker: self.ticker })
+    }
+
     pub fn clear(&mut self) {
         self.buy_side_limit_order_book.clear();
         self.sell_side_limit_order_book.clear();
+        self.order_sides.clear();
     }
 
-    pub fn highest_bid_price_by_exchange(&self) -> BTreeMap<&str, NotNan<f64>> {
+    pub fn highest_bid_price_by_exchange(&self) -> BTreeMap<ExchangeId, Price> {
         self.buy_side_limit_order_book.highest_price_by_exchange()
     }
 
-    pub fn lowest_ask_price_by_exchange(&self) -> BTreeMap<&str, NotNan<f64>> {
+    pub fn lowest_ask_price_by_exchange(&self) -> BTreeMap<ExchangeId, Price> {
         self.sell_side_limit_order_book.lowest_price_by_exchange()
     }
 
-    pub fn spread(&mut self) -> Option<NotNan<f64>> {
+    pub fn best_bid_price(&self) -> Option<Price> {
+        self.buy_side_limit_order_book.highest_price()
+    }
+
+    pub fn best_ask_price(&self) -> Option<Price> {
+        self.sell_side_limit_order_book.lowest_price()
+    }
+
+    /// The best bid and ask across every exchange, with the exchanges which
+    /// contribute to each.
+    pub fn quote(&self) -> Quote {
+        let best_bid = self.best_bid_price().and_then(|price| self.quote_level(OrderSide::BUY, price));
+        let best_ask = self.best_ask_price().and_then(|price| self.quote_level(OrderSide::SELL, price));
+        Quote::new(self.ticker, best_bid, best_ask)
+    }
+
+    fn quote_level(&self, order_side: OrderSide, price: Price) -> Option<QuoteLevel> {
+        let price_level = self.single_side_limit_order_book(order_side).price_level(price)?;
+        let (price, volume) = price_level.total_volume_with_price_level();
+        Some(
+            QuoteLevel {
+                price,
+                volume,
+                volume_by_source_exchange: price_level.total_volume_by_source_exchange(),
+            }
+        )
+    }
+
+    pub fn spread(&self) -> Result<Option<Price>, OrderBookError> {
         let buy_price = self.buy_side_limit_order_book.highest_price();
         let sell_price = self.sell_side_limit_order_book.lowest_price();
         match (buy_price, sell_price) {
             (Some(buy_price), Some(sell_price)) => {
-                Some(sell_price - buy_price)
+                self.checked_spread(buy_price, sell_price).map(Some)
             },
             _ => {
-                None
+                Ok(None)
             },
         }
     }
 
-    pub fn spread_by_exchange(&mut self) -> BTreeMap<&str, Option<NotNan<f64>>> {
+    pub fn spread_by_exchange(&self) -> Result<BTreeMap<ExchangeId, Option<Price>>, OrderBookError> {
         let buy_price_by_exchange = self.buy_side_limit_order_book.highest_price_by_exchange();
         let sell_price_by_exchange = self.sell_side_limit_order_book.lowest_price_by_exchange();
 
-        let buy_price_exchanges = buy_price_by_exchange.keys().cloned().collect::<BTreeSet<&str>>();
-        let sell_price_exchanges = sell_price_by_exchange.keys().cloned().collect::<BTreeSet<&str>>();
+        let buy_price_exchanges = buy_price_by_exchange.keys().cloned().collect::<BTreeSet<ExchangeId>>();
+        let sell_price_exchanges = sell_price_by_exchange.keys().cloned().collect::<BTreeSet<ExchangeId>>();
         let exchanges = buy_price_exchanges.union(&sell_price_exchanges);
 
         let mut spread_by_exchange = BTreeMap::new();
@@ -120,7 +489,7 @@ impl<'s> DoubleSideLimitOrderBook<'s> {
 
             match (optional_buy_price, optional_sell_price) {
                 (Some(buy_price), Some(sell_price)) => {
-                    let spread = sell_price - buy_price;
+                    let spread = self.checked_spread(*buy_price, *sell_price)?;
                     *existing_entry = Some(spread);
                 },
                 _ => {
@@ -129,6 +498,12 @@ impl<'s> DoubleSideLimitOrderBook<'s> {
             }
         }
 
-        spread_by_exchange
+        Ok(spread_by_exchange)
+    }
+
+    fn checked_spread(&self, buy_price: Price, sell_price: Price) -> Result<Price, OrderBookError> {
+        sell_price
+            .checked_sub(buy_price)
+            .ok_or(OrderBookError::PriceOverflow { ticker: self.ticker })
     }
 }
\ No newline at end of file
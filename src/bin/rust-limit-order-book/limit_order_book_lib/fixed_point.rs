@@ -0,0 +1,496 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::ops::Sub;
+use std::ops::SubAssign;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde::Serializer;
+
+
+/// The largest number of decimal places which can be parsed.
+pub const MAX_SCALE: u32 = 18;
+
+/// A signed fixed point decimal number, `mantissa * 10^(-scale)`.
+///
+/// Each value carries its own scale, which is the number of decimal places it
+/// was parsed with. Exchanges quote each instrument with a fixed number of
+/// decimal places, so values parsed from the same instrument share a scale.
+///
+/// Values with different scales compare equal when they represent the same
+/// number, so `100.5` and `100.50` are the same key in a `BTreeMap`.
+///
+/// Addition, subtraction and multiplication are exact. The scale of a sum is
+/// the larger scale of the two operands, and the scale of a product is the sum
+/// of the scales of the two operands. Overflow panics, in the same way as
+/// integer overflow in a debug build, so values from a feed or an order should
+/// go through the `checked_` methods, which return `None` instead. Division is
+/// not exact, so it is only available through `checked_div_with_scale`.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FixedPoint {
+    mantissa: i128,
+    scale: u32,
+}
+
+pub type Price = FixedPoint;
+pub type Quantity = FixedPoint;
+
+/// A price multiplied by a quantity, for example the dollar cost of buying some BTC.
+pub type Notional = FixedPoint;
+
+impl FixedPoint {
+    pub const ZERO: FixedPoint = FixedPoint { mantissa: 0, scale: 0 };
+
+    /// Greater than any other value. Used as the price of market BUY orders.
+    pub const MAX: FixedPoint = FixedPoint { mantissa: i128::MAX, scale: 0 };
+
+    /// Less than any other value. Used as the price of market SELL orders.
+    pub const MIN: FixedPoint = FixedPoint { mantissa: i128::MIN, scale: 0 };
+
+    pub fn new(mantissa: i128, scale: u32) -> FixedPoint {
+        FixedPoint {
+            mantissa,
+            scale,
+        }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Parse a string which may be in exponent notation, for example `1E-8`,
+    /// which some exchanges send for small increments.
+    pub fn from_str_with_exponent(s: &str) -> Result<FixedPoint, FixedPointParseError> {
+        let (significand, exponent) = match s.split_once(['e', 'E']) {
+            Some((significand, exponent)) => {
+                (significand, exponent)
+            },
+            None => {
+                return FixedPoint::from_str(s);
+            },
+        };
+        let error = || {
+            FixedPointParseError {
+                input: String::from(s),
+            }
+        };
+        let significand = FixedPoint::from_str(significand).map_err(|_| error())?;
+        let exponent: i64 = exponent.parse().map_err(|_| error())?;
+
+        // significand * 10^exponent = mantissa * 10^(exponent - scale)
+        let scale = i64::from(significand.scale) - exponent;
+        if scale >= 0 {
+            let scale = u32::try_from(scale).ok().filter(|scale| *scale <= MAX_SCALE).ok_or_else(error)?;
+            Ok(
+                FixedPoint {
+                    mantissa: significand.mantissa,
+                    scale,
+                }
+            )
+        } else {
+            let multiplier = u32::try_from(-scale).ok().and_then(|power| 10i128.checked_pow(power)).ok_or_else(error)?;
+            Ok(
+                FixedPoint {
+                    mantissa: significand.mantissa.checked_mul(multiplier).ok_or_else(error)?,
+                    scale: 0,
+                }
+            )
+        }
+    }
+
+    /// Convert to a different scale without losing precision.
+    ///
+    /// Returns `None` if the value cannot be represented exactly with `scale`
+    /// decimal places.
+    pub fn with_scale(self, scale: u32) -> Option<FixedPoint> {
+        self.rescaled_mantissa(scale).map(
+            |mantissa| {
+                FixedPoint {
+                    mantissa,
+                    scale,
+                }
+            }
+        )
+    }
+
+    /// The smallest scale which represents this value exactly, for example 2
+    /// for `0.0100`.
+    pub fn significant_scale(&self) -> u32 {
+        let mut mantissa = self.mantissa;
+        let mut scale = self.scale;
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.mantissa > 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    pub fn checked_add(self, rhs: FixedPoint) -> Option<FixedPoint> {
+        let (mantissa, rhs_mantissa, scale) = self.rescaled_mantissas(&rhs)?;
+        Some(
+            FixedPoint {
+                mantissa: mantissa.checked_add(rhs_mantissa)?,
+                scale,
+            }
+        )
+    }
+
+    pub fn checked_sub(self, rhs: FixedPoint) -> Option<FixedPoint> {
+        let (mantissa, rhs_mantissa, scale) = self.rescaled_mantissas(&rhs)?;
+        Some(
+            FixedPoint {
+                mantissa: mantissa.checked_sub(rhs_mantissa)?,
+                scale,
+            }
+        )
+    }
+
+    pub fn checked_mul(self, rhs: FixedPoint) -> Option<FixedPoint> {
+        Some(
+            FixedPoint {
+                mantissa: self.mantissa.checked_mul(rhs.mantissa)?,
+                scale: self.scale.checked_add(rhs.scale)?,
+            }
+        )
+    }
+
+    pub fn checked_neg(self) -> Option<FixedPoint> {
+        Some(
+            FixedPoint {
+                mantissa: self.mantissa.checked_neg()?,
+                scale: self.scale,
+            }
+        )
+    }
+
+    /// Divide by `divisor`, giving a result with `scale` decimal places.
+    ///
+    /// Division is not exact, so the result is truncated towards zero. Returns
+    /// `None` if `divisor` is zero or if the result overflows.
+    pub fn checked_div_with_scale(self, divisor: FixedPoint, scale: u32) -> Option<FixedPoint> {
+        if divisor.is_zero() {
+            return None;
+        }
+        // self / divisor = (mantissa * 10^(divisor.scale + scale - self.scale) / divisor.mantissa) * 10^(-scale)
+        let numerator_scale = i64::from(divisor.scale) + i64::from(scale) - i64::from(self.scale);
+        let (numerator, denominator) = if numerator_scale >= 0 {
+            let multiplier = 10i128.checked_pow(u32::try_from(numerator_scale).ok()?)?;
+            (self.mantissa.checked_mul(multiplier)?, divisor.mantissa)
+        } else {
+            let multiplier = 10i128.checked_pow(u32::try_from(-numerator_scale).ok()?)?;
+            (self.mantissa, divisor.mantissa.checked_mul(multiplier)?)
+        };
+        Some(
+            FixedPoint {
+                mantissa: numerator.checked_div(denominator)?,
+                scale,
+            }
+        )
+    }
+
+    /// Whether this is a whole number of `increment`, for example whether a
+    /// price is on the tick grid. Only zero is a multiple of zero.
+    pub fn is_multiple_of(&self, increment: FixedPoint) -> bool {
+        self.checked_is_multiple_of(increment).expect("fixed point overflow")
+    }
+
+    /// The same as `is_multiple_of`, returning `None` if the two values cannot
+    /// be brought to the same scale.
+    pub fn checked_is_multiple_of(&self, increment: FixedPoint) -> Option<bool> {
+        let (mantissa, increment_mantissa, _scale) = self.rescaled_mantissas(&increment)?;
+        // `checked_rem` fails for a zero increment, and for `i128::MIN % -1`
+        match mantissa.checked_rem(increment_mantissa) {
+            Some(remainder) => {
+                Some(remainder == 0)
+            },
+            None => {
+                Some(mantissa == 0 || increment_mantissa == -1)
+            },
+        }
+    }
+
+    /// The largest multiple of `increment` which is not greater than this
+    /// value, for example the tick at or below a price. A zero increment
+    /// leaves the value unchanged.
+    pub fn floor_to_multiple_of(self, increment: FixedPoint) -> FixedPoint {
+        self.checked_floor_to_multiple_of(increment).expect("fixed point overflow")
+    }
+
+    pub fn checked_floor_to_multiple_of(self, increment: FixedPoint) -> Option<FixedPoint> {
+        let (mantissa, increment_mantissa, scale) = self.rescaled_mantissas(&increment)?;
+        if increment_mantissa == 0 {
+            return Some(self);
+        }
+        let increment_mantissa = increment_mantissa.checked_abs()?;
+        Some(
+            FixedPoint {
+                mantissa: mantissa.checked_div_euclid(increment_mantissa)?.checked_mul(increment_mantissa)?,
+                scale,
+            }
+        )
+    }
+
+    /// The smallest multiple of `increment` which is not less than this value.
+    /// A zero increment leaves the value unchanged.
+    pub fn ceil_to_multiple_of(self, increment: FixedPoint) -> FixedPoint {
+        self.checked_ceil_to_multiple_of(increment).expect("fixed point overflow")
+    }
+
+    pub fn checked_ceil_to_multiple_of(self, increment: FixedPoint) -> Option<FixedPoint> {
+        self.checked_neg()?.checked_floor_to_multiple_of(increment)?.checked_neg()
+    }
+
+    /// The smallest positive value which is a multiple of both this value and
+    /// `other`, for example `0.05` for `0.01` and `0.025`. A zero value has no
+    /// multiples, so the other value is returned. Returns `None` on overflow.
+    pub fn checked_least_common_multiple(self, other: FixedPoint) -> Option<FixedPoint> {
+        let (mantissa, other_mantissa, scale) = self.rescaled_mantissas(&other)?;
+        let (mantissa, other_mantissa) = (mantissa.checked_abs()?, other_mantissa.checked_abs()?);
+        if mantissa == 0 || other_mantissa == 0 {
+            return Some(FixedPoint { mantissa: std::cmp::max(mantissa, other_mantissa), scale });
+        }
+        let (mut divisor, mut remainder) = (mantissa, other_mantissa);
+        while remainder != 0 {
+            (divisor, remainder) = (remainder, divisor % remainder);
+        }
+        Some(
+            FixedPoint {
+                mantissa: (mantissa / divisor).checked_mul(other_mantissa)?,
+                scale,
+            }
+        )
+    }
+
+    fn rescaled_mantissa(&self, scale: u32) -> Option<i128> {
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => {
+                Some(self.mantissa)
+            },
+            Ordering::Greater => {
+                let multiplier = 10i128.checked_pow(scale - self.scale)?;
+                self.mantissa.checked_mul(multiplier)
+            },
+            Ordering::Less => {
+                let divisor = 10i128.checked_pow(self.scale - scale)?;
+                if self.mantissa % divisor == 0 {
+                    Some(self.mantissa / divisor)
+                } else {
+                    None
+                }
+            },
+        }
+    }
+
+    fn rescaled_mantissas(&self, other: &FixedPoint) -> Option<(i128, i128, u32)> {
+        let scale = std::cmp::max(self.scale, other.scale);
+        Some((self.rescaled_mantissa(scale)?, other.rescaled_mantissa(scale)?, scale))
+    }
+}
+
+impl PartialEq for FixedPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FixedPoint {
+
+}
+
+impl PartialOrd for FixedPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FixedPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = std::cmp::max(self.scale, other.scale);
+        match (self.rescaled_mantissa(scale), other.rescaled_mantissa(scale)) {
+            (Some(mantissa), Some(other_mantissa)) => {
+                mantissa.cmp(&other_mantissa)
+            },
+            // One of the two values already has the larger scale, so at most one
+            // of them can overflow. A value which overflows has a larger magnitude
+            // than the other value, so its sign decides the ordering.
+            (None, _) => {
+                self.mantissa.signum().cmp(&0)
+            },
+            (_, None) => {
+                0.cmp(&other.mantissa.signum())
+            },
+        }
+    }
+}
+
+impl Add for FixedPoint {
+    type Output = FixedPoint;
+    fn add(self, rhs: FixedPoint) -> FixedPoint {
+        self.checked_add(rhs).expect("fixed point overflow")
+    }
+}
+
+impl AddAssign for FixedPoint {
+    fn add_assign(&mut self, rhs: FixedPoint) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for FixedPoint {
+    type Output = FixedPoint;
+    fn sub(self, rhs: FixedPoint) -> FixedPoint {
+        self.checked_sub(rhs).expect("fixed point overflow")
+    }
+}
+
+impl SubAssign for FixedPoint {
+    fn sub_assign(&mut self, rhs: FixedPoint) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for FixedPoint {
+    type Output = FixedPoint;
+    fn mul(self, rhs: FixedPoint) -> FixedPoint {
+        self.checked_mul(rhs).expect("fixed point overflow")
+    }
+}
+
+impl Neg for FixedPoint {
+    type Output = FixedPoint;
+    fn neg(self) -> FixedPoint {
+        self.checked_neg().expect("fixed point overflow")
+    }
+}
+
+impl From<i64> for FixedPoint {
+    fn from(value: i64) -> FixedPoint {
+        FixedPoint {
+            mantissa: i128::from(value),
+            scale: 0,
+        }
+    }
+}
+
+impl Sum for FixedPoint {
+    fn sum<I: Iterator<Item = FixedPoint>>(iter: I) -> FixedPoint {
+        iter.fold(FixedPoint::ZERO, |total, value| total + value)
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let magnitude = self.mantissa.unsigned_abs();
+        match 10u128.checked_pow(self.scale) {
+            Some(divisor) if self.scale > 0 => {
+                let integer_part = magnitude / divisor;
+                let fractional_part = magnitude % divisor;
+                let width = self.scale as usize;
+                write!(f, "{sign}{integer_part}.{fractional_part:0width$}")
+            },
+            _ => {
+                write!(f, "{sign}{magnitude}")
+            },
+        }
+    }
+}
+
+impl Serialize for FixedPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixedPointParseError {
+    input: String,
+}
+
+impl std::error::Error for FixedPointParseError {
+
+}
+
+impl fmt::Display for FixedPointParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let input = self.input.as_str();
+        write!(f, "{input} is not a valid fixed point number")
+    }
+}
+
+impl FromStr for FixedPoint {
+    type Err = FixedPointParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            FixedPointParseError {
+                input: String::from(s),
+            }
+        };
+
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+
+        let (integer_part, fractional_part) = match unsigned.split_once('.') {
+            Some((integer_part, fractional_part)) => (integer_part, fractional_part),
+            None => (unsigned, ""),
+        };
+
+        let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if integer_part.is_empty() && fractional_part.is_empty() {
+            return Err(error());
+        }
+        if !all_digits(integer_part) || !all_digits(fractional_part) {
+            return Err(error());
+        }
+
+        let scale = fractional_part.len() as u32;
+        if scale > MAX_SCALE {
+            return Err(error());
+        }
+
+        let mut mantissa: i128 = 0;
+        for byte in integer_part.bytes().chain(fractional_part.bytes()) {
+            let digit = i128::from(byte - b'0');
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|mantissa| mantissa.checked_add(digit))
+                .ok_or_else(error)?;
+        }
+
+        if negative {
+            mantissa = -mantissa;
+        }
+
+        Ok(
+            FixedPoint {
+                mantissa,
+                scale,
+            }
+        )
+    }
+}
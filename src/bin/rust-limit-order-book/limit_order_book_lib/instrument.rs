@@ -0,0 +1,83 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::Symbol;
+
+
+/// A canonical instrument, for example `BTC-USD`, which is the key of each book
+/// in `MultiTickerLimitOrderBook`.
+///
+/// Each venue spells the same instrument differently, for example `BTC-USD`,
+/// `btcusd` and `XXBTZUSD`. `instrument_lib::InstrumentRegistry` resolves the
+/// symbols of each venue to the canonical instrument.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Instrument {
+    pub base_asset: Symbol,
+    pub quote_asset: Symbol,
+}
+
+impl Instrument {
+    pub fn new(base_asset: Symbol, quote_asset: Symbol) -> Instrument {
+        Instrument {
+            base_asset,
+            quote_asset,
+        }
+    }
+
+    /// Return the instrument of the canonical asset codes `base_asset` and
+    /// `quote_asset`, registering them if they have not been seen before.
+    pub fn intern(base_asset: &str, quote_asset: &str) -> Instrument {
+        Instrument::new(Symbol::intern(base_asset), Symbol::intern(quote_asset))
+    }
+}
+
+impl fmt::Debug for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+impl fmt::Display for Instrument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let base_asset = self.base_asset;
+        let quote_asset = self.quote_asset;
+        write!(f, "{base_asset}-{quote_asset}")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InstrumentParseError {
+    input: String,
+}
+
+impl std::error::Error for InstrumentParseError {
+
+}
+
+impl fmt::Display for InstrumentParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let input = self.input.as_str();
+        write!(f, "{input} is not a valid instrument, expected BASE-QUOTE")
+    }
+}
+
+impl FromStr for Instrument {
+    type Err = InstrumentParseError;
+
+    /// Parse the canonical form `BASE-QUOTE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('-') {
+            Some((base_asset, quote_asset))
+                if !base_asset.is_empty() && !quote_asset.is_empty() && !quote_asset.contains('-') => {
+                Ok(Instrument::intern(base_asset, quote_asset))
+            },
+            _ => {
+                Err(
+                    InstrumentParseError {
+                        input: String::from(s),
+                    }
+                )
+            },
+        }
+    }
+}
@@ -1,10 +1,30 @@
 
+mod fixed_point;
+mod instrument;
+mod order_book_error;
 mod price_level;
+mod quote;
+mod symbol;
+mod trading_rules;
 mod single_side_limit_order_book;
 mod double_side_limit_order_book;
 mod multi_ticker_limit_order_book;
 
+pub use fixed_point::FixedPoint;
+pub use fixed_point::FixedPointParseError;
+pub use fixed_point::Notional;
+pub use fixed_point::Price;
+pub use fixed_point::Quantity;
+pub use instrument::Instrument;
+pub use instrument::InstrumentParseError;
+pub use order_book_error::OrderBookError;
 pub use price_level::PriceLevel;
+pub use quote::MarketState;
+pub use quote::Quote;
+pub use quote::QuoteLevel;
+pub use symbol::ExchangeId;
+pub use symbol::Symbol;
+pub use trading_rules::TradingRules;
 pub use single_side_limit_order_book::SingleSideLimitOrderBook;
 pub use double_side_limit_order_book::DoubleSideLimitOrderBook;
 pub use multi_ticker_limit_order_book::MultiTickerLimitOrderBook;
@@ -12,10 +32,8 @@ pub use multi_ticker_limit_order_book::MultiTickerLimitOrderBook;
 use std::fmt;
 use std::str::FromStr;
 
-use ordered_float::NotNan;
 
-
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub enum OrderSide {
     BUY,
     SELL,
@@ -58,35 +76,168 @@ impl FromStr for OrderSide {
     }
 }
 
+/// Controls what a book does with an incoming order which crosses the opposite side.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum MatchingMode {
+    /// Orders are stored without being matched. This is the behaviour wanted when
+    /// merging the books of several exchanges, where crossing orders come from
+    /// different venues and cannot trade with each other.
+    #[default]
+    Aggregate,
+    /// Incoming orders are matched against the opposite side in price-time
+    /// priority. Any remaining volume rests in the book.
+    PriceTimePriority,
+}
+
+/// Order type and time in force of an order.
+///
+/// All order types other than `Limit` are matched against the opposite side of
+/// the book regardless of the `MatchingMode` of the book, because their
+/// behaviour is defined by what they would trade with.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum OrderType {
+    /// Rests in the book. Matched against the opposite side only when the book
+    /// is in `MatchingMode::PriceTimePriority`.
+    #[default]
+    Limit,
+    /// Matched against the opposite side at any price. Any unfilled volume is
+    /// cancelled.
+    Market,
+    /// Matched against the opposite side up to the limit price. Any unfilled
+    /// volume is cancelled.
+    ImmediateOrCancel,
+    /// Matched against the opposite side up to the limit price only if the
+    /// whole volume can be filled. Otherwise the order is rejected with
+    /// `OrderBookError::OrderKilled` and the book is left unchanged.
+    FillOrKill,
+    /// Rests in the book without matching. Rejected if it would cross the
+    /// opposite side.
+    PostOnly,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct OrderId(pub u64);
+
+impl fmt::Display for OrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let order_id = self.0;
+        write!(f, "{order_id}")
+    }
+}
+
 #[derive(Debug)]
-pub struct Order<'s> {
-    ticker: &'s str,
+pub struct Order {
+    order_id: OrderId,
+    ticker: Instrument,
     order_side: OrderSide,
-    price: NotNan<f64>,
-    volume: NotNan<f64>,
-    source_exchange: &'s str, // TODO: know the price to buy/sell X BTC but don't know who has it
+    order_type: OrderType,
+    price: Price,
+    volume: Quantity,
+    source_exchange: ExchangeId, // TODO: know the price to buy/sell X BTC but don't know who has it
 }
     
-impl<'s> Order<'s> {
+impl Order {
     pub fn new(
-        ticker: &'s str,
+        order_id: OrderId,
+        ticker: Instrument,
+        order_side: OrderSide,
+        price: Price,
+        volume: Quantity,
+        source_exchange: ExchangeId,
+    ) -> Order {
+        Order::new_with_order_type(
+            order_id,
+            ticker,
+            order_side,
+            OrderType::Limit,
+            price,
+            volume,
+            source_exchange,
+        )
+    }
+
+    /// Construct an order of any `OrderType`.
+    ///
+    /// For `OrderType::Market` the price is ignored, and replaced with a price
+    /// which crosses every price level on the opposite side.
+    pub fn new_with_order_type(
+        order_id: OrderId,
+        ticker: Instrument,
         order_side: OrderSide,
-        price: f64,
-        volume: f64,
-        source_exchange: &'s str,
-    ) -> Result<Order<'s>, ordered_float::FloatIsNan> {
-        Ok(
-            Order {
-                ticker,
-                order_side,
-                price: NotNan::new(price)?,
-                volume: NotNan::new(volume)?,
-                source_exchange,
+        order_type: OrderType,
+        price: Price,
+        volume: Quantity,
+        source_exchange: ExchangeId,
+    ) -> Order {
+        let price = match (order_type, order_side) {
+            (OrderType::Market, OrderSide::BUY) => Price::MAX,
+            (OrderType::Market, OrderSide::SELL) => Price::MIN,
+            _ => price,
+        };
+        Order {
+            order_id,
+            ticker,
+            order_side,
+            order_type,
+            price,
+            volume,
+            source_exchange,
+        }
+    }
+
+    pub fn new_market(
+        order_id: OrderId,
+        ticker: Instrument,
+        order_side: OrderSide,
+        volume: Quantity,
+        source_exchange: ExchangeId,
+    ) -> Order {
+        Order::new_with_order_type(
+            order_id,
+            ticker,
+            order_side,
+            OrderType::Market,
+            Price::ZERO,
+            volume,
+            source_exchange,
+        )
+    }
+
+    pub fn order_id(&self) -> OrderId {
+        self.order_id
+    }
+
+    fn check_volume(&self) -> Result<(), OrderBookError> {
+        check_volume(self.order_id, self.volume)
+    }
+}
+
+fn check_volume(order_id: OrderId, volume: Quantity) -> Result<(), OrderBookError> {
+    if volume.is_positive() {
+        Ok(())
+    } else {
+        Err(
+            OrderBookError::NonPositiveVolume {
+                order_id,
+                volume,
             }
         )
     }
 }
 
+/// A trade between a resting (maker) order and an incoming (taker) order.
+///
+/// Fills always take place at the price of the maker order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fill {
+    pub maker_order_id: OrderId,
+    pub taker_order_id: OrderId,
+    pub maker_source_exchange: ExchangeId,
+    pub price: Price,
+    pub volume: Quantity,
+    pub maker_remaining_volume: Quantity,
+}
+
 
 #[cfg(test)]
 mod tests;
@@ -1,74 +1,415 @@
 
 use std::collections::BTreeMap;
 
-use ordered_float::NotNan;
-
 use super::DoubleSideLimitOrderBook;
 use super::OrderSide;
 use super::Order;
+use super::Fill;
+use super::MatchingMode;
+use super::OrderId;
+use super::Price;
+use super::Quantity;
+use super::ExchangeId;
+use super::Instrument;
+use super::OrderBookError;
+use super::OrderType;
+use super::Quote;
+use super::TradingRules;
+
 
+/// The volume of each price level of one side of the book of one venue.
+type VenuePriceLevels = BTreeMap<Price, Quantity>;
 
 #[derive(Debug)]
-pub struct MultiTickerLimitOrderBook<'s> {
-    double_limit_order_books: BTreeMap<&'s str, DoubleSideLimitOrderBook<'s>>,
+pub struct MultiTickerLimitOrderBook {
+    double_limit_order_books: BTreeMap<Instrument, DoubleSideLimitOrderBook>,
+    order_tickers: BTreeMap<OrderId, Instrument>,
+    trading_rules: BTreeMap<Instrument, TradingRules>,
+    /// The volume of each level of venue data at the price the venue sent,
+    /// before it is snapped to the grid of the trading rules.
+    venue_price_levels: BTreeMap<(Instrument, ExchangeId, OrderSide), VenuePriceLevels>,
+    next_order_id: u64,
+    matching_mode: MatchingMode,
+}
+
+impl Default for MultiTickerLimitOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<'s> MultiTickerLimitOrderBook<'s> {
-    pub fn new() -> MultiTickerLimitOrderBook<'s> {
+impl MultiTickerLimitOrderBook {
+    pub fn new() -> MultiTickerLimitOrderBook {
+        MultiTickerLimitOrderBook::new_with_matching_mode(MatchingMode::default())
+    }
+
+    pub fn new_with_matching_mode(matching_mode: MatchingMode) -> MultiTickerLimitOrderBook {
         MultiTickerLimitOrderBook {
             double_limit_order_books: BTreeMap::new(),
+            order_tickers: BTreeMap::new(),
+            trading_rules: BTreeMap::new(),
+            venue_price_levels: BTreeMap::new(),
+            next_order_id: 0,
+            matching_mode,
         }
     }
 
-    pub fn add_order(&mut self, order: Order<'s>) {
-        let ticker = &order.ticker;
+    /// Allocate a new order id which has not been returned by this function before.
+    ///
+    /// Orders which are constructed with ids from some other source may collide
+    /// with ids returned by this function.
+    pub fn next_order_id(&mut self) -> OrderId {
+        self.next_order_id += 1;
+        OrderId(self.next_order_id)
+    }
+
+    /// Set the trading rules which `add_order` and `replace_order` enforce for
+    /// `ticker`, replacing any rules set before. Orders already in the book are
+    /// not checked.
+    pub fn set_trading_rules(&mut self, ticker: Instrument, trading_rules: TradingRules) {
+        self.trading_rules.insert(ticker, trading_rules);
+    }
+
+    pub fn trading_rules(&self, ticker: Instrument) -> Option<&TradingRules> {
+        self.trading_rules.get(&ticker)
+    }
+
+    /// The smallest volume of `ticker` which can be traded, from its trading
+    /// rules. See `TradingRules::volume_increment`.
+    pub fn volume_increment(&self, ticker: Instrument) -> Quantity {
+        match self.trading_rules.get(&ticker) {
+            Some(trading_rules) => trading_rules.volume_increment(),
+            None => TradingRules::new().volume_increment(),
+        }
+    }
+
+    /// Add an order, which must follow the trading rules of its ticker if it
+    /// has any. Returns the fills.
+    pub fn add_order(&mut self, order: Order) -> Result<Vec<Fill>, OrderBookError> {
+        let ticker = order.ticker;
+        let order_id = order.order_id;
+        if self.order_tickers.contains_key(&order_id) {
+            return Err(
+                OrderBookError::DuplicateOrderId {
+                    order_id,
+                }
+            );
+        }
+        if let Some(trading_rules) = self.trading_rules.get(&ticker) {
+            let mid_price = match self.double_limit_order_books.get(&ticker) {
+                Some(double_side_limit_order_book) => double_side_limit_order_book.mid_price()?,
+                None => None,
+            };
+            trading_rules.check_order(order_id, order.order_type, order.price, order.volume, mid_price)?;
+        }
         let double_side_limit_order_book =
             self.double_limit_order_books
                 .entry(ticker)
                 .or_insert(
-                    DoubleSideLimitOrderBook::new(ticker),
+                    DoubleSideLimitOrderBook::new_with_matching_mode(ticker, self.matching_mode),
                 );
-        double_side_limit_order_book.add_order(order);
+        let fills = double_side_limit_order_book.add_order(order)?;
+        if double_side_limit_order_book.contains_order(order_id) {
+            self.order_tickers.insert(order_id, ticker);
+        }
+        for fill in &fills {
+            if !fill.maker_remaining_volume.is_positive() {
+                self.order_tickers.remove(&fill.maker_order_id);
+            }
+        }
+        Ok(fills)
     }
 
-    pub fn total_volume_by_price_level(&mut self, ticker: &'s str, order_side: &OrderSide)
-        -> BTreeMap<NotNan<f64>, NotNan<f64>>
-    {
-        let double_side_limit_order_book = 
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Result<Order, OrderBookError> {
+        let order =
+            self.double_side_limit_order_book_for_order_mut(order_id)?
+                .cancel_order(order_id)?;
+        self.order_tickers.remove(&order_id);
+        Ok(order)
+    }
+
+    pub fn reduce_order(&mut self, order_id: OrderId, volume: Quantity) -> Result<Quantity, OrderBookError> {
+        let remaining_volume =
+            self.double_side_limit_order_book_for_order_mut(order_id)?
+                .reduce_order(order_id, volume)?;
+        if !remaining_volume.is_positive() {
+            self.order_tickers.remove(&order_id);
+        }
+        Ok(remaining_volume)
+    }
+
+    /// Change the price and volume of an order, which must follow the trading
+    /// rules of its ticker if it has any. An order which stays at the same
+    /// price is only checked against the lot size and minimum notional, so a
+    /// resting order can be reduced even after the mid price has moved away
+    /// from it. Returns the fills.
+    pub fn replace_order(&mut self, order_id: OrderId, price: Price, volume: Quantity) -> Result<Vec<Fill>, OrderBookError> {
+        let trading_rules = self.order_tickers
+            .get(&order_id)
+            .and_then(|ticker| self.trading_rules.get(ticker).zip(self.double_limit_order_books.get(ticker)));
+        if let Some((trading_rules, double_side_limit_order_book)) = trading_rules {
+            if double_side_limit_order_book.order(order_id)?.price == price {
+                trading_rules.check_volume_change(order_id, price, volume)?;
+            } else {
+                // Only limit and post only orders rest, and both are checked as limit orders
+                let mid_price = double_side_limit_order_book.mid_price()?;
+                trading_rules.check_order(order_id, OrderType::Limit, price, volume, mid_price)?;
+            }
+        }
+        let double_side_limit_order_book = self.double_side_limit_order_book_for_order_mut(order_id)?;
+        let fills = double_side_limit_order_book.replace_order(order_id, price, volume)?;
+        if !double_side_limit_order_book.contains_order(order_id) {
+            self.order_tickers.remove(&order_id);
+        }
+        for fill in &fills {
+            if !fill.maker_remaining_volume.is_positive() {
+                self.order_tickers.remove(&fill.maker_order_id);
+            }
+        }
+        Ok(fills)
+    }
+
+    /// Set the total volume quoted by `source_exchange` at one price level.
+    ///
+    /// This applies an L2 update from an exchange feed. Any existing orders from
+    /// `source_exchange` at the price level are replaced by a single order with
+    /// the new volume, and a volume of zero deletes them. Volume from other
+    /// exchanges at the same price level is left untouched. The new order is
+    /// never matched, whatever the matching mode of the book.
+    ///
+    /// Venue data is not checked against the trading rules of `ticker`, but it
+    /// is snapped to their grid if they set `snap_venue_data`. The volume of
+    /// every level which snaps to the same price is summed, so an update to one
+    /// of them leaves the others in place.
+    pub fn set_price_level_volume(
+        &mut self,
+        ticker: Instrument,
+        order_side: OrderSide,
+        price: Price,
+        source_exchange: ExchangeId,
+        volume: Quantity,
+    ) -> Result<(), OrderBookError> {
+        if volume.is_negative() {
+            return Err(
+                OrderBookError::NegativePriceLevelVolume {
+                    price,
+                    volume,
+                }
+            );
+        }
+        let trading_rules = self.trading_rules.get(&ticker);
+        let venue_price_levels = self.venue_price_levels.entry((ticker, source_exchange, order_side)).or_default();
+        let previous_volume = if volume.is_positive() {
+            venue_price_levels.insert(price, volume)
+        } else {
+            venue_price_levels.remove(&price)
+        };
+        let (price, volume) = match snapped_venue_price_level(trading_rules, order_side, venue_price_levels, price, volume) {
+            Ok(snapped_price_level) => {
+                snapped_price_level
+            },
+            Err(error) => {
+                // Leave the book unchanged
+                match previous_volume {
+                    Some(previous_volume) => venue_price_levels.insert(price, previous_volume),
+                    None => venue_price_levels.remove(&price),
+                };
+                return Err(error);
+            },
+        };
+
+        let cancelled_orders =
             self.double_limit_order_books
                 .entry(ticker)
                 .or_insert(
-                    DoubleSideLimitOrderBook::new(ticker)
-                );
-        double_side_limit_order_book.total_volume_by_price_level(order_side)
+                    DoubleSideLimitOrderBook::new_with_matching_mode(ticker, self.matching_mode),
+                )
+                .cancel_orders_by_source_exchange_at_price_level(order_side, price, source_exchange)?;
+        for order in &cancelled_orders {
+            self.order_tickers.remove(&order.order_id);
+        }
+
+        if volume.is_positive() {
+            let order_id = self.next_order_id();
+            let order = Order::new(order_id, ticker, order_side, price, volume, source_exchange);
+            self.add_resting_order(order)?;
+        }
+        Ok(())
     }
 
-    pub fn total_volume_by_price_level_and_source_exchange(&mut self, ticker: &'s str, order_side: &OrderSide)
-        -> BTreeMap<NotNan<f64>, BTreeMap<&str, NotNan<f64>>>
-    {
-        let double_side_limit_order_book = 
+    /// Replace all of the volume quoted by `source_exchange` for `ticker` with
+    /// a new snapshot of its book.
+    ///
+    /// `bids` and `asks` are lists of price levels and the total volume at each
+    /// price level. Levels with zero volume are ignored. The orders of other
+    /// exchanges are left untouched, and price levels which are left empty are
+    /// removed. If any level has a negative volume, or overflows when it is
+    /// snapped, the book is left unchanged.
+    /// As with `set_price_level_volume`, levels are snapped to the grid of the
+    /// trading rules of `ticker` if they set `snap_venue_data`.
+    pub fn replace_exchange_snapshot(
+        &mut self,
+        ticker: Instrument,
+        source_exchange: ExchangeId,
+        bids: &[(Price, Quantity)],
+        asks: &[(Price, Quantity)],
+    ) -> Result<(), OrderBookError> {
+        let negative_volume =
+            bids.iter()
+                .chain(asks.iter())
+                .find(|(_price, volume)| volume.is_negative());
+        if let Some((price, volume)) = negative_volume {
+            return Err(
+                OrderBookError::NegativePriceLevelVolume {
+                    price: *price,
+                    volume: *volume,
+                }
+            );
+        }
+
+        let trading_rules = self.trading_rules.get(&ticker);
+        let snapshot = [
+            (OrderSide::BUY, snapped_venue_snapshot(trading_rules, OrderSide::BUY, bids)?),
+            (OrderSide::SELL, snapped_venue_snapshot(trading_rules, OrderSide::SELL, asks)?),
+        ];
+
+        let cancelled_orders =
             self.double_limit_order_books
                 .entry(ticker)
                 .or_insert(
-                    DoubleSideLimitOrderBook::new(ticker)
-                );
-        double_side_limit_order_book.total_volume_by_price_level_and_source_exchange(order_side)
+                    DoubleSideLimitOrderBook::new_with_matching_mode(ticker, self.matching_mode),
+                )
+                .cancel_orders_by_source_exchange(source_exchange)?;
+        for order in &cancelled_orders {
+            self.order_tickers.remove(&order.order_id);
+        }
+
+        for (order_side, (venue_price_levels, snapped_price_levels)) in snapshot {
+            for (price, volume) in snapped_price_levels {
+                if volume.is_positive() {
+                    let order_id = self.next_order_id();
+                    let order = Order::new(order_id, ticker, order_side, price, volume, source_exchange);
+                    self.add_resting_order(order)?;
+                }
+            }
+            self.venue_price_levels.insert((ticker, source_exchange, order_side), venue_price_levels);
+        }
+        Ok(())
+    }
+
+    fn add_resting_order(&mut self, order: Order) -> Result<(), OrderBookError> {
+        let ticker = order.ticker;
+        let order_id = order.order_id;
+        if self.order_tickers.contains_key(&order_id) {
+            return Err(
+                OrderBookError::DuplicateOrderId {
+                    order_id,
+                }
+            );
+        }
+        self.double_limit_order_books
+            .entry(ticker)
+            .or_insert(
+                DoubleSideLimitOrderBook::new_with_matching_mode(ticker, self.matching_mode),
+            )
+            .add_resting_order(order)?;
+        self.order_tickers.insert(order_id, ticker);
+        Ok(())
     }
 
-    pub fn total_volume_by_source_exchange(&mut self, ticker: &'s str, order_side: &OrderSide)
-        -> BTreeMap<&str, NotNan<f64>>
+    fn double_side_limit_order_book_for_order_mut(&mut self, order_id: OrderId)
+        -> Result<&mut DoubleSideLimitOrderBook, OrderBookError>
     {
-        let double_side_limit_order_book =
-            self.double_limit_order_books.
-                entry(ticker)
-                .or_insert(
-                    DoubleSideLimitOrderBook::new(ticker)
-                );
-        double_side_limit_order_book.total_volume_by_source_exchange(order_side)
+        self.order_tickers
+            .get(&order_id)
+            .and_then(|ticker| self.double_limit_order_books.get_mut(ticker))
+            .ok_or(OrderBookError::UnknownOrderId { order_id })
+    }
+
+    /// The book for `ticker`.
+    ///
+    /// Queries never create a book, so a ticker which has never been added to
+    /// the book is an error rather than an empty book.
+    pub fn double_side_limit_order_book(&self, ticker: Instrument)
+        -> Result<&DoubleSideLimitOrderBook, OrderBookError>
+    {
+        self.double_limit_order_books
+            .get(&ticker)
+            .ok_or(OrderBookError::UnknownTicker { ticker })
+    }
+
+    pub fn contains_ticker(&self, ticker: Instrument) -> bool {
+        self.double_limit_order_books.contains_key(&ticker)
+    }
+
+    pub fn tickers(&self) -> Vec<Instrument> {
+        self.double_limit_order_books.keys().copied().collect()
+    }
+
+    pub fn total_volume_by_price_level(&self, ticker: Instrument, order_side: &OrderSide)
+        -> Result<BTreeMap<Price, Quantity>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.total_volume_by_price_level(order_side))
+    }
+
+    pub fn total_volume_by_price_level_and_source_exchange(&self, ticker: Instrument, order_side: &OrderSide)
+        -> Result<BTreeMap<Price, BTreeMap<ExchangeId, Quantity>>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.total_volume_by_price_level_and_source_exchange(order_side))
+    }
+
+    pub fn total_volume_by_source_exchange(&self, ticker: Instrument, order_side: &OrderSide)
+        -> Result<BTreeMap<ExchangeId, Quantity>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.total_volume_by_source_exchange(order_side))
+    }
+
+    pub fn cumulative_volume_to_price(&self, ticker: Instrument, order_side: &OrderSide, limit_price: Price)
+        -> Result<Quantity, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.cumulative_volume_to_price(order_side, limit_price))
+    }
+
+    pub fn cumulative_volume_to_price_by_source_exchange(&self, ticker: Instrument, order_side: &OrderSide, limit_price: Price)
+        -> Result<BTreeMap<ExchangeId, Quantity>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.cumulative_volume_to_price_by_source_exchange(order_side, limit_price))
+    }
+
+    pub fn price_at_cumulative_volume(&self, ticker: Instrument, order_side: &OrderSide, cumulative_volume: Quantity)
+        -> Result<Option<Price>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.price_at_cumulative_volume(order_side, cumulative_volume))
+    }
+
+    pub fn price_at_cumulative_volume_by_source_exchange(&self, ticker: Instrument, order_side: &OrderSide, cumulative_volume: Quantity)
+        -> Result<BTreeMap<ExchangeId, Price>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.price_at_cumulative_volume_by_source_exchange(order_side, cumulative_volume))
+    }
+
+    pub fn depth_within_basis_points(&self, ticker: Instrument, order_side: &OrderSide, basis_points: u32)
+        -> Result<Option<Quantity>, OrderBookError>
+    {
+        self.double_side_limit_order_book(ticker)?.depth_within_basis_points(order_side, basis_points)
+    }
+
+    pub fn depth_within_basis_points_by_source_exchange(&self, ticker: Instrument, order_side: &OrderSide, basis_points: u32)
+        -> Result<BTreeMap<ExchangeId, Quantity>, OrderBookError>
+    {
+        self.double_side_limit_order_book(ticker)?.depth_within_basis_points_by_source_exchange(order_side, basis_points)
+    }
+
+    pub fn mid_price(&self, ticker: Instrument)
+        -> Result<Option<Price>, OrderBookError>
+    {
+        self.double_side_limit_order_book(ticker)?.mid_price()
     }
 
     pub fn clear(&mut self) {
         self.double_limit_order_books.clear();
+        self.order_tickers.clear();
+        self.venue_price_levels.clear();
     }
 
     pub fn debug_print(&self) {
@@ -76,41 +417,117 @@ impl<'s> MultiTickerLimitOrderBook<'s> {
         println!("{lob:?}");
     }
 
-    pub fn highest_bid_price_by_exchange(&mut self, ticker: &'s str)
-        -> BTreeMap<&str, NotNan<f64>>
+    pub fn highest_bid_price_by_exchange(&self, ticker: Instrument)
+        -> Result<BTreeMap<ExchangeId, Price>, OrderBookError>
     {
-        self.double_limit_order_books
-            .entry(ticker)
-            .or_insert(DoubleSideLimitOrderBook::new(ticker))
-            .highest_bid_price_by_exchange()
+        Ok(self.double_side_limit_order_book(ticker)?.highest_bid_price_by_exchange())
     }
 
-    pub fn lowest_ask_price_by_exchange(&mut self, ticker: &'s str)
-        -> BTreeMap<&str, NotNan<f64>>
+    pub fn lowest_ask_price_by_exchange(&self, ticker: Instrument)
+        -> Result<BTreeMap<ExchangeId, Price>, OrderBookError>
     {
-        self.double_limit_order_books
-            .entry(ticker)
-            .or_insert(DoubleSideLimitOrderBook::new(ticker))
-            .lowest_ask_price_by_exchange()
+        Ok(self.double_side_limit_order_book(ticker)?.lowest_ask_price_by_exchange())
     }
 
-    pub fn spread(&mut self, ticker: &'s str) -> Option<NotNan<f64>> {
-        let double_side_limit_order_book =
-            self.double_limit_order_books
-                .entry(ticker)
-                .or_insert(
-                    DoubleSideLimitOrderBook::new(ticker)
-                );
-        double_side_limit_order_book.spread()
+    pub fn best_bid_price(&self, ticker: Instrument)
+        -> Result<Option<Price>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.best_bid_price())
     }
 
-    pub fn spread_by_exchange(&mut self, ticker: &'s str) -> BTreeMap<&str, Option<NotNan<f64>>> {
-        let double_side_limit_order_book =
-            self.double_limit_order_books
-                .entry(ticker)
-                .or_insert(
-                    DoubleSideLimitOrderBook::new(ticker)
-                );
-        double_side_limit_order_book.spread_by_exchange()
+    pub fn best_ask_price(&self, ticker: Instrument)
+        -> Result<Option<Price>, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.best_ask_price())
+    }
+
+    pub fn quote(&self, ticker: Instrument)
+        -> Result<Quote, OrderBookError>
+    {
+        Ok(self.double_side_limit_order_book(ticker)?.quote())
+    }
+
+    pub fn spread(&self, ticker: Instrument)
+        -> Result<Option<Price>, OrderBookError>
+    {
+        self.double_side_limit_order_book(ticker)?.spread()
+    }
+
+    pub fn spread_by_exchange(&self, ticker: Instrument)
+        -> Result<BTreeMap<ExchangeId, Option<Price>>, OrderBookError>
+    {
+        self.double_side_limit_order_book(ticker)?.spread_by_exchange()
     }
-}
\ No newline at end of file
+}
+
+
+fn normalize_venue_level(trading_rules: Option<&TradingRules>, order_side: OrderSide, price: Price, volume: Quantity)
+    -> Result<(Price, Quantity), OrderBookError>
+{
+    match trading_rules {
+        Some(trading_rules) => {
+            trading_rules.normalize_venue_level(order_side, price, volume)
+        },
+        None => {
+            Ok((price, volume))
+        },
+    }
+}
+
+fn checked_add_volume(price: Price, volume: Quantity, other_volume: Quantity) -> Result<Quantity, OrderBookError> {
+    volume
+        .checked_add(other_volume)
+        .ok_or(OrderBookError::PriceLevelOverflow { price, volume })
+}
+
+/// The venue levels of one side of a snapshot, and the snapped price levels
+/// which are added to the book for them.
+fn snapped_venue_snapshot(trading_rules: Option<&TradingRules>, order_side: OrderSide, price_levels: &[(Price, Quantity)])
+    -> Result<(VenuePriceLevels, VenuePriceLevels), OrderBookError>
+{
+    let mut venue_price_levels = BTreeMap::new();
+    for (price, volume) in price_levels.iter().filter(|(_price, volume)| volume.is_positive()) {
+        let venue_volume = venue_price_levels.entry(*price).or_insert(Quantity::ZERO);
+        *venue_volume = checked_add_volume(*price, *venue_volume, *volume)?;
+    }
+
+    let mut snapped_volumes = BTreeMap::new();
+    for (price, volume) in &venue_price_levels {
+        let (snapped_price, _volume) = normalize_venue_level(trading_rules, order_side, *price, *volume)?;
+        let snapped_volume = snapped_volumes.entry(snapped_price).or_insert(Quantity::ZERO);
+        *snapped_volume = checked_add_volume(snapped_price, *snapped_volume, *volume)?;
+    }
+    let snapped_price_levels =
+        snapped_volumes
+            .into_iter()
+            .map(|(price, volume)| normalize_venue_level(trading_rules, order_side, price, volume))
+            .collect::<Result<VenuePriceLevels, _>>()?;
+    Ok((venue_price_levels, snapped_price_levels))
+}
+
+/// The snapped price of the venue level at `price`, and the snapped total
+/// volume of every venue level which snaps to that price.
+///
+/// Bids snap down and asks snap up, so the levels which snap to a price are
+/// the ones next to it, at or above it for bids and at or below it for asks.
+fn snapped_venue_price_level(
+    trading_rules: Option<&TradingRules>,
+    order_side: OrderSide,
+    venue_price_levels: &VenuePriceLevels,
+    price: Price,
+    volume: Quantity,
+) -> Result<(Price, Quantity), OrderBookError> {
+    let (snapped_price, _volume) = normalize_venue_level(trading_rules, order_side, price, volume)?;
+    let price_levels: Box<dyn Iterator<Item = (&Price, &Quantity)>> = match order_side {
+        OrderSide::BUY => Box::new(venue_price_levels.range(snapped_price..)),
+        OrderSide::SELL => Box::new(venue_price_levels.range(..=snapped_price).rev()),
+    };
+    let mut snapped_volume = Quantity::ZERO;
+    for (price, volume) in price_levels {
+        if normalize_venue_level(trading_rules, order_side, *price, *volume)?.0 != snapped_price {
+            break;
+        }
+        snapped_volume = checked_add_volume(snapped_price, snapped_volume, *volume)?;
+    }
+    normalize_venue_level(trading_rules, order_side, snapped_price, snapped_volume)
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+
+use super::Notional;
+use super::OrderId;
+use super::OrderSide;
+use super::Price;
+use super::Quantity;
+use super::Instrument;
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum OrderBookError {
+    /// The order was added to a book for the other side.
+    WrongSide {
+        order_id: OrderId,
+        expected: OrderSide,
+        actual: OrderSide,
+    },
+    /// The order was added to a book for a different ticker.
+    WrongTicker {
+        order_id: OrderId,
+        expected: Instrument,
+        actual: Instrument,
+    },
+    /// The order was added to a price level with a different price.
+    WrongPrice {
+        order_id: OrderId,
+        expected: Price,
+        actual: Price,
+    },
+    /// The volume of an order, or the volume of a change to an order, was
+    /// negative or zero.
+    NonPositiveVolume {
+        order_id: OrderId,
+        volume: Quantity,
+    },
+    /// The volume of an L2 update for a price level was negative. A volume of
+    /// zero deletes the price level.
+    NegativePriceLevelVolume {
+        price: Price,
+        volume: Quantity,
+    },
+    /// Snapping an L2 update to the grid of the trading rules, summing it with
+    /// the other levels which snap to the same price, or adding a volume to the
+    /// running totals of a price level, overflowed.
+    PriceLevelOverflow {
+        price: Price,
+        volume: Quantity,
+    },
+    /// An L2 update has more significant decimal places than the price or
+    /// volume scale of the trading rules, or overflowed when converted to it.
+    PriceLevelScale {
+        price: Price,
+        volume: Quantity,
+    },
+    /// No order with this id is in the book.
+    UnknownOrderId {
+        order_id: OrderId,
+    },
+    /// An order with this id is already in the book.
+    DuplicateOrderId {
+        order_id: OrderId,
+    },
+    /// Adding the order would have crossed the book, for example a post only
+    /// order priced through the best price on the opposite side.
+    CrossedBook {
+        order_id: OrderId,
+    },
+    /// A fill or kill order which could not be completely filled, so it was
+    /// cancelled without changing the book.
+    OrderKilled {
+        order_id: OrderId,
+    },
+    /// No order for this instrument has ever been added to the book.
+    UnknownTicker {
+        ticker: Instrument,
+    },
+    /// Calculating the mid price or the spread of the book of this instrument
+    /// overflowed.
+    PriceOverflow {
+        ticker: Instrument,
+    },
+    /// Converting a notional of this instrument to a volume overflowed, for
+    /// example when planning the fills of a budget.
+    NotionalOverflow {
+        ticker: Instrument,
+    },
+    /// The price of a limit order was negative or zero.
+    NonPositivePrice {
+        order_id: OrderId,
+        price: Price,
+    },
+    /// The price of the order is not a multiple of the tick size of its ticker.
+    PriceNotOnTick {
+        order_id: OrderId,
+        price: Price,
+        tick_size: Price,
+    },
+    /// The volume of the order is not a multiple of the lot size of its ticker.
+    VolumeNotOnLot {
+        order_id: OrderId,
+        volume: Quantity,
+        lot_size: Quantity,
+    },
+    /// The price times the volume of the order is below the minimum notional
+    /// of its ticker.
+    BelowMinimumNotional {
+        order_id: OrderId,
+        notional: Notional,
+        minimum_notional: Notional,
+    },
+    /// The price of the order is too far from the mid price of the book.
+    PriceOutsideBand {
+        order_id: OrderId,
+        price: Price,
+        lower_bound: Price,
+        upper_bound: Price,
+    },
+    /// Checking the order against the trading rules of its ticker overflowed.
+    OrderOverflow {
+        order_id: OrderId,
+    },
+}
+
+impl std::error::Error for OrderBookError {
+
+}
+
+impl fmt::Display for OrderBookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderBookError::WrongSide { order_id, expected, actual } => {
+                write!(f, "order {order_id} has side {actual:?} but was added to the {expected:?} side of the book")
+            },
+            OrderBookError::WrongTicker { order_id, expected, actual } => {
+                write!(f, "order {order_id} has ticker {actual} but was added to the book for {expected}")
+            },
+            OrderBookError::WrongPrice { order_id, expected, actual } => {
+                write!(f, "order {order_id} has price {actual} but was added to the price level {expected}")
+            },
+            OrderBookError::NonPositiveVolume { order_id, volume } => {
+                write!(f, "order {order_id} has volume {volume}, volume must be greater than zero")
+            },
+            OrderBookError::NegativePriceLevelVolume { price, volume } => {
+                write!(f, "price level {price} was given volume {volume}, volume must not be negative")
+            },
+            OrderBookError::PriceLevelOverflow { price, volume } => {
+                write!(f, "price level {price} overflowed with volume {volume}")
+            },
+            OrderBookError::PriceLevelScale { price, volume } => {
+                write!(f, "price level {price} with volume {volume} has more decimal places than the price and volume scales")
+            },
+            OrderBookError::UnknownOrderId { order_id } => {
+                write!(f, "order {order_id} is not in the book")
+            },
+            OrderBookError::DuplicateOrderId { order_id } => {
+                write!(f, "order {order_id} is already in the book")
+            },
+            OrderBookError::CrossedBook { order_id } => {
+                write!(f, "order {order_id} would cross the book")
+            },
+            OrderBookError::OrderKilled { order_id } => {
+                write!(f, "fill or kill order {order_id} could not be completely filled")
+            },
+            OrderBookError::UnknownTicker { ticker } => {
+                write!(f, "instrument {ticker} is not in the book")
+            },
+            OrderBookError::PriceOverflow { ticker } => {
+                write!(f, "the mid price or spread of instrument {ticker} overflowed")
+            },
+            OrderBookError::NotionalOverflow { ticker } => {
+                write!(f, "a notional of instrument {ticker} overflowed when converted to a volume")
+            },
+            OrderBookError::NonPositivePrice { order_id, price } => {
+                write!(f, "order {order_id} has price {price}, price must be greater than zero")
+            },
+            OrderBookError::PriceNotOnTick { order_id, price, tick_size } => {
+                write!(f, "order {order_id} has price {price}, which is not a multiple of the tick size {tick_size}")
+            },
+            OrderBookError::VolumeNotOnLot { order_id, volume, lot_size } => {
+                write!(f, "order {order_id} has volume {volume}, which is not a multiple of the lot size {lot_size}")
+            },
+            OrderBookError::BelowMinimumNotional { order_id, notional, minimum_notional } => {
+                write!(f, "order {order_id} has notional {notional}, which is below the minimum notional {minimum_notional}")
+            },
+            OrderBookError::PriceOutsideBand { order_id, price, lower_bound, upper_bound } => {
+                write!(f, "order {order_id} has price {price}, which is outside the price band {lower_bound} to {upper_bound}")
+            },
+            OrderBookError::OrderOverflow { order_id } => {
+                write!(f, "order {order_id} overflowed when checked against the trading rules")
+            },
+        }
+    }
+}
@@ -2,99 +2,295 @@
 use std::collections::VecDeque;
 use std::collections::BTreeMap;
 
-use ordered_float::NotNan;
-
 use super::Order;
+use super::Fill;
+use super::OrderId;
+use super::Price;
+use super::Quantity;
+use super::ExchangeId;
+use super::OrderBookError;
+use super::check_volume;
 
 
 #[derive(Debug)]
-pub struct PriceLevel<'s> {
+pub struct PriceLevel {
     /// Example use of PriceLevel
     ///
-    /// let source_exchange_coinbase = "COINBASE";
-    /// let source_exchange_gemini = "GEMINI";
-    /// let ticker = "example ticker";
+    /// let source_exchange_coinbase = limit_order_book_lib::ExchangeId::intern("COINBASE");
+    /// let source_exchange_gemini = limit_order_book_lib::ExchangeId::intern("GEMINI");
+    /// let ticker = limit_order_book_lib::Instrument::intern("BTC", "USD");
     /// 
     /// let order1 = limit_order_book_lib::Order::new(
+    ///     limit_order_book_lib::OrderId(1),
     ///     ticker,
     ///     limit_order_book_lib::OrderSide::BUY,
-    ///     100.0,
-    ///     20.0,
+    ///     "100.0".parse().unwrap(),
+    ///     "20.0".parse().unwrap(),
     ///     source_exchange_coinbase,
-    /// ).unwrap();
+    /// );
     ///
     /// let order2 = limit_order_book_lib::Order::new(
+    ///     limit_order_book_lib::OrderId(2),
     ///     ticker,
     ///     limit_order_book_lib::OrderSide::BUY,
-    ///     100.0,
-    ///     10.0,
+    ///     "100.0".parse().unwrap(),
+    ///     "10.0".parse().unwrap(),
     ///     source_exchange_coinbase,
-    /// ).unwrap();
+    /// );
     ///
     /// let order3 = limit_order_book_lib::Order::new(
+    ///     limit_order_book_lib::OrderId(3),
     ///     ticker,
     ///     limit_order_book_lib::OrderSide::BUY,
-    ///     100.0,
-    ///     5.0,
+    ///     "100.0".parse().unwrap(),
+    ///     "5.0".parse().unwrap(),
     ///     source_exchange_gemini,
-    /// ).unwrap();
+    /// );
     ///
     /// let orders = VecDeque::from(
     ///     vec![order1, order2, order3],
     /// );
     ///
-    /// use ordered_float::NotNan;
-    /// let mut price_level = limit_order_book_lib::PriceLevel::new(NotNan::new(100.0).unwrap());
+    /// let mut price_level = limit_order_book_lib::PriceLevel::new("100.0".parse().unwrap());
     /// for order in orders {
-    ///     price_level.add_order(order);
+    ///     price_level.add_order(order).unwrap();
     /// }
     ///
     /// println!("{price_level:?}");
 
-    price: NotNan<f64>,
-    orders: VecDeque<Order<'s>>,
+    price: Price,
+    orders: VecDeque<Order>,
+    // Running totals of `orders`, kept up to date by every method which
+    // changes the queue. Exchanges with no volume have no entry.
+    total_volume: Quantity,
+    total_volume_by_source_exchange: BTreeMap<ExchangeId, Quantity>,
 }
 
-impl <'s> PriceLevel<'s> {
-    pub fn new(price: NotNan<f64>) -> PriceLevel<'s> {
+impl PriceLevel {
+    pub fn new(price: Price) -> PriceLevel {
         PriceLevel {
             price,
             orders: VecDeque::new(),
+            total_volume: Quantity::ZERO,
+            total_volume_by_source_exchange: BTreeMap::new(),
         }
     }
 
-    pub fn add_order(&mut self, order: Order<'s>) {
-        assert!(order.price == self.price);
+    pub fn add_order(&mut self, order: Order) -> Result<(), OrderBookError> {
+        if order.price != self.price {
+            return Err(
+                OrderBookError::WrongPrice {
+                    order_id: order.order_id,
+                    expected: self.price,
+                    actual: order.price,
+                }
+            );
+        }
+        order.check_volume()?;
+        self.add_volume(order.source_exchange, order.volume)?;
         self.orders.push_back(order);
+        Ok(())
     }
 
-    pub fn total_volume_by_source_exchange(&self)
-        -> BTreeMap<&str, NotNan<f64>>
-    {
-        let mut total_volume_by_source_exchange = BTreeMap::new();
-        for order in &self.orders {
-            let source_exchange = order.source_exchange;
-            let total_volume = total_volume_by_source_exchange.entry(source_exchange).or_insert(NotNan::default());
-            *total_volume += order.volume;
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Result<Order, OrderBookError> {
+        let index = self.position(order_id)?;
+        self.remove_volume(self.orders[index].source_exchange, self.orders[index].volume)?;
+        let order = self.orders.remove(index).expect("index is in range");
+        Ok(order)
+    }
+
+    /// Reduce the volume of an order, keeping its position in the queue.
+    ///
+    /// Returns the remaining volume. If the reduction is greater than or equal
+    /// to the volume of the order then the order is removed from the queue and
+    /// the remaining volume is zero.
+    pub fn reduce_order(&mut self, order_id: OrderId, volume: Quantity) -> Result<Quantity, OrderBookError> {
+        check_volume(order_id, volume)?;
+        let index = self.position(order_id)?;
+        let source_exchange = self.orders[index].source_exchange;
+        let order_volume = self.orders[index].volume;
+        if volume >= order_volume {
+            self.remove_volume(source_exchange, order_volume)?;
+            self.orders.remove(index);
+            Ok(Quantity::ZERO)
+        } else {
+            let remaining_volume = order_volume
+                .checked_sub(volume)
+                .ok_or(OrderBookError::PriceLevelOverflow { price: self.price, volume })?;
+            self.remove_volume(source_exchange, volume)?;
+            self.orders[index].volume = remaining_volume;
+            Ok(remaining_volume)
         }
-        total_volume_by_source_exchange
     }
 
-    pub fn total_volume_with_price_level(&self) -> (NotNan<f64>, NotNan<f64>) {
-        let mut total_volume = NotNan::default();
-        for order in &self.orders {
-            total_volume += order.volume;
+    /// Change the volume of an order at this price level.
+    ///
+    /// Decreasing the volume keeps the time priority of the order. Increasing
+    /// the volume moves the order to the back of the queue.
+    pub fn replace_order(&mut self, order_id: OrderId, volume: Quantity) -> Result<(), OrderBookError> {
+        check_volume(order_id, volume)?;
+        let index = self.position(order_id)?;
+        let source_exchange = self.orders[index].source_exchange;
+        self.change_volume(source_exchange, self.orders[index].volume, volume)?;
+        if volume <= self.orders[index].volume {
+            self.orders[index].volume = volume;
+        } else {
+            let mut order = self.orders.remove(index).expect("index is in range");
+            order.volume = volume;
+            self.orders.push_back(order);
         }
-        (self.price, total_volume)
+        Ok(())
+    }
+
+    /// Match an incoming order against the orders at this price level, front to back.
+    ///
+    /// The volume of the incoming order is reduced by the volume filled. Maker
+    /// orders which are completely filled are removed from the queue. Fills are
+    /// appended to `fills`, so if the volumes overflow, matching stops with an
+    /// error and the fills before it are kept.
+    pub fn match_order(&mut self, order: &mut Order, fills: &mut Vec<Fill>) -> Result<(), OrderBookError> {
+        while order.volume.is_positive() {
+            let (maker_source_exchange, maker_volume) = match self.orders.front() {
+                Some(maker_order) => (maker_order.source_exchange, maker_order.volume),
+                None => break,
+            };
+
+            let volume = std::cmp::min(maker_volume, order.volume);
+            let overflow = || OrderBookError::PriceLevelOverflow { price: self.price, volume };
+            let maker_remaining_volume = maker_volume.checked_sub(volume).ok_or_else(overflow)?;
+            let remaining_volume = order.volume.checked_sub(volume).ok_or_else(overflow)?;
+            self.remove_volume(maker_source_exchange, volume)?;
+
+            let maker_order = self.orders.front_mut().expect("maker order exists");
+            maker_order.volume = maker_remaining_volume;
+            order.volume = remaining_volume;
+            let maker_order_filled = !maker_order.volume.is_positive();
+
+            if volume.is_positive() {
+                fills.push(
+                    Fill {
+                        maker_order_id: maker_order.order_id,
+                        taker_order_id: order.order_id,
+                        maker_source_exchange: maker_order.source_exchange,
+                        price: self.price,
+                        volume,
+                        maker_remaining_volume: maker_order.volume,
+                    }
+                );
+            }
+
+            if maker_order_filled {
+                self.orders.pop_front();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove every order from `source_exchange` at this price level, keeping
+    /// the queue position of the orders from other exchanges.
+    pub fn cancel_orders_by_source_exchange(&mut self, source_exchange: ExchangeId) -> Result<Vec<Order>, OrderBookError> {
+        if let Some(cancelled_volume) = self.total_volume_by_source_exchange.get(&source_exchange) {
+            self.remove_volume(source_exchange, *cancelled_volume)?;
+        }
+        let (cancelled_orders, orders): (VecDeque<Order>, VecDeque<Order>) =
+            std::mem::take(&mut self.orders)
+                .into_iter()
+                .partition(|order| order.source_exchange == source_exchange);
+        self.orders = orders;
+        Ok(cancelled_orders.into())
+    }
+
+    fn add_volume(&mut self, source_exchange: ExchangeId, volume: Quantity) -> Result<(), OrderBookError> {
+        self.change_volume(source_exchange, Quantity::ZERO, volume)
+    }
+
+    fn remove_volume(&mut self, source_exchange: ExchangeId, volume: Quantity) -> Result<(), OrderBookError> {
+        self.change_volume(source_exchange, volume, Quantity::ZERO)
+    }
+
+    /// Update the running totals for an order of `source_exchange` whose volume
+    /// changes from `removed_volume` to `added_volume`. The totals are left
+    /// unchanged if they overflow.
+    fn change_volume(&mut self, source_exchange: ExchangeId, removed_volume: Quantity, added_volume: Quantity)
+        -> Result<(), OrderBookError>
+    {
+        let price = self.price;
+        let overflow = || OrderBookError::PriceLevelOverflow { price, volume: added_volume };
+        let change_total = |total_volume: Quantity| {
+            total_volume
+                .checked_sub(removed_volume)
+                .and_then(|total_volume| total_volume.checked_add(added_volume))
+                .ok_or_else(overflow)
+        };
+        let total_volume = change_total(self.total_volume)?;
+        let source_exchange_total_volume = change_total(
+            self.total_volume_by_source_exchange.get(&source_exchange).copied().unwrap_or(Quantity::ZERO)
+        )?;
+        self.total_volume = total_volume;
+        if source_exchange_total_volume.is_positive() {
+            self.total_volume_by_source_exchange.insert(source_exchange, source_exchange_total_volume);
+        } else {
+            self.total_volume_by_source_exchange.remove(&source_exchange);
+        }
+        Ok(())
+    }
+
+    fn position(&self, order_id: OrderId) -> Result<usize, OrderBookError> {
+        self.orders
+            .iter()
+            .position(|order| order.order_id == order_id)
+            .ok_or(OrderBookError::UnknownOrderId { order_id })
+    }
+
+    pub fn order_ids(&self) -> Vec<OrderId> {
+        self.orders.iter().map(|order| order.order_id).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    pub fn order_count(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn order(&self, order_id: OrderId) -> Result<&Order, OrderBookError> {
+        let index = self.position(order_id)?;
+        Ok(&self.orders[index])
+    }
+
+    pub fn source_exchange(&self, order_id: OrderId) -> Result<ExchangeId, OrderBookError> {
+        Ok(self.order(order_id)?.source_exchange)
+    }
+
+    pub fn contains_source_exchange(&self, source_exchange: ExchangeId) -> bool {
+        self.total_volume_by_source_exchange.contains_key(&source_exchange)
+    }
+
+    pub fn total_volume(&self) -> Quantity {
+        self.total_volume
+    }
+
+    pub fn total_volume_by_source_exchange(&self)
+        -> BTreeMap<ExchangeId, Quantity>
+    {
+        self.total_volume_by_source_exchange.clone()
+    }
+
+    pub fn total_volume_with_price_level(&self) -> (Price, Quantity) {
+        (self.price, self.total_volume)
     }
 
     pub fn total_volume_by_source_exchange_with_price_level(&self)
-        -> (NotNan<f64>, BTreeMap<&str, NotNan<f64>>)
+        -> (Price, BTreeMap<ExchangeId, Quantity>)
     {
         (self.price, self.total_volume_by_source_exchange())
     }
 
     pub fn clear(&mut self) {
-        self.orders.clear()
+        self.orders.clear();
+        self.total_volume = Quantity::ZERO;
+        self.total_volume_by_source_exchange.clear();
     }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+
+use std::collections::BTreeMap;
+
+use super::ExchangeId;
+use super::Price;
+use super::Quantity;
+use super::Instrument;
+use super::OrderBookError;
+
+
+/// The best price of one side of the book, the total volume at that price,
+/// and the volume of each exchange which contributes to it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct QuoteLevel {
+    pub price: Price,
+    pub volume: Quantity,
+    pub volume_by_source_exchange: BTreeMap<ExchangeId, Quantity>,
+}
+
+impl QuoteLevel {
+    pub fn source_exchanges(&self) -> Vec<ExchangeId> {
+        self.volume_by_source_exchange.keys().copied().collect()
+    }
+}
+
+/// How the best bid of the consolidated book compares to the best ask.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MarketState {
+    /// The best bid is below the best ask.
+    Normal,
+    /// The best bid is equal to the best ask. This happens in a consolidated
+    /// book when two exchanges quote the same price on opposite sides.
+    Locked,
+    /// The best bid is above the best ask.
+    Crossed,
+    /// At least one side of the book is empty.
+    OneSided,
+}
+
+/// The consolidated best bid and offer of a ticker across every exchange.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Quote {
+    pub ticker: Instrument,
+    pub best_bid: Option<QuoteLevel>,
+    pub best_ask: Option<QuoteLevel>,
+    pub market_state: MarketState,
+}
+
+impl Quote {
+    pub fn new(ticker: Instrument, best_bid: Option<QuoteLevel>, best_ask: Option<QuoteLevel>) -> Quote {
+        let market_state = match (&best_bid, &best_ask) {
+            (Some(best_bid), Some(best_ask)) => {
+                match best_bid.price.cmp(&best_ask.price) {
+                    std::cmp::Ordering::Less => MarketState::Normal,
+                    std::cmp::Ordering::Equal => MarketState::Locked,
+                    std::cmp::Ordering::Greater => MarketState::Crossed,
+                }
+            },
+            _ => {
+                MarketState::OneSided
+            },
+        };
+        Quote {
+            ticker,
+            best_bid,
+            best_ask,
+            market_state,
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.market_state == MarketState::Locked
+    }
+
+    pub fn is_crossed(&self) -> bool {
+        self.market_state == MarketState::Crossed
+    }
+
+    /// The best ask minus the best bid. Zero when locked and negative when crossed.
+    pub fn spread(&self) -> Result<Option<Price>, OrderBookError> {
+        match (&self.best_bid, &self.best_ask) {
+            (Some(best_bid), Some(best_ask)) => {
+                best_ask.price
+                    .checked_sub(best_bid.price)
+                    .map(Some)
+                    .ok_or(OrderBookError::PriceOverflow { ticker: self.ticker })
+            },
+            _ => {
+                Ok(None)
+            },
+        }
+    }
+}
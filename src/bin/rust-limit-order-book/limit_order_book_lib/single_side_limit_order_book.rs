@@ -1,52 +1,371 @@
 
 use std::collections::BTreeMap;
-
-use ordered_float::NotNan;
+use std::collections::BTreeSet;
 
 use super::PriceLevel;
 use super::OrderSide;
 use super::Order;
+use super::Fill;
+use super::OrderId;
+use super::Price;
+use super::Quantity;
+use super::ExchangeId;
+use super::OrderBookError;
+use super::check_volume;
 
 
 #[derive(Debug)]
-pub struct SingleSideLimitOrderBook<'s> {
+pub struct SingleSideLimitOrderBook {
     order_side: OrderSide,
-    price_levels: BTreeMap<NotNan<f64>, PriceLevel<'s>>,
+    price_levels: BTreeMap<Price, PriceLevel>,
+    order_prices: BTreeMap<OrderId, Price>,
+    // Top of book caches, kept up to date by every method which changes the
+    // price levels. `prices_by_source_exchange` holds the prices of the
+    // levels where each exchange has volume.
+    best_price: Option<Price>,
+    prices_by_source_exchange: BTreeMap<ExchangeId, BTreeSet<Price>>,
 }
 
-impl<'s> SingleSideLimitOrderBook<'s> {
-    pub fn new(order_side: OrderSide) -> SingleSideLimitOrderBook<'s> {
+impl SingleSideLimitOrderBook {
+    pub fn new(order_side: OrderSide) -> SingleSideLimitOrderBook {
         SingleSideLimitOrderBook {
             order_side,
             price_levels: BTreeMap::new(),
+            order_prices: BTreeMap::new(),
+            best_price: None,
+            prices_by_source_exchange: BTreeMap::new(),
         }
     }
 
-    pub fn add_order(&mut self, order: Order<'s>) {
-        assert!(order.order_side == self.order_side); // TODO: maybe some proper error type here
+    pub fn add_order(&mut self, order: Order) -> Result<(), OrderBookError> {
+        if order.order_side != self.order_side {
+            return Err(
+                OrderBookError::WrongSide {
+                    order_id: order.order_id,
+                    expected: self.order_side,
+                    actual: order.order_side,
+                }
+            );
+        }
+        if self.order_prices.contains_key(&order.order_id) {
+            return Err(
+                OrderBookError::DuplicateOrderId {
+                    order_id: order.order_id,
+                }
+            );
+        }
+        order.check_volume()?;
+        let order_id = order.order_id;
         let price = order.price;
+        let source_exchange = order.source_exchange;
         let price_level = self.price_levels.entry(price).or_insert(PriceLevel::new(price));
-        price_level.add_order(order);
+        price_level.add_order(order)?;
+        self.order_prices.insert(order_id, price);
+        self.update_price_level_caches(price, &[source_exchange]);
+        Ok(())
+    }
+
+    pub fn cancel_order(&mut self, order_id: OrderId) -> Result<Order, OrderBookError> {
+        let price = self.order_price(order_id)?;
+        let price_level = self.price_level_mut(order_id, price)?;
+        let order = price_level.cancel_order(order_id)?;
+        self.order_prices.remove(&order_id);
+        self.update_price_level_caches(price, &[order.source_exchange]);
+        Ok(order)
+    }
+
+    pub fn reduce_order(&mut self, order_id: OrderId, volume: Quantity) -> Result<Quantity, OrderBookError> {
+        let price = self.order_price(order_id)?;
+        let price_level = self.price_level_mut(order_id, price)?;
+        let source_exchange = price_level.source_exchange(order_id)?;
+        let remaining_volume = price_level.reduce_order(order_id, volume)?;
+        if !remaining_volume.is_positive() {
+            self.order_prices.remove(&order_id);
+            self.update_price_level_caches(price, &[source_exchange]);
+        }
+        Ok(remaining_volume)
+    }
+
+    /// Change the price and volume of an order.
+    ///
+    /// An order which stays at the same price and does not increase in volume
+    /// keeps its time priority. Otherwise the order goes to the back of the
+    /// queue at its new price level. The order is not matched against the
+    /// opposite side, which `DoubleSideLimitOrderBook::replace_order` does.
+    pub fn replace_order(&mut self, order_id: OrderId, price: Price, volume: Quantity) -> Result<(), OrderBookError> {
+        check_volume(order_id, volume)?;
+        let existing_price = self.order_price(order_id)?;
+
+        if price == existing_price {
+            let price_level = self.price_level_mut(order_id, price)?;
+            return price_level.replace_order(order_id, volume);
+        }
+
+        let mut order = self.cancel_order(order_id)?;
+        order.price = price;
+        order.volume = volume;
+        self.add_order(order)
+    }
+
+    /// Remove every order from `source_exchange` at one price level. Returns the
+    /// removed orders.
+    pub fn cancel_orders_by_source_exchange_at_price_level(&mut self, price: Price, source_exchange: ExchangeId)
+        -> Result<Vec<Order>, OrderBookError>
+    {
+        let cancelled_orders = match self.price_levels.get_mut(&price) {
+            Some(price_level) => price_level.cancel_orders_by_source_exchange(source_exchange)?,
+            None => Vec::new(),
+        };
+        for order in &cancelled_orders {
+            self.order_prices.remove(&order.order_id);
+        }
+        self.update_price_level_caches(price, &[source_exchange]);
+        Ok(cancelled_orders)
+    }
+
+    /// Remove every order from `source_exchange` at every price level. Returns
+    /// the removed orders.
+    pub fn cancel_orders_by_source_exchange(&mut self, source_exchange: ExchangeId) -> Result<Vec<Order>, OrderBookError> {
+        let prices = self.prices_by_source_exchange.get(&source_exchange).cloned().unwrap_or_default();
+        let mut cancelled_orders = Vec::new();
+        for price in prices {
+            cancelled_orders.extend(self.cancel_orders_by_source_exchange_at_price_level(price, source_exchange)?);
+        }
+        Ok(cancelled_orders)
+    }
+
+    /// Match an incoming order from the opposite side against this side of the book.
+    ///
+    /// Price levels are visited best price first and orders within each price
+    /// level are visited in time priority. Matching stops when the incoming order
+    /// is filled or when the best price no longer crosses the price of the
+    /// incoming order. The volume of the incoming order is reduced by the volume
+    /// filled.
+    pub fn match_order(&mut self, order: &mut Order) -> Result<Vec<Fill>, OrderBookError> {
+        if order.order_side == self.order_side {
+            return Err(
+                OrderBookError::WrongSide {
+                    order_id: order.order_id,
+                    expected: self.opposite_side(),
+                    actual: order.order_side,
+                }
+            );
+        }
+
+        let mut fills = Vec::new();
+
+        while order.volume.is_positive() {
+            let best_price = match self.best_price() {
+                Some(best_price) if self.crosses_price(order, best_price) => best_price,
+                _ => break,
+            };
+
+            let price_level = self.price_levels.get_mut(&best_price).expect("best price level exists");
+            let mut price_level_fills = Vec::new();
+            let matched = price_level.match_order(order, &mut price_level_fills);
+            let mut maker_source_exchanges = Vec::new();
+            for fill in &price_level_fills {
+                if !fill.maker_remaining_volume.is_positive() {
+                    self.order_prices.remove(&fill.maker_order_id);
+                }
+                maker_source_exchanges.push(fill.maker_source_exchange);
+            }
+            fills.extend(price_level_fills);
+            self.update_price_level_caches(best_price, &maker_source_exchanges);
+            matched?;
+        }
+
+        Ok(fills)
+    }
+
+    /// True if an incoming order from the opposite side would match against
+    /// at least one price level on this side.
+    pub fn crosses(&self, order: &Order) -> bool {
+        match self.best_price() {
+            Some(best_price) => self.crosses_price(order, best_price),
+            None => false,
+        }
+    }
+
+    /// The total volume on this side which an incoming order from the opposite
+    /// side could match against, ignoring the volume of the incoming order.
+    pub fn crossing_volume(&self, order: &Order) -> Quantity {
+        let mut crossing_volume = Quantity::ZERO;
+        for (price, price_level) in &self.price_levels {
+            if self.crosses_price(order, *price) {
+                crossing_volume += price_level.total_volume_with_price_level().1;
+            }
+        }
+        crossing_volume
+    }
+
+    fn best_price(&self) -> Option<Price> {
+        self.best_price
+    }
+
+    fn opposite_side(&self) -> OrderSide {
+        match self.order_side {
+            OrderSide::BUY => OrderSide::SELL,
+            OrderSide::SELL => OrderSide::BUY,
+        }
+    }
+
+    fn crosses_price(&self, order: &Order, price: Price) -> bool {
+        match order.order_side {
+            OrderSide::BUY => order.price >= price,
+            OrderSide::SELL => order.price <= price,
+        }
+    }
+
+    /// The total volume of the price levels at `limit_price` or better. For
+    /// the SELL side, this is the volume which can be bought without paying
+    /// more than `limit_price`.
+    pub fn cumulative_volume_to_price(&self, limit_price: Price) -> Quantity {
+        self.price_levels_best_first()
+            .take_while(|(price, _)| self.is_at_least_as_good_as(**price, limit_price))
+            .map(|(_, price_level)| price_level.total_volume_with_price_level().1)
+            .sum()
+    }
+
+    pub fn cumulative_volume_to_price_by_source_exchange(&self, limit_price: Price)
+        -> BTreeMap<ExchangeId, Quantity>
+    {
+        let mut cumulative_volume_by_source_exchange = BTreeMap::new();
+        for (price, price_level) in self.price_levels_best_first() {
+            if !self.is_at_least_as_good_as(*price, limit_price) {
+                break;
+            }
+            for (source_exchange, volume) in price_level.total_volume_by_source_exchange() {
+                let cumulative_volume = cumulative_volume_by_source_exchange.entry(source_exchange).or_insert(Quantity::ZERO);
+                *cumulative_volume += volume;
+            }
+        }
+        cumulative_volume_by_source_exchange
+    }
+
+    /// The price of the price level at which the volume summed from the best
+    /// price first reaches `cumulative_volume`. For the SELL side, this is the
+    /// price paid for the last unit of a BUY of `cumulative_volume`.
+    ///
+    /// Returns `None` if this side has less volume than `cumulative_volume`.
+    pub fn price_at_cumulative_volume(&self, cumulative_volume: Quantity) -> Option<Price> {
+        let mut volume_so_far = Quantity::ZERO;
+        for (price, price_level) in self.price_levels_best_first() {
+            volume_so_far += price_level.total_volume_with_price_level().1;
+            if volume_so_far >= cumulative_volume {
+                return Some(*price);
+            }
+        }
+        None
+    }
+
+    /// The same as `price_at_cumulative_volume`, using the volume of each
+    /// exchange on its own. Exchanges with less volume than
+    /// `cumulative_volume` are not included.
+    pub fn price_at_cumulative_volume_by_source_exchange(&self, cumulative_volume: Quantity)
+        -> BTreeMap<ExchangeId, Price>
+    {
+        let mut volume_so_far_by_source_exchange = BTreeMap::new();
+        let mut price_by_source_exchange = BTreeMap::new();
+        for (price, price_level) in self.price_levels_best_first() {
+            for (source_exchange, volume) in price_level.total_volume_by_source_exchange() {
+                let volume_so_far = volume_so_far_by_source_exchange.entry(source_exchange).or_insert(Quantity::ZERO);
+                *volume_so_far += volume;
+                if *volume_so_far >= cumulative_volume {
+                    price_by_source_exchange.entry(source_exchange).or_insert(*price);
+                }
+            }
+        }
+        price_by_source_exchange
+    }
+
+    fn price_levels_best_first(&self) -> Box<dyn Iterator<Item = (&Price, &PriceLevel)> + '_> {
+        match self.order_side {
+            OrderSide::BUY => Box::new(self.price_levels.iter().rev()),
+            OrderSide::SELL => Box::new(self.price_levels.iter()),
+        }
+    }
+
+    fn is_at_least_as_good_as(&self, price: Price, limit_price: Price) -> bool {
+        match self.order_side {
+            OrderSide::BUY => price >= limit_price,
+            OrderSide::SELL => price <= limit_price,
+        }
+    }
+
+    pub fn contains_order(&self, order_id: OrderId) -> bool {
+        self.order_prices.contains_key(&order_id)
+    }
+
+    pub fn price_level(&self, price: Price) -> Option<&PriceLevel> {
+        self.price_levels.get(&price)
+    }
+
+    pub fn order(&self, order_id: OrderId) -> Result<&Order, OrderBookError> {
+        let price = self.order_price(order_id)?;
+        self.price_levels
+            .get(&price)
+            .ok_or(OrderBookError::UnknownOrderId { order_id })?
+            .order(order_id)
+    }
+
+    fn order_price(&self, order_id: OrderId) -> Result<Price, OrderBookError> {
+        self.order_prices
+            .get(&order_id)
+            .copied()
+            .ok_or(OrderBookError::UnknownOrderId { order_id })
+    }
+
+    fn price_level_mut(&mut self, order_id: OrderId, price: Price) -> Result<&mut PriceLevel, OrderBookError> {
+        self.price_levels
+            .get_mut(&price)
+            .ok_or(OrderBookError::UnknownOrderId { order_id })
+    }
+
+    /// Bring the caches up to date after the volume of `source_exchanges` at
+    /// `price` has changed, removing the price level if it is now empty.
+    fn update_price_level_caches(&mut self, price: Price, source_exchanges: &[ExchangeId]) {
+        for source_exchange in source_exchanges {
+            let has_volume = self.price_levels
+                .get(&price)
+                .is_some_and(|price_level| price_level.contains_source_exchange(*source_exchange));
+            if has_volume {
+                self.prices_by_source_exchange.entry(*source_exchange).or_default().insert(price);
+            } else if let Some(prices) = self.prices_by_source_exchange.get_mut(source_exchange) {
+                prices.remove(&price);
+                if prices.is_empty() {
+                    self.prices_by_source_exchange.remove(source_exchange);
+                }
+            }
+        }
+
+        if self.price_levels.get(&price).is_some_and(|price_level| price_level.is_empty()) {
+            self.price_levels.remove(&price);
+        }
+
+        self.best_price = match self.order_side {
+            OrderSide::BUY => self.highest_price(),
+            OrderSide::SELL => self.lowest_price(),
+        };
     }
 
-    pub fn total_volume_by_price_level(&self) -> BTreeMap<NotNan<f64>, NotNan<f64>> {
-        let total_volume_by_price_level = self.price_levels.values().map(
+    pub fn total_volume_by_price_level(&self) -> BTreeMap<Price, Quantity> {
+        self.price_levels.values().map(
             |price_level| {
                 price_level.total_volume_with_price_level()
             }
-        ).collect();
-        total_volume_by_price_level
+        ).collect()
     }
 
     pub fn total_volume_by_source_exchange(&self)
-        -> BTreeMap<&str, NotNan<f64>>
+        -> BTreeMap<ExchangeId, Quantity>
     {
         let mut total_volume_by_source_exchange = BTreeMap::new();
 
         for price_level in self.price_levels.values() {
             let price_level_total_volume_by_source_exchange = price_level.total_volume_by_source_exchange();
             for (source_exchange, total_volume) in price_level_total_volume_by_source_exchange {
-                let existing_total_volume = total_volume_by_source_exchange.entry(source_exchange).or_insert(NotNan::default());
+                let existing_total_volume = total_volume_by_source_exchange.entry(source_exchange).or_insert(Quantity::ZERO);
                 *existing_total_volume += total_volume;
             }
         }
@@ -55,125 +374,51 @@ impl<'s> SingleSideLimitOrderBook<'s> {
     }
 
     pub fn total_volume_by_price_level_and_source_exchange(&self)
-        -> BTreeMap<NotNan<f64>, BTreeMap<&str, NotNan<f64>>>
+        -> BTreeMap<Price, BTreeMap<ExchangeId, Quantity>>
     {
-        let total_volume_by_price_level_and_source_exchange = self.price_levels.values().map(
+        self.price_levels.values().map(
             |price_level| {
                 price_level.total_volume_by_source_exchange_with_price_level()
             }
-        ).collect();
-        total_volume_by_price_level_and_source_exchange
+        ).collect()
     }
 
     pub fn clear(&mut self) {
-        self.price_levels.clear()
+        self.price_levels.clear();
+        self.order_prices.clear();
+        self.best_price = None;
+        self.prices_by_source_exchange.clear();
     }
 
-    pub fn highest_price(&self) -> Option<NotNan<f64>> {
-        self.price_levels
-            .iter()
-            .filter(
-                |price_level| {
-                    price_level.1.total_volume_with_price_level().1.into_inner() > 0.0
-                }
-            )
-            .last()
-            .map(
-                |price_level| {
-                    price_level.1.total_volume_with_price_level().0
-                }
-            )
+    // Empty price levels are always removed, so the first and last keys are
+    // the lowest and highest prices with volume.
+    pub fn highest_price(&self) -> Option<Price> {
+        self.price_levels.keys().next_back().copied()
     }
 
-    pub fn lowest_price(&self) -> Option<NotNan<f64>> {
-        self.price_levels
+    pub fn lowest_price(&self) -> Option<Price> {
+        self.price_levels.keys().next().copied()
+    }
+
+    pub fn highest_price_by_exchange(&self) -> BTreeMap<ExchangeId, Price> {
+        self.prices_by_source_exchange
             .iter()
-            .filter(
-                |price_level| {
-                    price_level.1.total_volume_with_price_level().1.into_inner() > 0.0
-                }
-            )
-            .next()
-            .map(
-                |price_level| {
-                    price_level.1.total_volume_with_price_level().0
+            .filter_map(
+                |(source_exchange, prices)| {
+                    prices.last().map(|price| (*source_exchange, *price))
                 }
             )
+            .collect()
     }
 
-    pub fn highest_price_by_exchange(&self) -> BTreeMap<&str, NotNan<f64>> {
-        let mut highest_price_by_exchange = BTreeMap::new();
-
-        // NOTE: This function can report that some exchanges have zero volume
-        // because the highest price differs across exchanges
-        // self.price_levels
-        //     .iter()
-        //     .last()
-        //     .map(
-        //         |price_level| {
-        //             let total_volume_by_source_exchange = price_level.1.total_volume_by_source_exchange();
-        //             for (source_exchange, volume) in total_volume_by_source_exchange {
-        //                 let entry = highest_price_by_exchange.entry(source_exchange).or_insert(NotNan::default());
-        //                 *entry += volume;
-        //             }
-        //         }
-        //     );
-
-        for price_level in &self.price_levels {
-            let price_level = price_level.1;
-            let total_volume_by_source_exchange_with_price_level =
-                price_level.total_volume_by_source_exchange_with_price_level();
-            let price = total_volume_by_source_exchange_with_price_level.0;
-            let total_volume_by_source_exchange = total_volume_by_source_exchange_with_price_level.1;
-
-            for total_volume_and_source_exchange in total_volume_by_source_exchange {
-                let source_exchange = total_volume_and_source_exchange.0;
-                let existing_entry =
-                    highest_price_by_exchange.entry(source_exchange).or_insert(price);
-                if price > *existing_entry {
-                    *existing_entry = price;
+    pub fn lowest_price_by_exchange(&self) -> BTreeMap<ExchangeId, Price> {
+        self.prices_by_source_exchange
+            .iter()
+            .filter_map(
+                |(source_exchange, prices)| {
+                    prices.first().map(|price| (*source_exchange, *price))
                 }
-            }
-        }
-
-        highest_price_by_exchange
+            )
+            .collect()
     }
-
-    pub fn lowest_price_by_exchange(&self) -> BTreeMap<&str, NotNan<f64>> {
-        let mut lowest_price_by_exchange = BTreeMap::new();
-
-        for price_level in &self.price_levels {
-            let price_level = price_level.1;
-            let total_volume_by_source_exchange_with_price_level = 
-                price_level.total_volume_by_source_exchange_with_price_level();
-            let price = total_volume_by_source_exchange_with_price_level.0;
-            let total_volume_by_source_exchange = total_volume_by_source_exchange_with_price_level.1;
-
-            for total_volume_and_source_exchange in total_volume_by_source_exchange {
-                let source_exchange = total_volume_and_source_exchange.0;
-                let existing_entry = 
-                    lowest_price_by_exchange.entry(source_exchange).or_insert(price);
-                if price < *existing_entry {
-                    *existing_entry = price;
-                }
-            }
-        }
-
-        // NOTE: This function can report that some exchanges have zero volume
-        // because the lowest price differs across exchanges
-        // self.price_levels
-        //     .iter()
-        //     .next()
-        //     .map(
-        //         |price_level| {
-        //             let total_volume_by_source_exchange = price_level.1.total_volume_by_source_exchange();
-        //             for (source_exchange, volume) in total_volume_by_source_exchange {
-        //                 let entry = lowest_price_by_exchange.entry(source_exchange).or_insert(NotNan::default());
-        //                 *entry += volume;
-        //             }
-        //         }
-        //     );
-
-        lowest_price_by_exchange
-    }
-}
\ No newline at end of file
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::RwLock;
+
+
+/// Maps interned names to ids and back.
+///
+/// Names are leaked when they are first interned, so that they can be returned
+/// as `&'static str`. Each distinct name is only leaked once, and the number of
+/// distinct tickers and exchanges is small.
+struct Registry {
+    names: Vec<&'static str>,
+    ids: BTreeMap<&'static str, u32>,
+}
+
+impl Registry {
+    const fn new() -> Registry {
+        Registry {
+            names: Vec::new(),
+            ids: BTreeMap::new(),
+        }
+    }
+}
+
+fn intern(registry: &RwLock<Registry>, name: &str) -> u32 {
+    if let Some(id) = lookup(registry, name) {
+        return id;
+    }
+    let mut registry = registry.write().expect("registry lock poisoned");
+    if let Some(id) = registry.ids.get(name) {
+        return *id;
+    }
+    let id = u32::try_from(registry.names.len()).expect("too many interned names");
+    let name: &'static str = Box::leak(String::from(name).into_boxed_str());
+    registry.names.push(name);
+    registry.ids.insert(name, id);
+    id
+}
+
+fn lookup(registry: &RwLock<Registry>, name: &str) -> Option<u32> {
+    let registry = registry.read().expect("registry lock poisoned");
+    registry.ids.get(name).copied()
+}
+
+fn name(registry: &RwLock<Registry>, id: u32) -> &'static str {
+    let registry = registry.read().expect("registry lock poisoned");
+    registry.names[id as usize]
+}
+
+fn all(registry: &RwLock<Registry>) -> Vec<u32> {
+    let registry = registry.read().expect("registry lock poisoned");
+    (0..registry.names.len() as u32).collect()
+}
+
+static SYMBOL_REGISTRY: RwLock<Registry> = RwLock::new(Registry::new());
+static EXCHANGE_ID_REGISTRY: RwLock<Registry> = RwLock::new(Registry::new());
+
+
+/// An interned name, for example the asset `BTC`.
+///
+/// Cheap to copy and compare. Symbols are ordered by the order in which they
+/// were first interned.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Return the symbol for `name`, registering it if it has not been seen before.
+    pub fn intern(name: &str) -> Symbol {
+        Symbol(intern(&SYMBOL_REGISTRY, name))
+    }
+
+    /// Return the symbol for `name` only if it has already been registered.
+    pub fn lookup(name: &str) -> Option<Symbol> {
+        lookup(&SYMBOL_REGISTRY, name).map(Symbol)
+    }
+
+    /// Every symbol which has been registered, in registration order.
+    pub fn all() -> Vec<Symbol> {
+        all(&SYMBOL_REGISTRY).into_iter().map(Symbol).collect()
+    }
+
+    pub fn name(&self) -> &'static str {
+        name(&SYMBOL_REGISTRY, self.0)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.name())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+
+/// An interned exchange name, for example `COINBASE`.
+///
+/// Cheap to copy and compare. Exchange ids are ordered by the order in which
+/// they were first interned.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct ExchangeId(u32);
+
+impl ExchangeId {
+    /// Return the exchange id for `name`, registering it if it has not been seen before.
+    pub fn intern(name: &str) -> ExchangeId {
+        ExchangeId(intern(&EXCHANGE_ID_REGISTRY, name))
+    }
+
+    /// Return the exchange id for `name` only if it has already been registered.
+    pub fn lookup(name: &str) -> Option<ExchangeId> {
+        lookup(&EXCHANGE_ID_REGISTRY, name).map(ExchangeId)
+    }
+
+    /// Every exchange id which has been registered, in registration order.
+    pub fn all() -> Vec<ExchangeId> {
+        all(&EXCHANGE_ID_REGISTRY).into_iter().map(ExchangeId).collect()
+    }
+
+    pub fn name(&self) -> &'static str {
+        name(&EXCHANGE_ID_REGISTRY, self.0)
+    }
+}
+
+impl fmt::Debug for ExchangeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.name())
+    }
+}
+
+impl fmt::Display for ExchangeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
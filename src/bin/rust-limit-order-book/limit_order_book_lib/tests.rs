@@ -2,56 +2,59 @@
 use super::*;
 
 use std::collections::BTreeMap;
+use std::str::FromStr;
 
-
-const TICKER_1: &str = "EXAMPLE1";
-const TICKER_2: &str = "EXAMPLE2";
-
-const SOURCE_EXCHANGE_1: &str = "SRCEX1";
-const SOURCE_EXCHANGE_2: &str = "SRCEX2";
+use crate::test_support_lib::fixed_point;
+use crate::test_support_lib::source_exchange_1;
+use crate::test_support_lib::source_exchange_2;
+use crate::test_support_lib::ticker_1;
+use crate::test_support_lib::ticker_2;
 
 
 #[test]
 fn price_level_test() {
     
-    let price = 100.0;
-    let mut price_level = PriceLevel::new(NotNan::new(price).unwrap());
+    let price = fixed_point("100.0");
+    let mut price_level = PriceLevel::new(price);
 
-    let ticker = "EXAMPLE";
+    let ticker = Instrument::intern("EXAMPLE", "USD");
     let order_side = OrderSide::BUY;
 
     let order_1 = Order::new(
+        OrderId(1),
         ticker,
         order_side,
         price,
-        20.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("20.0"),
+        source_exchange_1(),
+    );
 
     let order_2 = Order::new(
+        OrderId(2),
         ticker,
         order_side,
         price,
-        25.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("25.0"),
+        source_exchange_1(),
+    );
 
     let order_3 = Order::new(
+        OrderId(3),
         ticker,
         order_side,
         price,
-        55.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("55.0"),
+        source_exchange_2(),
+    );
 
-    price_level.add_order(order_1);
-    price_level.add_order(order_2);
-    price_level.add_order(order_3);
+    price_level.add_order(order_1).unwrap();
+    price_level.add_order(order_2).unwrap();
+    price_level.add_order(order_3).unwrap();
 
     let total_volume = price_level.total_volume_with_price_level();
     let expected_total_volume = (
-        NotNan::new(price).unwrap(),
-        NotNan::new(20.0 + 25.0 + 55.0).unwrap(),
+        price,
+        fixed_point("100.0"),
     );
 
     assert_eq!(total_volume, expected_total_volume);
@@ -60,8 +63,8 @@ fn price_level_test() {
 
     let total_volume = price_level.total_volume_with_price_level();
     let expected_total_volume = (
-        NotNan::new(price).unwrap(),
-        NotNan::default(),
+        price,
+        FixedPoint::ZERO,
     );
 
     assert_eq!(total_volume, expected_total_volume)
@@ -72,43 +75,46 @@ fn price_level_test() {
 fn single_side_limit_order_book_test() {
 
     let order_side = OrderSide::BUY;
-    let ticker = "EXAMPLE1";
+    let ticker = ticker_1();
 
     let mut single_side_limit_order_book = SingleSideLimitOrderBook::new(order_side);
 
     let order_1 = Order::new(
+        OrderId(1),
         ticker,
         order_side,
-        100.0,
-        20.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("100.0"),
+        fixed_point("20.0"),
+        source_exchange_1(),
+    );
 
     let order_2 = Order::new(
+        OrderId(2),
         ticker,
         order_side,
-        102.0,
-        10.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("102.0"),
+        fixed_point("10.0"),
+        source_exchange_2(),
+    );
 
     let order_3 = Order::new(
+        OrderId(3),
         ticker,
         order_side,
-        102.0,
-        12.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("102.0"),
+        fixed_point("12.0"),
+        source_exchange_2(),
+    );
     
-    single_side_limit_order_book.add_order(order_1);
-    single_side_limit_order_book.add_order(order_2);
-    single_side_limit_order_book.add_order(order_3);
+    single_side_limit_order_book.add_order(order_1).unwrap();
+    single_side_limit_order_book.add_order(order_2).unwrap();
+    single_side_limit_order_book.add_order(order_3).unwrap();
 
     let total_volume = single_side_limit_order_book.total_volume_by_price_level();
     let expected_total_volume = BTreeMap::from(
         [
-            (NotNan::new(100.0).unwrap(), NotNan::new(20.0).unwrap()),
-            (NotNan::new(102.0).unwrap(), NotNan::new(10.0 + 12.0).unwrap()),
+            (fixed_point("100.0"), fixed_point("20.0")),
+            (fixed_point("102.0"), fixed_point("22.0")),
         ]
     );
 
@@ -119,8 +125,8 @@ fn single_side_limit_order_book_test() {
     let highest_price = single_side_limit_order_book.highest_price();
     let lowest_price = single_side_limit_order_book.lowest_price();
 
-    assert_eq!(lowest_price, Some(NotNan::new(100.0).unwrap()));
-    assert_eq!(highest_price, Some(NotNan::new(102.0).unwrap()));
+    assert_eq!(lowest_price, Some(fixed_point("100.0")));
+    assert_eq!(highest_price, Some(fixed_point("102.0")));
 
     // Test highest price by exchange and lowest price by exchange
 
@@ -129,14 +135,14 @@ fn single_side_limit_order_book_test() {
 
     let expected_highest_price_by_exchange = BTreeMap::from(
         [
-            (SOURCE_EXCHANGE_1, NotNan::new(100.0).unwrap()),
-            (SOURCE_EXCHANGE_2, NotNan::new(102.0).unwrap()),
+            (source_exchange_1(), fixed_point("100.0")),
+            (source_exchange_2(), fixed_point("102.0")),
         ]
     );
     let expected_lowest_price_by_exchange = BTreeMap::from(
         [
-            (SOURCE_EXCHANGE_1, NotNan::new(100.0).unwrap()),
-            (SOURCE_EXCHANGE_2, NotNan::new(102.0).unwrap()),
+            (source_exchange_1(), fixed_point("100.0")),
+            (source_exchange_2(), fixed_point("102.0")),
         ]
     );
 
@@ -148,44 +154,47 @@ fn single_side_limit_order_book_test() {
 #[test]
 fn double_side_limit_order_book_test() {
 
-    let ticker = "EXAMPLE1";
+    let ticker = ticker_1();
 
     let mut double_side_limit_order_book = DoubleSideLimitOrderBook::new(ticker);
 
     let order_1 = Order::new(
+        OrderId(1),
         ticker,
         OrderSide::BUY,
-        102.0,
-        10.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("102.0"),
+        fixed_point("10.0"),
+        source_exchange_1(),
+    );
 
     let order_2 = Order::new(
+        OrderId(2),
         ticker,
         OrderSide::BUY,
-        100.0,
-        20.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("100.0"),
+        fixed_point("20.0"),
+        source_exchange_2(),
+    );
 
     let order_3 = Order::new(
+        OrderId(3),
         ticker,
         OrderSide::SELL,
-        110.0,
-        12.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("110.0"),
+        fixed_point("12.0"),
+        source_exchange_2(),
+    );
     
-    double_side_limit_order_book.add_order(order_1);
-    double_side_limit_order_book.add_order(order_2);
-    double_side_limit_order_book.add_order(order_3);
+    double_side_limit_order_book.add_order(order_1).unwrap();
+    double_side_limit_order_book.add_order(order_2).unwrap();
+    double_side_limit_order_book.add_order(order_3).unwrap();
 
     {
         let total_volume = double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY);
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(100.0).unwrap(), NotNan::new(20.0).unwrap()),
-                (NotNan::new(102.0).unwrap(), NotNan::new(10.0).unwrap()),
+                (fixed_point("100.0"), fixed_point("20.0")),
+                (fixed_point("102.0"), fixed_point("10.0")),
             ]
         );
 
@@ -197,7 +206,7 @@ fn double_side_limit_order_book_test() {
 
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(110.0).unwrap(), NotNan::new(12.0).unwrap()),
+                (fixed_point("110.0"), fixed_point("12.0")),
             ]
         );
 
@@ -209,8 +218,8 @@ fn double_side_limit_order_book_test() {
         let total_volume = double_side_limit_order_book.total_volume_by_source_exchange(&OrderSide::BUY);
         let expected_total_volume = BTreeMap::from(
             [
-                (SOURCE_EXCHANGE_1, NotNan::new(10.0).unwrap()),
-                (SOURCE_EXCHANGE_2, NotNan::new(20.0).unwrap()),
+                (source_exchange_1(), fixed_point("10.0")),
+                (source_exchange_2(), fixed_point("20.0")),
             ]
         );
         assert_eq!(total_volume, expected_total_volume);
@@ -220,7 +229,7 @@ fn double_side_limit_order_book_test() {
         let total_volume = double_side_limit_order_book.total_volume_by_source_exchange(&OrderSide::SELL);
         let expected_total_volume = BTreeMap::from(
             [
-                (SOURCE_EXCHANGE_2, NotNan::new(12.0).unwrap()),
+                (source_exchange_2(), fixed_point("12.0")),
             ]
         );
         assert_eq!(total_volume, expected_total_volume);
@@ -231,8 +240,8 @@ fn double_side_limit_order_book_test() {
         let total_volume = double_side_limit_order_book.total_volume_by_price_level_and_source_exchange(&OrderSide::BUY);
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(100.0).unwrap(), BTreeMap::from([(SOURCE_EXCHANGE_2, NotNan::new(20.0).unwrap())])),
-                (NotNan::new(102.0).unwrap(), BTreeMap::from([(SOURCE_EXCHANGE_1, NotNan::new(10.0).unwrap())])),
+                (fixed_point("100.0"), BTreeMap::from([(source_exchange_2(), fixed_point("20.0"))])),
+                (fixed_point("102.0"), BTreeMap::from([(source_exchange_1(), fixed_point("10.0"))])),
             ]
         );
         assert_eq!(total_volume, expected_total_volume);
@@ -242,7 +251,7 @@ fn double_side_limit_order_book_test() {
         let total_volume = double_side_limit_order_book.total_volume_by_price_level_and_source_exchange(&OrderSide::SELL);
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(110.0).unwrap(), BTreeMap::from([(SOURCE_EXCHANGE_2, NotNan::new(12.0).unwrap())])),
+                (fixed_point("110.0"), BTreeMap::from([(source_exchange_2(), fixed_point("12.0"))])),
             ]
         );
         assert_eq!(total_volume, expected_total_volume);
@@ -250,19 +259,19 @@ fn double_side_limit_order_book_test() {
 
     // Test spread
     {
-        let spread = double_side_limit_order_book.spread();
-        assert_eq!(spread, Some(NotNan::new(8.0).unwrap()));
+        let spread = double_side_limit_order_book.spread().unwrap();
+        assert_eq!(spread, Some(fixed_point("8.0")));
     }
 
     // Test spread by exchange
     {
-        let spread_by_exchange = double_side_limit_order_book.spread_by_exchange();
+        let spread_by_exchange = double_side_limit_order_book.spread_by_exchange().unwrap();
         assert_eq!(
             spread_by_exchange,
             BTreeMap::from(
                 [
-                    (SOURCE_EXCHANGE_1, None),
-                    (SOURCE_EXCHANGE_2, Some(NotNan::new(10.0).unwrap()))
+                    (source_exchange_1(), None),
+                    (source_exchange_2(), Some(fixed_point("10.0")))
                 ]
             )
         )
@@ -276,66 +285,72 @@ fn multi_ticker_limit_order_book_test() {
     let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
     
     let order_1 = Order::new(
-        TICKER_1,
+        OrderId(1),
+        ticker_1(),
         OrderSide::BUY,
-        100.0,
-        20.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("100.0"),
+        fixed_point("20.0"),
+        source_exchange_1(),
+    );
 
     let order_2 = Order::new(
-        TICKER_1,
+        OrderId(2),
+        ticker_1(),
         OrderSide::BUY,
-        102.0,
-        10.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("102.0"),
+        fixed_point("10.0"),
+        source_exchange_2(),
+    );
 
     let order_3 = Order::new(
-        TICKER_1,
+        OrderId(3),
+        ticker_1(),
         OrderSide::SELL,
-        110.0,
-        12.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("110.0"),
+        fixed_point("12.0"),
+        source_exchange_2(),
+    );
 
     let order_4 = Order::new(
-        TICKER_2,
+        OrderId(4),
+        ticker_2(),
         OrderSide::BUY,
-        10.0,
-        1.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("10.0"),
+        fixed_point("1.0"),
+        source_exchange_1(),
+    );
 
     let order_5 = Order::new(
-        TICKER_2,
+        OrderId(5),
+        ticker_2(),
         OrderSide::BUY,
-        10.2,
-        2.0,
-        SOURCE_EXCHANGE_1,
-    ).unwrap();
+        fixed_point("10.2"),
+        fixed_point("2.0"),
+        source_exchange_1(),
+    );
 
     let order_6 = Order::new(
-        TICKER_2,
+        OrderId(6),
+        ticker_2(),
         OrderSide::BUY,
-        10.3,
-        3.0,
-        SOURCE_EXCHANGE_2,
-    ).unwrap();
+        fixed_point("10.3"),
+        fixed_point("3.0"),
+        source_exchange_2(),
+    );
     
-    multi_ticker_limit_order_book.add_order(order_1);
-    multi_ticker_limit_order_book.add_order(order_2);
-    multi_ticker_limit_order_book.add_order(order_3);
-    multi_ticker_limit_order_book.add_order(order_4);
-    multi_ticker_limit_order_book.add_order(order_5);
-    multi_ticker_limit_order_book.add_order(order_6);
+    multi_ticker_limit_order_book.add_order(order_1).unwrap();
+    multi_ticker_limit_order_book.add_order(order_2).unwrap();
+    multi_ticker_limit_order_book.add_order(order_3).unwrap();
+    multi_ticker_limit_order_book.add_order(order_4).unwrap();
+    multi_ticker_limit_order_book.add_order(order_5).unwrap();
+    multi_ticker_limit_order_book.add_order(order_6).unwrap();
 
     {
-        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(TICKER_1, &OrderSide::BUY);
+        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::BUY).unwrap();
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(100.0).unwrap(), NotNan::new(20.0).unwrap()),
-                (NotNan::new(102.0).unwrap(), NotNan::new(10.0).unwrap()),
+                (fixed_point("100.0"), fixed_point("20.0")),
+                (fixed_point("102.0"), fixed_point("10.0")),
             ]
         );
 
@@ -343,11 +358,11 @@ fn multi_ticker_limit_order_book_test() {
     }
 
     {
-        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(TICKER_1, &OrderSide::SELL);
+        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::SELL).unwrap();
 
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(110.0).unwrap(), NotNan::new(12.0).unwrap()),
+                (fixed_point("110.0"), fixed_point("12.0")),
             ]
         );
 
@@ -355,13 +370,13 @@ fn multi_ticker_limit_order_book_test() {
     }
 
     {
-        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(TICKER_2, &OrderSide::BUY);
+        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_2(), &OrderSide::BUY).unwrap();
 
         let expected_total_volume = BTreeMap::from(
             [
-                (NotNan::new(10.0).unwrap(), NotNan::new(1.0).unwrap()),
-                (NotNan::new(10.2).unwrap(), NotNan::new(2.0).unwrap()),
-                (NotNan::new(10.3).unwrap(), NotNan::new(3.0).unwrap()),
+                (fixed_point("10.0"), fixed_point("1.0")),
+                (fixed_point("10.2"), fixed_point("2.0")),
+                (fixed_point("10.3"), fixed_point("3.0")),
             ]
         );
 
@@ -369,7 +384,7 @@ fn multi_ticker_limit_order_book_test() {
     }
 
     {
-        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(TICKER_2, &OrderSide::SELL);
+        let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_2(), &OrderSide::SELL).unwrap();
 
         let expected_total_volume = BTreeMap::from([]);
 
@@ -379,36 +394,36 @@ fn multi_ticker_limit_order_book_test() {
 
 
 fn add_some_orders(multi_ticker_limit_order_book: &mut MultiTickerLimitOrderBook) {
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY , 100.0, 20.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY , 100.0, 10.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY , 100.0,  5.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY ,  98.0, 20.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY ,  98.0,  5.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::SELL, 120.0, 20.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::SELL, 120.0, 10.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::SELL, 122.0, 10.0, SOURCE_EXCHANGE_1).unwrap());
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(101), ticker_1(), OrderSide::BUY , fixed_point("100.0"), fixed_point("20.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(102), ticker_1(), OrderSide::BUY , fixed_point("100.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(103), ticker_1(), OrderSide::BUY , fixed_point("100.0"),  fixed_point("5.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(104), ticker_1(), OrderSide::BUY ,  fixed_point("98.0"), fixed_point("20.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(105), ticker_1(), OrderSide::BUY ,  fixed_point("98.0"),  fixed_point("5.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(106), ticker_1(), OrderSide::SELL, fixed_point("120.0"), fixed_point("20.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(107), ticker_1(), OrderSide::SELL, fixed_point("120.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(108), ticker_1(), OrderSide::SELL, fixed_point("122.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
     
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY , 100.0, 10.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY , 100.0,  5.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY , 100.0,  2.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY ,  99.0,  5.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::BUY ,  99.0,  5.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::SELL, 120.0, 10.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::SELL, 120.0, 10.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_1, OrderSide::SELL, 120.0,  1.0, SOURCE_EXCHANGE_2).unwrap());
-
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY , 1000.0, 20.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY , 1000.0,  5.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY ,  980.0,  5.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::SELL, 1200.0, 20.0, SOURCE_EXCHANGE_1).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::SELL, 1220.0, 10.0, SOURCE_EXCHANGE_1).unwrap());
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(109), ticker_1(), OrderSide::BUY , fixed_point("100.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(110), ticker_1(), OrderSide::BUY , fixed_point("100.0"),  fixed_point("5.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(111), ticker_1(), OrderSide::BUY , fixed_point("100.0"),  fixed_point("2.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(112), ticker_1(), OrderSide::BUY ,  fixed_point("99.0"),  fixed_point("5.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(113), ticker_1(), OrderSide::BUY ,  fixed_point("99.0"),  fixed_point("5.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(114), ticker_1(), OrderSide::SELL, fixed_point("120.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(115), ticker_1(), OrderSide::SELL, fixed_point("120.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(116), ticker_1(), OrderSide::SELL, fixed_point("120.0"),  fixed_point("1.0"), source_exchange_2())).unwrap();
+
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(117), ticker_2(), OrderSide::BUY , fixed_point("1000.0"), fixed_point("20.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(118), ticker_2(), OrderSide::BUY , fixed_point("1000.0"),  fixed_point("5.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(119), ticker_2(), OrderSide::BUY ,  fixed_point("980.0"),  fixed_point("5.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(120), ticker_2(), OrderSide::SELL, fixed_point("1200.0"), fixed_point("20.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(121), ticker_2(), OrderSide::SELL, fixed_point("1220.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
     
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY , 1000.0, 10.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY , 1000.0,  5.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY ,  990.0,  5.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::BUY ,  990.0,  5.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::SELL, 1200.0, 10.0, SOURCE_EXCHANGE_2).unwrap());
-    multi_ticker_limit_order_book.add_order(Order::new(TICKER_2, OrderSide::SELL, 1200.0, 10.0, SOURCE_EXCHANGE_2).unwrap());
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(122), ticker_2(), OrderSide::BUY , fixed_point("1000.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(123), ticker_2(), OrderSide::BUY , fixed_point("1000.0"),  fixed_point("5.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(124), ticker_2(), OrderSide::BUY ,  fixed_point("990.0"),  fixed_point("5.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(125), ticker_2(), OrderSide::BUY ,  fixed_point("990.0"),  fixed_point("5.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(126), ticker_2(), OrderSide::SELL, fixed_point("1200.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(127), ticker_2(), OrderSide::SELL, fixed_point("1200.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
 }
 
 
@@ -420,32 +435,32 @@ fn multi_ticker_limit_order_book_total_volume_by_price_level_and_source_exchange
     add_some_orders(&mut multi_ticker_limit_order_book);
 
     let total_volume_by_price_level_and_source_exchange =
-        multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(TICKER_1, &OrderSide::BUY);
+        multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::BUY).unwrap();
 
     let expected_total_volume_by_price_level_and_source_exchange = BTreeMap::from(
         [
             (
-                NotNan::new(98.0).unwrap(),
+                fixed_point("98.0"),
                 BTreeMap::from(
                     [
-                        (SOURCE_EXCHANGE_1, NotNan::new(25.0).unwrap()),
+                        (source_exchange_1(), fixed_point("25.0")),
                     ]
                 )
             ),
             (
-                NotNan::new(99.0).unwrap(),
+                fixed_point("99.0"),
                 BTreeMap::from(
                     [
-                        (SOURCE_EXCHANGE_2, NotNan::new(10.0).unwrap()),
+                        (source_exchange_2(), fixed_point("10.0")),
                     ]
                 )
             ),
             (
-                NotNan::new(100.0).unwrap(),
+                fixed_point("100.0"),
                 BTreeMap::from(
                     [
-                        (SOURCE_EXCHANGE_1, NotNan::new(35.0).unwrap()),
-                        (SOURCE_EXCHANGE_2, NotNan::new(17.0).unwrap()),
+                        (source_exchange_1(), fixed_point("35.0")),
+                        (source_exchange_2(), fixed_point("17.0")),
                     ]
                 )
             )
@@ -467,13 +482,13 @@ fn multi_ticker_limit_order_book_total_volume_by_price_level_test() {
     add_some_orders(&mut multi_ticker_limit_order_book);
 
     let total_volume_by_price_level =
-        multi_ticker_limit_order_book.total_volume_by_price_level(TICKER_1, &OrderSide::BUY);
+        multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::BUY).unwrap();
 
     let expected_total_volume_by_price_level = BTreeMap::from(
         [
-            (NotNan::new(98.0).unwrap(), NotNan::new(25.0).unwrap()),
-            (NotNan::new(99.0).unwrap(), NotNan::new(10.0).unwrap()),
-            (NotNan::new(100.0).unwrap(), NotNan::new(35.0 + 17.0).unwrap())
+            (fixed_point("98.0"), fixed_point("25.0")),
+            (fixed_point("99.0"), fixed_point("10.0")),
+            (fixed_point("100.0"), fixed_point("52.0"))
         ]
     );
 
@@ -500,12 +515,12 @@ fn multi_ticker_limit_order_book_total_volume_by_source_exchange_test() {
 
     {
         let total_volume_by_source_exchange = 
-            multi_ticker_limit_order_book.total_volume_by_source_exchange(TICKER_1, &OrderSide::BUY);
+            multi_ticker_limit_order_book.total_volume_by_source_exchange(ticker_1(), &OrderSide::BUY).unwrap();
 
         let expected_total_volume_by_source_exchange = BTreeMap::from(
             [
-                (SOURCE_EXCHANGE_1, NotNan::new(60.0).unwrap()),
-                (SOURCE_EXCHANGE_2, NotNan::new(27.0).unwrap()),
+                (source_exchange_1(), fixed_point("60.0")),
+                (source_exchange_2(), fixed_point("27.0")),
             ]
         );
 
@@ -514,12 +529,12 @@ fn multi_ticker_limit_order_book_total_volume_by_source_exchange_test() {
 
     {
         let total_volume_by_source_exchange = 
-            multi_ticker_limit_order_book.total_volume_by_source_exchange(TICKER_1, &OrderSide::SELL);
+            multi_ticker_limit_order_book.total_volume_by_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
 
         let expected_total_volume_by_source_exchange = BTreeMap::from(
             [
-                (SOURCE_EXCHANGE_1, NotNan::new(40.0).unwrap()),
-                (SOURCE_EXCHANGE_2, NotNan::new(21.0).unwrap()),
+                (source_exchange_1(), fixed_point("40.0")),
+                (source_exchange_2(), fixed_point("21.0")),
             ]
         );
 
@@ -528,6 +543,1107 @@ fn multi_ticker_limit_order_book_total_volume_by_source_exchange_test() {
 }
 
 
+#[test]
+fn price_level_cancel_reduce_replace_test() {
+
+    let price = fixed_point("100.0");
+    let mut price_level = PriceLevel::new(price);
+
+    price_level.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::BUY, price, fixed_point("20.0"), source_exchange_1())).unwrap();
+    price_level.add_order(Order::new(OrderId(2), ticker_1(), OrderSide::BUY, price, fixed_point("25.0"), source_exchange_1())).unwrap();
+    price_level.add_order(Order::new(OrderId(3), ticker_1(), OrderSide::BUY, price, fixed_point("55.0"), source_exchange_2())).unwrap();
+
+    // Reducing an order keeps its position in the queue
+    let remaining_volume = price_level.reduce_order(OrderId(1), fixed_point("5.0"));
+    assert_eq!(remaining_volume, Ok(fixed_point("15.0")));
+    assert_eq!(price_level.order_ids(), vec![OrderId(1), OrderId(2), OrderId(3)]);
+
+    // Replacing with a smaller volume keeps the position in the queue
+    price_level.replace_order(OrderId(2), fixed_point("10.0")).unwrap();
+    assert_eq!(price_level.order_ids(), vec![OrderId(1), OrderId(2), OrderId(3)]);
+
+    // Replacing with a larger volume loses time priority
+    price_level.replace_order(OrderId(1), fixed_point("30.0")).unwrap();
+    assert_eq!(price_level.order_ids(), vec![OrderId(2), OrderId(3), OrderId(1)]);
+
+    let total_volume = price_level.total_volume_with_price_level();
+    assert_eq!(total_volume, (price, fixed_point("95.0")));
+
+    // Reducing by the full volume removes the order
+    let remaining_volume = price_level.reduce_order(OrderId(3), fixed_point("60.0"));
+    assert_eq!(remaining_volume, Ok(FixedPoint::ZERO));
+    assert_eq!(price_level.order_ids(), vec![OrderId(2), OrderId(1)]);
+
+    let cancelled_order = price_level.cancel_order(OrderId(2)).unwrap();
+    assert_eq!(cancelled_order.order_id(), OrderId(2));
+    assert_eq!(price_level.order_ids(), vec![OrderId(1)]);
+
+    // Unknown order ids
+    assert!(price_level.cancel_order(OrderId(2)).is_err());
+    assert!(price_level.reduce_order(OrderId(3), fixed_point("1.0")).is_err());
+    assert_eq!(
+        price_level.replace_order(OrderId(4), fixed_point("1.0")),
+        Err(OrderBookError::UnknownOrderId { order_id: OrderId(4) }),
+    );
+
+    assert!(price_level.cancel_order(OrderId(1)).is_ok());
+    assert!(price_level.is_empty());
+}
+
+
+#[test]
+fn single_side_limit_order_book_cancel_reduce_replace_test() {
+
+    let order_side = OrderSide::SELL;
+
+    let mut single_side_limit_order_book = SingleSideLimitOrderBook::new(order_side);
+
+    single_side_limit_order_book.add_order(Order::new(OrderId(1), ticker_1(), order_side, fixed_point("100.0"), fixed_point("20.0"), source_exchange_1())).unwrap();
+    single_side_limit_order_book.add_order(Order::new(OrderId(2), ticker_1(), order_side, fixed_point("100.0"), fixed_point("10.0"), source_exchange_2())).unwrap();
+    single_side_limit_order_book.add_order(Order::new(OrderId(3), ticker_1(), order_side, fixed_point("102.0"), fixed_point("12.0"), source_exchange_2())).unwrap();
+
+    // Cancelling the last order at a price level removes the price level
+    let cancelled_order = single_side_limit_order_book.cancel_order(OrderId(3)).unwrap();
+    assert_eq!(cancelled_order.order_id(), OrderId(3));
+    assert!(single_side_limit_order_book.price_level(fixed_point("102.0")).is_none());
+    assert_eq!(single_side_limit_order_book.highest_price(), Some(fixed_point("100.0")));
+    assert!(single_side_limit_order_book.cancel_order(OrderId(3)).is_err());
+
+    // Reducing the full volume of an order removes it from the book
+    let remaining_volume = single_side_limit_order_book.reduce_order(OrderId(2), fixed_point("10.0"));
+    assert_eq!(remaining_volume, Ok(FixedPoint::ZERO));
+    assert!(single_side_limit_order_book.reduce_order(OrderId(2), fixed_point("1.0")).is_err());
+
+    single_side_limit_order_book.add_order(Order::new(OrderId(4), ticker_1(), order_side, fixed_point("100.0"), fixed_point("5.0"), source_exchange_2())).unwrap();
+    assert_eq!(
+        single_side_limit_order_book.price_level(fixed_point("100.0")).unwrap().order_ids(),
+        vec![OrderId(1), OrderId(4)],
+    );
+
+    // Replacing with a new price loses time priority
+    single_side_limit_order_book.replace_order(OrderId(1), fixed_point("101.0"), fixed_point("20.0")).unwrap();
+    single_side_limit_order_book.replace_order(OrderId(1), fixed_point("100.0"), fixed_point("20.0")).unwrap();
+    assert_eq!(
+        single_side_limit_order_book.price_level(fixed_point("100.0")).unwrap().order_ids(),
+        vec![OrderId(4), OrderId(1)],
+    );
+    assert!(single_side_limit_order_book.price_level(fixed_point("101.0")).is_none());
+
+    assert_eq!(
+        single_side_limit_order_book.replace_order(OrderId(5), fixed_point("100.0"), fixed_point("1.0")),
+        Err(OrderBookError::UnknownOrderId { order_id: OrderId(5) }),
+    );
+
+    let total_volume = single_side_limit_order_book.total_volume_by_price_level();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (fixed_point("100.0"), fixed_point("25.0")),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+}
+
+
+#[test]
+fn multi_ticker_limit_order_book_cancel_reduce_replace_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+
+    add_some_orders(&mut multi_ticker_limit_order_book);
+
+    // ticker_1() SELL 122.0 has a single order
+    let cancelled_order = multi_ticker_limit_order_book.cancel_order(OrderId(108)).unwrap();
+    assert_eq!(cancelled_order.order_id(), OrderId(108));
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(108)).is_err());
+
+    let remaining_volume = multi_ticker_limit_order_book.reduce_order(OrderId(101), fixed_point("15.0"));
+    assert_eq!(remaining_volume, Ok(fixed_point("5.0")));
+
+    // Move the ticker_2() SELL order at 1220.0 to 1210.0
+    multi_ticker_limit_order_book.replace_order(OrderId(121), fixed_point("1210.0"), fixed_point("10.0")).unwrap();
+    assert_eq!(
+        multi_ticker_limit_order_book.replace_order(OrderId(999), fixed_point("1210.0"), fixed_point("10.0")),
+        Err(OrderBookError::UnknownOrderId { order_id: OrderId(999) }),
+    );
+
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::SELL).unwrap();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (fixed_point("120.0"), fixed_point("51.0")),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::BUY).unwrap();
+    assert_eq!(total_volume.get(&fixed_point("100.0")), Some(&fixed_point("37.0")));
+
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_2(), &OrderSide::SELL).unwrap();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (fixed_point("1200.0"), fixed_point("40.0")),
+            (fixed_point("1210.0"), fixed_point("10.0")),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+}
+
+
+#[test]
+fn double_side_limit_order_book_price_time_priority_matching_test() {
+
+    let mut double_side_limit_order_book =
+        DoubleSideLimitOrderBook::new_with_matching_mode(ticker_1(), MatchingMode::PriceTimePriority);
+
+    let fills = double_side_limit_order_book.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::SELL, fixed_point("101.0"),  fixed_point("5.0"), source_exchange_1())).unwrap();
+    assert!(fills.is_empty());
+    double_side_limit_order_book.add_order(Order::new(OrderId(2), ticker_1(), OrderSide::SELL, fixed_point("101.0"),  fixed_point("3.0"), source_exchange_2())).unwrap();
+    double_side_limit_order_book.add_order(Order::new(OrderId(3), ticker_1(), OrderSide::SELL, fixed_point("102.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
+
+    // A BUY which crosses two price levels is filled in price-time priority
+    let fills = double_side_limit_order_book.add_order(Order::new(OrderId(4), ticker_1(), OrderSide::BUY, fixed_point("102.0"), fixed_point("12.0"), source_exchange_2())).unwrap();
+    let expected_fills = vec![
+        Fill {
+            maker_order_id: OrderId(1),
+            taker_order_id: OrderId(4),
+            maker_source_exchange: source_exchange_1(),
+            price: fixed_point("101.0"),
+            volume: fixed_point("5.0"),
+            maker_remaining_volume: FixedPoint::ZERO,
+        },
+        Fill {
+            maker_order_id: OrderId(2),
+            taker_order_id: OrderId(4),
+            maker_source_exchange: source_exchange_2(),
+            price: fixed_point("101.0"),
+            volume: fixed_point("3.0"),
+            maker_remaining_volume: FixedPoint::ZERO,
+        },
+        Fill {
+            maker_order_id: OrderId(3),
+            taker_order_id: OrderId(4),
+            maker_source_exchange: source_exchange_1(),
+            price: fixed_point("102.0"),
+            volume: fixed_point("4.0"),
+            maker_remaining_volume: fixed_point("6.0"),
+        },
+    ];
+    assert_eq!(fills, expected_fills);
+
+    // The taker was completely filled so does not rest in the book
+    assert!(!double_side_limit_order_book.contains_order(OrderId(4)));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(1)));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(2)));
+    assert!(double_side_limit_order_book.contains_order(OrderId(3)));
+    assert_eq!(
+        double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY),
+        BTreeMap::new(),
+    );
+    assert_eq!(
+        double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL),
+        BTreeMap::from([(fixed_point("102.0"), fixed_point("6.0"))]),
+    );
+
+    // A BUY which does not cross rests in the book
+    let fills = double_side_limit_order_book.add_order(Order::new(OrderId(5), ticker_1(), OrderSide::BUY, fixed_point("101.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(double_side_limit_order_book.spread(), Ok(Some(fixed_point("1.0"))));
+
+    // A SELL which crosses with remaining volume rests at its limit price
+    let fills = double_side_limit_order_book.add_order(Order::new(OrderId(6), ticker_1(), OrderSide::SELL, fixed_point("100.0"), fixed_point("15.0"), source_exchange_2())).unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].maker_order_id, OrderId(5));
+    assert_eq!(fills[0].price, fixed_point("101.0"));
+    assert_eq!(fills[0].volume, fixed_point("10.0"));
+    assert!(double_side_limit_order_book.contains_order(OrderId(6)));
+    assert_eq!(
+        double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL),
+        BTreeMap::from(
+            [
+                (fixed_point("100.0"), fixed_point("5.0")),
+                (fixed_point("102.0"), fixed_point("6.0")),
+            ]
+        ),
+    );
+}
+
+
+#[test]
+fn multi_ticker_limit_order_book_aggregate_mode_does_not_match_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::SELL, fixed_point("100.0"), fixed_point("5.0"), source_exchange_1())).unwrap();
+    let fills = multi_ticker_limit_order_book.add_order(Order::new(OrderId(2), ticker_1(), OrderSide::BUY, fixed_point("101.0"), fixed_point("5.0"), source_exchange_2())).unwrap();
+    assert!(fills.is_empty());
+    assert_eq!(multi_ticker_limit_order_book.spread(ticker_1()).unwrap(), Some(fixed_point("-1.0")));
+}
+
+
+#[test]
+fn multi_ticker_limit_order_book_price_time_priority_matching_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new_with_matching_mode(MatchingMode::PriceTimePriority);
+
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::SELL, fixed_point("100.0"), fixed_point("5.0"), source_exchange_1())).unwrap();
+    multi_ticker_limit_order_book.add_order(Order::new(OrderId(2), ticker_2(), OrderSide::SELL, fixed_point("100.0"), fixed_point("5.0"), source_exchange_1())).unwrap();
+    let fills = multi_ticker_limit_order_book.add_order(Order::new(OrderId(3), ticker_1(), OrderSide::BUY, fixed_point("101.0"), fixed_point("8.0"), source_exchange_2())).unwrap();
+
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].maker_order_id, OrderId(1));
+    assert_eq!(fills[0].price, fixed_point("100.0"));
+    assert_eq!(fills[0].volume, fixed_point("5.0"));
+
+    // Filled makers can no longer be cancelled, the remainder of the taker can
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(1)).is_err());
+    assert_eq!(multi_ticker_limit_order_book.reduce_order(OrderId(3), fixed_point("1.0")), Ok(fixed_point("2.0")));
+
+    // Orders for other tickers are not matched
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(2)).is_ok());
+}
+
+
+fn add_some_sell_orders(double_side_limit_order_book: &mut DoubleSideLimitOrderBook) {
+    double_side_limit_order_book.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::SELL, fixed_point("101.0"),  fixed_point("5.0"), source_exchange_1())).unwrap();
+    double_side_limit_order_book.add_order(Order::new(OrderId(2), ticker_1(), OrderSide::SELL, fixed_point("101.0"),  fixed_point("3.0"), source_exchange_2())).unwrap();
+    double_side_limit_order_book.add_order(Order::new(OrderId(3), ticker_1(), OrderSide::SELL, fixed_point("102.0"), fixed_point("10.0"), source_exchange_1())).unwrap();
+}
+
+
+fn expected_sell_volume_by_price_level() -> BTreeMap<FixedPoint, FixedPoint> {
+    BTreeMap::from(
+        [
+            (fixed_point("101.0"), fixed_point("8.0")),
+            (fixed_point("102.0"), fixed_point("10.0")),
+        ]
+    )
+}
+
+
+#[test]
+fn market_order_test() {
+
+    let mut double_side_limit_order_book = DoubleSideLimitOrderBook::new(ticker_1());
+    add_some_sell_orders(&mut double_side_limit_order_book);
+
+    // Market orders match at any price, even when the book is not matching limit orders
+    let order = Order::new_market(OrderId(4), ticker_1(), OrderSide::BUY, fixed_point("12.0"), source_exchange_2());
+    let fills = double_side_limit_order_book.add_order(order).unwrap();
+
+    let filled = fills.iter().map(|fill| (fill.maker_order_id, fill.price, fill.volume)).collect::<Vec<_>>();
+    assert_eq!(
+        filled,
+        vec![
+            (OrderId(1), fixed_point("101.0"), fixed_point("5.0")),
+            (OrderId(2), fixed_point("101.0"), fixed_point("3.0")),
+            (OrderId(3), fixed_point("102.0"), fixed_point("4.0")),
+        ]
+    );
+
+    // Unfilled volume of a market order is cancelled
+    let order = Order::new_market(OrderId(5), ticker_1(), OrderSide::BUY, fixed_point("100.0"), source_exchange_2());
+    let fills = double_side_limit_order_book.add_order(order).unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].volume, fixed_point("6.0"));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(5)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY), BTreeMap::new());
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL), BTreeMap::new());
+}
+
+
+#[test]
+fn immediate_or_cancel_order_test() {
+
+    let mut double_side_limit_order_book = DoubleSideLimitOrderBook::new(ticker_1());
+    add_some_sell_orders(&mut double_side_limit_order_book);
+
+    // Matches only up to the limit price, the remainder is cancelled
+    let order = Order::new_with_order_type(OrderId(4), ticker_1(), OrderSide::BUY, OrderType::ImmediateOrCancel, fixed_point("101.0"), fixed_point("10.0"), source_exchange_2());
+    let fills = double_side_limit_order_book.add_order(order).unwrap();
+    assert_eq!(fills.len(), 2);
+    assert_eq!(fills.iter().map(|fill| fill.volume).sum::<FixedPoint>(), fixed_point("8.0"));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(4)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY), BTreeMap::new());
+
+    // Does not cross, so nothing happens
+    let order = Order::new_with_order_type(OrderId(5), ticker_1(), OrderSide::BUY, OrderType::ImmediateOrCancel, fixed_point("101.0"), fixed_point("10.0"), source_exchange_2());
+    let fills = double_side_limit_order_book.add_order(order).unwrap();
+    assert!(fills.is_empty());
+    assert!(!double_side_limit_order_book.contains_order(OrderId(5)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY), BTreeMap::new());
+    assert_eq!(
+        double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL),
+        BTreeMap::from([(fixed_point("102.0"), fixed_point("10.0"))]),
+    );
+}
+
+
+#[test]
+fn fill_or_kill_order_test() {
+
+    let mut double_side_limit_order_book = DoubleSideLimitOrderBook::new(ticker_1());
+    add_some_sell_orders(&mut double_side_limit_order_book);
+
+    // Not enough volume at or below the limit price, the order is killed and
+    // the book is unchanged
+    let order = Order::new_with_order_type(OrderId(4), ticker_1(), OrderSide::BUY, OrderType::FillOrKill, fixed_point("101.0"), fixed_point("10.0"), source_exchange_2());
+    assert_eq!(
+        double_side_limit_order_book.add_order(order),
+        Err(OrderBookError::OrderKilled { order_id: OrderId(4) }),
+    );
+    assert!(!double_side_limit_order_book.contains_order(OrderId(4)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY), BTreeMap::new());
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL), expected_sell_volume_by_price_level());
+
+    // Enough volume, filled completely
+    let order = Order::new_with_order_type(OrderId(5), ticker_1(), OrderSide::BUY, OrderType::FillOrKill, fixed_point("102.0"), fixed_point("18.0"), source_exchange_2());
+    let fills = double_side_limit_order_book.add_order(order).unwrap();
+    assert_eq!(fills.len(), 3);
+    assert_eq!(fills.iter().map(|fill| fill.volume).sum::<FixedPoint>(), fixed_point("18.0"));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(5)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL), BTreeMap::new());
+}
+
+
+#[test]
+fn post_only_order_test() {
+
+    let mut double_side_limit_order_book =
+        DoubleSideLimitOrderBook::new_with_matching_mode(ticker_1(), MatchingMode::PriceTimePriority);
+    add_some_sell_orders(&mut double_side_limit_order_book);
+
+    // Would cross, so it is rejected and the book is unchanged
+    let order = Order::new_with_order_type(OrderId(4), ticker_1(), OrderSide::BUY, OrderType::PostOnly, fixed_point("101.0"), fixed_point("1.0"), source_exchange_2());
+    assert_eq!(
+        double_side_limit_order_book.add_order(order),
+        Err(OrderBookError::CrossedBook { order_id: OrderId(4) }),
+    );
+    assert!(!double_side_limit_order_book.contains_order(OrderId(4)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY), BTreeMap::new());
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL), expected_sell_volume_by_price_level());
+
+    // Does not cross, so it rests in the book
+    let order = Order::new_with_order_type(OrderId(5), ticker_1(), OrderSide::BUY, OrderType::PostOnly, fixed_point("100.5"), fixed_point("1.0"), source_exchange_2());
+    let fills = double_side_limit_order_book.add_order(order).unwrap();
+    assert!(fills.is_empty());
+    assert!(double_side_limit_order_book.contains_order(OrderId(5)));
+    assert_eq!(double_side_limit_order_book.spread(), Ok(Some(fixed_point("0.5"))));
+}
+
+
+#[test]
+fn replace_order_crossing_test() {
+
+    let mut double_side_limit_order_book =
+        DoubleSideLimitOrderBook::new_with_matching_mode(ticker_1(), MatchingMode::PriceTimePriority);
+    add_some_sell_orders(&mut double_side_limit_order_book);
+    double_side_limit_order_book.add_order(Order::new_with_order_type(OrderId(4), ticker_1(), OrderSide::BUY, OrderType::PostOnly, fixed_point("100.0"), fixed_point("2.0"), source_exchange_2())).unwrap();
+    double_side_limit_order_book.add_order(Order::new(OrderId(5), ticker_1(), OrderSide::BUY, fixed_point("99.0"), fixed_point("6.0"), source_exchange_2())).unwrap();
+
+    // A post only order cannot be replaced through the spread, and stays unchanged
+    assert_eq!(
+        double_side_limit_order_book.replace_order(OrderId(4), fixed_point("101.0"), fixed_point("2.0")),
+        Err(OrderBookError::CrossedBook { order_id: OrderId(4) }),
+    );
+    assert!(double_side_limit_order_book.contains_order(OrderId(4)));
+    assert_eq!(double_side_limit_order_book.best_bid_price(), Some(fixed_point("100.0")));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL), expected_sell_volume_by_price_level());
+
+    // A limit order replaced through the spread is matched, and the book is not left crossed
+    let fills = double_side_limit_order_book.replace_order(OrderId(5), fixed_point("101.0"), fixed_point("6.0")).unwrap();
+    let filled = fills.iter().map(|fill| (fill.maker_order_id, fill.volume)).collect::<Vec<_>>();
+    assert_eq!(filled, vec![(OrderId(1), fixed_point("5.0")), (OrderId(2), fixed_point("1.0"))]);
+    assert!(!double_side_limit_order_book.contains_order(OrderId(1)));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(5)));
+    assert_eq!(double_side_limit_order_book.best_bid_price(), Some(fixed_point("100.0")));
+    assert_eq!(double_side_limit_order_book.best_ask_price(), Some(fixed_point("101.0")));
+}
+
+
+#[test]
+fn order_book_error_test() {
+
+    let mut double_side_limit_order_book = DoubleSideLimitOrderBook::new(ticker_1());
+    add_some_sell_orders(&mut double_side_limit_order_book);
+
+    let order = Order::new(OrderId(4), ticker_2(), OrderSide::BUY, fixed_point("100.0"), fixed_point("1.0"), source_exchange_1());
+    assert_eq!(
+        double_side_limit_order_book.add_order(order),
+        Err(OrderBookError::WrongTicker { order_id: OrderId(4), expected: ticker_1(), actual: ticker_2() }),
+    );
+
+    let order = Order::new(OrderId(1), ticker_1(), OrderSide::BUY, fixed_point("100.0"), fixed_point("1.0"), source_exchange_1());
+    assert_eq!(
+        double_side_limit_order_book.add_order(order),
+        Err(OrderBookError::DuplicateOrderId { order_id: OrderId(1) }),
+    );
+
+    let order = Order::new(OrderId(5), ticker_1(), OrderSide::BUY, fixed_point("100.0"), fixed_point("0.0"), source_exchange_1());
+    assert_eq!(
+        double_side_limit_order_book.add_order(order),
+        Err(OrderBookError::NonPositiveVolume { order_id: OrderId(5), volume: fixed_point("0.0") }),
+    );
+
+    assert_eq!(
+        double_side_limit_order_book.reduce_order(OrderId(1), fixed_point("-1.0")),
+        Err(OrderBookError::NonPositiveVolume { order_id: OrderId(1), volume: fixed_point("-1.0") }),
+    );
+    assert_eq!(
+        double_side_limit_order_book.cancel_order(OrderId(6)).map(|order| order.order_id()),
+        Err(OrderBookError::UnknownOrderId { order_id: OrderId(6) }),
+    );
+
+    // Nothing was changed by the rejected requests
+    assert!(!double_side_limit_order_book.contains_order(OrderId(4)));
+    assert!(!double_side_limit_order_book.contains_order(OrderId(5)));
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::BUY), BTreeMap::new());
+    assert_eq!(double_side_limit_order_book.total_volume_by_price_level(&OrderSide::SELL), expected_sell_volume_by_price_level());
+
+    let mut single_side_limit_order_book = SingleSideLimitOrderBook::new(OrderSide::BUY);
+    let order = Order::new(OrderId(1), ticker_1(), OrderSide::SELL, fixed_point("100.0"), fixed_point("1.0"), source_exchange_1());
+    assert_eq!(
+        single_side_limit_order_book.add_order(order),
+        Err(OrderBookError::WrongSide { order_id: OrderId(1), expected: OrderSide::BUY, actual: OrderSide::SELL }),
+    );
+
+    let mut price_level = PriceLevel::new(fixed_point("100.0"));
+    let order = Order::new(OrderId(1), ticker_1(), OrderSide::BUY, fixed_point("101.0"), fixed_point("1.0"), source_exchange_1());
+    assert_eq!(
+        price_level.add_order(order),
+        Err(OrderBookError::WrongPrice { order_id: OrderId(1), expected: fixed_point("100.0"), actual: fixed_point("101.0") }),
+    );
+}
+
+
+#[test]
+fn trading_rules_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+    let mut trading_rules = TradingRules::new();
+    trading_rules.tick_size = Some(fixed_point("0.05"));
+    trading_rules.lot_size = Some(fixed_point("0.1"));
+    trading_rules.minimum_notional = Some(fixed_point("10"));
+    trading_rules.price_band_basis_points = Some(1000);
+    multi_ticker_limit_order_book.set_trading_rules(ticker_1(), trading_rules.clone());
+    assert_eq!(multi_ticker_limit_order_book.trading_rules(ticker_1()), Some(&trading_rules));
+    assert_eq!(multi_ticker_limit_order_book.trading_rules(ticker_2()), None);
+
+    let mut add_order = |order_id: u64, order_side: OrderSide, price: &str, volume: &str| {
+        let order = Order::new(OrderId(order_id), ticker_1(), order_side, fixed_point(price), fixed_point(volume), source_exchange_1());
+        multi_ticker_limit_order_book.add_order(order)
+    };
+
+    // Without a mid price the price band is not checked
+    assert_eq!(add_order(101, OrderSide::BUY, "95.00", "1.0"), Ok(Vec::new()));
+    assert_eq!(add_order(102, OrderSide::SELL, "105.05", "0.5"), Ok(Vec::new()));
+
+    assert_eq!(
+        add_order(103, OrderSide::BUY, "100.01", "1.0"),
+        Err(OrderBookError::PriceNotOnTick { order_id: OrderId(103), price: fixed_point("100.01"), tick_size: fixed_point("0.05") }),
+    );
+    assert_eq!(
+        add_order(104, OrderSide::BUY, "100.00", "1.05"),
+        Err(OrderBookError::VolumeNotOnLot { order_id: OrderId(104), volume: fixed_point("1.05"), lot_size: fixed_point("0.1") }),
+    );
+    assert_eq!(
+        add_order(105, OrderSide::BUY, "0", "1.0"),
+        Err(OrderBookError::NonPositivePrice { order_id: OrderId(105), price: fixed_point("0") }),
+    );
+    assert_eq!(
+        add_order(106, OrderSide::BUY, "-100.00", "1.0"),
+        Err(OrderBookError::NonPositivePrice { order_id: OrderId(106), price: fixed_point("-100.00") }),
+    );
+    assert_eq!(
+        add_order(107, OrderSide::BUY, "99.00", "0.1"),
+        Err(OrderBookError::BelowMinimumNotional { order_id: OrderId(107), notional: fixed_point("9.9"), minimum_notional: fixed_point("10") }),
+    );
+    // Orders which overflow when they are checked are rejected rather than panicking
+    assert_eq!(
+        trading_rules.check_order(OrderId(112), OrderType::Limit, FixedPoint::MAX, fixed_point("1.0"), None),
+        Err(OrderBookError::OrderOverflow { order_id: OrderId(112) }),
+    );
+    assert_eq!(
+        trading_rules.check_order(OrderId(112), OrderType::Limit, fixed_point("100.00"), FixedPoint::new(i128::MAX, 1), None),
+        Err(OrderBookError::OrderOverflow { order_id: OrderId(112) }),
+    );
+    // The mid price is 100.025, so the band is 90.0225 to 110.0275
+    assert_eq!(
+        add_order(108, OrderSide::SELL, "110.05", "1.0"),
+        Err(
+            OrderBookError::PriceOutsideBand {
+                order_id: OrderId(108),
+                price: fixed_point("110.05"),
+                lower_bound: fixed_point("90.0225"),
+                upper_bound: fixed_point("110.0275"),
+            }
+        ),
+    );
+    assert_eq!(add_order(109, OrderSide::SELL, "110.00", "1.0"), Ok(Vec::new()));
+
+    // Market orders have no price, but their volume must be on the lot size
+    let order = Order::new_market(OrderId(110), ticker_1(), OrderSide::BUY, fixed_point("0.05"), source_exchange_1());
+    assert_eq!(
+        multi_ticker_limit_order_book.add_order(order),
+        Err(OrderBookError::VolumeNotOnLot { order_id: OrderId(110), volume: fixed_point("0.05"), lot_size: fixed_point("0.1") }),
+    );
+
+    // Replacing an order is checked in the same way
+    assert_eq!(
+        multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.03"), fixed_point("1.0")),
+        Err(OrderBookError::PriceNotOnTick { order_id: OrderId(101), price: fixed_point("95.03"), tick_size: fixed_point("0.05") }),
+    );
+    multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.05"), fixed_point("2.0")).unwrap();
+
+    // Rejected orders do not change the book, and other tickers have no rules
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::BUY).unwrap();
+    assert_eq!(total_volume, BTreeMap::from([(fixed_point("95.05"), fixed_point("2.0"))]));
+    let order = Order::new(OrderId(111), ticker_2(), OrderSide::BUY, fixed_point("-1.001"), fixed_point("0.001"), source_exchange_1());
+    assert!(multi_ticker_limit_order_book.add_order(order).is_ok());
+
+    // An order which stays at the same price is only checked against the lot
+    // size and minimum notional, even if its price is now off the tick or
+    // outside the band
+    let mut tighter_trading_rules = trading_rules.clone();
+    tighter_trading_rules.tick_size = Some(fixed_point("0.1"));
+    tighter_trading_rules.price_band_basis_points = Some(100);
+    multi_ticker_limit_order_book.set_trading_rules(ticker_1(), tighter_trading_rules);
+    multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.05"), fixed_point("1.0")).unwrap();
+    assert_eq!(
+        multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.05"), fixed_point("0.15")),
+        Err(OrderBookError::VolumeNotOnLot { order_id: OrderId(101), volume: fixed_point("0.15"), lot_size: fixed_point("0.1") }),
+    );
+    assert_eq!(
+        multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.05"), fixed_point("0.1")),
+        Err(OrderBookError::BelowMinimumNotional { order_id: OrderId(101), notional: fixed_point("9.505"), minimum_notional: fixed_point("10") }),
+    );
+    // A new price is still checked against the tick and band
+    assert_eq!(
+        multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.10"), fixed_point("1.0")),
+        Err(
+            OrderBookError::PriceOutsideBand {
+                order_id: OrderId(101),
+                price: fixed_point("95.10"),
+                lower_bound: fixed_point("99.0495"),
+                upper_bound: fixed_point("101.0505"),
+            }
+        ),
+    );
+    multi_ticker_limit_order_book.replace_order(OrderId(101), fixed_point("95.05"), fixed_point("2.0")).unwrap();
+    multi_ticker_limit_order_book.set_trading_rules(ticker_1(), trading_rules.clone());
+
+    // Venue data is stored as sent unless the rules snap it
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::BUY, fixed_point("94.99"), source_exchange_2(), fixed_point("0.25")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::BUY).unwrap();
+    assert_eq!(total_volume[&fixed_point("94.99")], fixed_point("0.25"));
+
+    trading_rules.snap_venue_data = true;
+    multi_ticker_limit_order_book.set_trading_rules(ticker_1(), trading_rules);
+    // Bids snap down and asks snap up, and volumes snap down
+    let bids = [(fixed_point("94.99"), fixed_point("0.25")), (fixed_point("94.90"), fixed_point("0.05"))];
+    let asks = [(fixed_point("105.01"), fixed_point("1.19"))];
+    multi_ticker_limit_order_book.replace_exchange_snapshot(ticker_1(), source_exchange_2(), &bids, &asks).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::BUY).unwrap();
+    assert_eq!(total_volume[&fixed_point("94.95")], BTreeMap::from([(source_exchange_2(), fixed_point("0.2"))]));
+    assert!(!total_volume.contains_key(&fixed_point("94.90")));
+    assert!(!total_volume.contains_key(&fixed_point("94.99")));
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(total_volume[&fixed_point("105.05")][&source_exchange_2()], fixed_point("1.1"));
+
+    // Updates are keyed by the price the venue sent, not the snapped price
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("105.05"), source_exchange_2(), fixed_point("0")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(total_volume[&fixed_point("105.05")][&source_exchange_2()], fixed_point("1.1"));
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("105.01"), source_exchange_2(), fixed_point("0")).unwrap();
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("106.01"), source_exchange_2(), fixed_point("0.35")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(total_volume[&fixed_point("106.05")], BTreeMap::from([(source_exchange_2(), fixed_point("0.3"))]));
+    assert!(!total_volume[&fixed_point("105.05")].contains_key(&source_exchange_2()));
+
+    // Two venue levels which snap to the same price are summed, and deleting
+    // one of them leaves the volume of the other
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("106.03"), source_exchange_2(), fixed_point("0.2")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(total_volume[&fixed_point("106.05")], BTreeMap::from([(source_exchange_2(), fixed_point("0.5"))]));
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("106.01"), source_exchange_2(), fixed_point("0")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(total_volume[&fixed_point("106.05")], BTreeMap::from([(source_exchange_2(), fixed_point("0.2"))]));
+
+    // The same after a snapshot, on the bid side
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::BUY, fixed_point("94.97"), source_exchange_2(), fixed_point("0.3")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::BUY).unwrap();
+    assert_eq!(total_volume[&fixed_point("94.95")], BTreeMap::from([(source_exchange_2(), fixed_point("0.5"))]));
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::BUY, fixed_point("94.99"), source_exchange_2(), fixed_point("0")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::BUY).unwrap();
+    assert_eq!(total_volume[&fixed_point("94.95")], BTreeMap::from([(source_exchange_2(), fixed_point("0.3"))]));
+
+    // Venue data which overflows when it is snapped is rejected, and the book is unchanged
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(
+        multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, FixedPoint::MAX, source_exchange_2(), fixed_point("0.1")),
+        Err(OrderBookError::PriceLevelOverflow { price: FixedPoint::MAX, volume: fixed_point("0.1") }),
+    );
+    let asks = [(fixed_point("105.01"), fixed_point("1.19")), (FixedPoint::MAX, fixed_point("0.1"))];
+    assert!(multi_ticker_limit_order_book.replace_exchange_snapshot(ticker_1(), source_exchange_2(), &[], &asks).is_err());
+    assert_eq!(multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::SELL).unwrap(), total_volume);
+
+    // Venue data is stored with the price and volume scales of the instrument,
+    // and data with more significant decimal places is rejected
+    let mut trading_rules = TradingRules::new();
+    trading_rules.price_scale = Some(2);
+    trading_rules.volume_scale = Some(4);
+    multi_ticker_limit_order_book.set_trading_rules(ticker_2(), trading_rules);
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_2(), OrderSide::BUY, fixed_point("64000.1"), source_exchange_1(), fixed_point("0.50000000")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_2(), &OrderSide::BUY).unwrap();
+    let (price, volume) = total_volume.get_key_value(&fixed_point("64000.1")).unwrap();
+    assert_eq!((price.to_string(), volume.to_string()), (String::from("64000.10"), String::from("0.5000")));
+    assert_eq!(
+        multi_ticker_limit_order_book.set_price_level_volume(ticker_2(), OrderSide::BUY, fixed_point("64000.001"), source_exchange_1(), fixed_point("0.5")),
+        Err(OrderBookError::PriceLevelScale { price: fixed_point("64000.001"), volume: fixed_point("0.5") }),
+    );
+    let bids = [(fixed_point("64000.05"), fixed_point("0.00001"))];
+    assert!(multi_ticker_limit_order_book.replace_exchange_snapshot(ticker_2(), source_exchange_1(), &bids, &[]).is_err());
+    assert_eq!(multi_ticker_limit_order_book.total_volume_by_price_level(ticker_2(), &OrderSide::BUY).unwrap(), total_volume);
+}
+
+
+#[test]
+fn set_price_level_volume_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+
+    add_some_orders(&mut multi_ticker_limit_order_book);
+
+    // ticker_1() SELL 120.0 has 30.0 from source_exchange_1() and 21.0 from source_exchange_2()
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("120.0"), source_exchange_1(), fixed_point("0.5")).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    assert_eq!(
+        total_volume[&fixed_point("120.0")],
+        BTreeMap::from([(source_exchange_1(), fixed_point("0.5")), (source_exchange_2(), fixed_point("21.0"))]),
+    );
+
+    // The replaced orders are gone
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(106)).is_err());
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(107)).is_err());
+
+    // A new price level
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("119.5"), source_exchange_2(), fixed_point("2.0")).unwrap();
+    assert_eq!(multi_ticker_limit_order_book.spread(ticker_1()).unwrap(), Some(fixed_point("19.5")));
+
+    // Zero volume deletes, and removes the price level once it is empty
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("119.5"), source_exchange_2(), FixedPoint::ZERO).unwrap();
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("120.0"), source_exchange_2(), FixedPoint::ZERO).unwrap();
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level(ticker_1(), &OrderSide::SELL).unwrap();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (fixed_point("120.0"), fixed_point("0.5")),
+            (fixed_point("122.0"), fixed_point("10.0")),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+
+    // Deleting a price level which does not exist does nothing
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::BUY, fixed_point("50.0"), source_exchange_1(), FixedPoint::ZERO).unwrap();
+
+    assert_eq!(
+        multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::BUY, fixed_point("50.0"), source_exchange_1(), fixed_point("-1.0")),
+        Err(OrderBookError::NegativePriceLevelVolume { price: fixed_point("50.0"), volume: fixed_point("-1.0") }),
+    );
+}
+
+
+#[test]
+fn replace_exchange_snapshot_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+
+    add_some_orders(&mut multi_ticker_limit_order_book);
+
+    let bids = vec![
+        (fixed_point("99.0"), fixed_point("1.0")),
+        (fixed_point("97.0"), fixed_point("2.0")),
+    ];
+    let asks = vec![
+        (fixed_point("120.0"), fixed_point("3.0")),
+        (fixed_point("121.0"), FixedPoint::ZERO),
+    ];
+    multi_ticker_limit_order_book.replace_exchange_snapshot(ticker_1(), source_exchange_1(), &bids, &asks).unwrap();
+
+    // The 98.0 and 122.0 price levels only had orders from source_exchange_1() and are removed
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::BUY).unwrap();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (fixed_point("97.0"), BTreeMap::from([(source_exchange_1(), fixed_point("2.0"))])),
+            (fixed_point("99.0"), BTreeMap::from([(source_exchange_1(), fixed_point("1.0")), (source_exchange_2(), fixed_point("10.0"))])),
+            (fixed_point("100.0"), BTreeMap::from([(source_exchange_2(), fixed_point("17.0"))])),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+
+    let total_volume = multi_ticker_limit_order_book.total_volume_by_price_level_and_source_exchange(ticker_1(), &OrderSide::SELL).unwrap();
+    let expected_total_volume = BTreeMap::from(
+        [
+            (fixed_point("120.0"), BTreeMap::from([(source_exchange_1(), fixed_point("3.0")), (source_exchange_2(), fixed_point("21.0"))])),
+        ]
+    );
+    assert_eq!(total_volume, expected_total_volume);
+
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(101)).is_err());
+    assert!(multi_ticker_limit_order_book.cancel_order(OrderId(109)).is_ok());
+
+    // ticker_2() is untouched
+    assert_eq!(multi_ticker_limit_order_book.spread(ticker_2()).unwrap(), Some(fixed_point("200.0")));
+
+    // A snapshot with a negative volume leaves the book unchanged
+    let bids = vec![(fixed_point("99.5"), fixed_point("-1.0"))];
+    assert_eq!(
+        multi_ticker_limit_order_book.replace_exchange_snapshot(ticker_1(), source_exchange_1(), &bids, &[]),
+        Err(OrderBookError::NegativePriceLevelVolume { price: fixed_point("99.5"), volume: fixed_point("-1.0") }),
+    );
+    assert_eq!(multi_ticker_limit_order_book.spread(ticker_1()).unwrap(), Some(fixed_point("20.0")));
+}
+
+
+#[test]
+fn depth_queries_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+
+    add_some_orders(&mut multi_ticker_limit_order_book);
+
+    // ticker_1() BUY: 100.0 has 35.0 from source_exchange_1() and 17.0 from source_exchange_2(),
+    // 99.0 has 10.0 from source_exchange_2(), 98.0 has 25.0 from source_exchange_1()
+    // ticker_1() SELL: 120.0 has 30.0 from source_exchange_1() and 21.0 from source_exchange_2(),
+    // 122.0 has 10.0 from source_exchange_1()
+    assert_eq!(multi_ticker_limit_order_book.cumulative_volume_to_price(ticker_1(), &OrderSide::SELL, fixed_point("119.0")).unwrap(), FixedPoint::ZERO);
+    assert_eq!(multi_ticker_limit_order_book.cumulative_volume_to_price(ticker_1(), &OrderSide::SELL, fixed_point("121.0")).unwrap(), fixed_point("51.0"));
+    assert_eq!(multi_ticker_limit_order_book.cumulative_volume_to_price(ticker_1(), &OrderSide::SELL, fixed_point("122.0")).unwrap(), fixed_point("61.0"));
+    assert_eq!(multi_ticker_limit_order_book.cumulative_volume_to_price(ticker_1(), &OrderSide::BUY, fixed_point("99.0")).unwrap(), fixed_point("62.0"));
+    assert_eq!(
+        multi_ticker_limit_order_book.cumulative_volume_to_price_by_source_exchange(ticker_1(), &OrderSide::BUY, fixed_point("99.0")).unwrap(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("35.0")), (source_exchange_2(), fixed_point("27.0"))]),
+    );
+
+    assert_eq!(multi_ticker_limit_order_book.price_at_cumulative_volume(ticker_1(), &OrderSide::SELL, fixed_point("51.0")).unwrap(), Some(fixed_point("120.0")));
+    assert_eq!(multi_ticker_limit_order_book.price_at_cumulative_volume(ticker_1(), &OrderSide::SELL, fixed_point("52.0")).unwrap(), Some(fixed_point("122.0")));
+    assert_eq!(multi_ticker_limit_order_book.price_at_cumulative_volume(ticker_1(), &OrderSide::SELL, fixed_point("62.0")).unwrap(), None);
+    assert_eq!(multi_ticker_limit_order_book.price_at_cumulative_volume(ticker_1(), &OrderSide::BUY, fixed_point("60.0")).unwrap(), Some(fixed_point("99.0")));
+    assert_eq!(multi_ticker_limit_order_book.price_at_cumulative_volume(ticker_1(), &OrderSide::BUY, fixed_point("63.0")).unwrap(), Some(fixed_point("98.0")));
+    assert_eq!(
+        multi_ticker_limit_order_book.price_at_cumulative_volume_by_source_exchange(ticker_1(), &OrderSide::BUY, fixed_point("20.0")).unwrap(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("100.0")), (source_exchange_2(), fixed_point("99.0"))]),
+    );
+    // source_exchange_2() has only 27.0 on the BUY side
+    assert_eq!(
+        multi_ticker_limit_order_book.price_at_cumulative_volume_by_source_exchange(ticker_1(), &OrderSide::BUY, fixed_point("30.0")).unwrap(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("100.0"))]),
+    );
+
+    // The mid price is 110.0, so 1000 basis points is 99.0 to 121.0
+    assert_eq!(multi_ticker_limit_order_book.mid_price(ticker_1()).unwrap(), Some(fixed_point("110.0")));
+    assert_eq!(multi_ticker_limit_order_book.depth_within_basis_points(ticker_1(), &OrderSide::BUY, 1000).unwrap(), Some(fixed_point("62.0")));
+    assert_eq!(multi_ticker_limit_order_book.depth_within_basis_points(ticker_1(), &OrderSide::SELL, 1000).unwrap(), Some(fixed_point("51.0")));
+    assert_eq!(multi_ticker_limit_order_book.depth_within_basis_points(ticker_1(), &OrderSide::BUY, 2000).unwrap(), Some(fixed_point("87.0")));
+    assert_eq!(
+        multi_ticker_limit_order_book.depth_within_basis_points_by_source_exchange(ticker_1(), &OrderSide::SELL, 2000).unwrap(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("40.0")), (source_exchange_2(), fixed_point("21.0"))]),
+    );
+
+    // There is no mid price without both sides
+    let ticker_3 = Instrument::intern("EXAMPLE3", "USD");
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_3, OrderSide::BUY, fixed_point("100.0"), source_exchange_1(), fixed_point("1.0")).unwrap();
+    assert_eq!(multi_ticker_limit_order_book.depth_within_basis_points(ticker_3, &OrderSide::BUY, 1000), Ok(None));
+    assert!(multi_ticker_limit_order_book.depth_within_basis_points_by_source_exchange(ticker_3, &OrderSide::BUY, 1000).unwrap().is_empty());
+
+    // Venue data which overflows the mid price, the spread or the volume of a
+    // price level is an error rather than a panic
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_3, OrderSide::SELL, FixedPoint::MAX, source_exchange_1(), fixed_point("1.0")).unwrap();
+    assert_eq!(multi_ticker_limit_order_book.mid_price(ticker_3), Err(OrderBookError::PriceOverflow { ticker: ticker_3 }));
+    assert_eq!(multi_ticker_limit_order_book.depth_within_basis_points(ticker_3, &OrderSide::BUY, 1000), Err(OrderBookError::PriceOverflow { ticker: ticker_3 }));
+    assert_eq!(multi_ticker_limit_order_book.spread(ticker_3), Err(OrderBookError::PriceOverflow { ticker: ticker_3 }));
+    assert_eq!(multi_ticker_limit_order_book.spread_by_exchange(ticker_3), Err(OrderBookError::PriceOverflow { ticker: ticker_3 }));
+    assert_eq!(multi_ticker_limit_order_book.quote(ticker_3).unwrap().spread(), Err(OrderBookError::PriceOverflow { ticker: ticker_3 }));
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_3, OrderSide::BUY, fixed_point("-1"), source_exchange_1(), FixedPoint::MAX).unwrap();
+    assert_eq!(
+        multi_ticker_limit_order_book.set_price_level_volume(ticker_3, OrderSide::BUY, fixed_point("-1"), source_exchange_2(), FixedPoint::MAX),
+        Err(OrderBookError::PriceLevelOverflow { price: fixed_point("-1"), volume: FixedPoint::MAX }),
+    );
+}
+
+
+#[test]
+fn quote_test() {
+
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+
+    add_some_orders(&mut multi_ticker_limit_order_book);
+
+    assert_eq!(multi_ticker_limit_order_book.best_bid_price(ticker_1()).unwrap(), Some(fixed_point("100.0")));
+    assert_eq!(multi_ticker_limit_order_book.best_ask_price(ticker_1()).unwrap(), Some(fixed_point("120.0")));
+
+    let quote = multi_ticker_limit_order_book.quote(ticker_1()).unwrap();
+    let expected_quote = Quote {
+        ticker: ticker_1(),
+        best_bid: Some(
+            QuoteLevel {
+                price: fixed_point("100.0"),
+                volume: fixed_point("52.0"),
+                volume_by_source_exchange: BTreeMap::from([(source_exchange_1(), fixed_point("35.0")), (source_exchange_2(), fixed_point("17.0"))]),
+            }
+        ),
+        best_ask: Some(
+            QuoteLevel {
+                price: fixed_point("120.0"),
+                volume: fixed_point("51.0"),
+                volume_by_source_exchange: BTreeMap::from([(source_exchange_1(), fixed_point("30.0")), (source_exchange_2(), fixed_point("21.0"))]),
+            }
+        ),
+        market_state: MarketState::Normal,
+    };
+    assert_eq!(quote, expected_quote);
+    assert_eq!(quote.spread(), Ok(Some(fixed_point("20.0"))));
+    assert_eq!(quote.best_bid.unwrap().source_exchanges(), vec![source_exchange_1(), source_exchange_2()]);
+
+    // An ask from one exchange at the best bid of another locks the market
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("100.0"), source_exchange_2(), fixed_point("1.0")).unwrap();
+    let quote = multi_ticker_limit_order_book.quote(ticker_1()).unwrap();
+    assert_eq!(quote.market_state, MarketState::Locked);
+    assert!(quote.is_locked());
+    assert_eq!(quote.best_ask.unwrap().source_exchanges(), vec![source_exchange_2()]);
+
+    // And an ask below it crosses the market
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_1(), OrderSide::SELL, fixed_point("99.5"), source_exchange_2(), fixed_point("1.0")).unwrap();
+    let quote = multi_ticker_limit_order_book.quote(ticker_1()).unwrap();
+    assert!(quote.is_crossed());
+    assert_eq!(quote.spread(), Ok(Some(fixed_point("-0.5"))));
+
+    let ticker_3 = Instrument::intern("EXAMPLE3", "USD");
+    multi_ticker_limit_order_book.set_price_level_volume(ticker_3, OrderSide::SELL, fixed_point("100.0"), source_exchange_1(), fixed_point("1.0")).unwrap();
+    let quote = multi_ticker_limit_order_book.quote(ticker_3).unwrap();
+    assert_eq!(quote.market_state, MarketState::OneSided);
+    assert_eq!(quote.best_bid, None);
+    assert_eq!(quote.spread(), Ok(None));
+}
+
+
+#[test]
+fn running_aggregates_test() {
+
+    let price = fixed_point("100.0");
+    let mut price_level = PriceLevel::new(price);
+
+    price_level.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::BUY, price, fixed_point("20.0"), source_exchange_1())).unwrap();
+    price_level.add_order(Order::new(OrderId(2), ticker_1(), OrderSide::BUY, price, fixed_point("5.0"), source_exchange_2())).unwrap();
+    assert_eq!(price_level.order_count(), 2);
+    assert_eq!(price_level.total_volume(), fixed_point("25.0"));
+
+    price_level.reduce_order(OrderId(1), fixed_point("5.0")).unwrap();
+    price_level.replace_order(OrderId(2), fixed_point("8.0")).unwrap();
+    assert_eq!(price_level.total_volume(), fixed_point("23.0"));
+    assert_eq!(
+        price_level.total_volume_by_source_exchange(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("15.0")), (source_exchange_2(), fixed_point("8.0"))])
+    );
+
+    // An exchange whose volume is matched away no longer has an entry
+    let mut incoming_order = Order::new(OrderId(3), ticker_1(), OrderSide::SELL, price, fixed_point("15.0"), source_exchange_2());
+    price_level.match_order(&mut incoming_order, &mut Vec::new()).unwrap();
+    assert_eq!(price_level.order_count(), 1);
+    assert!(!price_level.contains_source_exchange(source_exchange_1()));
+    assert_eq!(price_level.total_volume_with_price_level(), (price, fixed_point("8.0")));
+
+    let mut single_side_limit_order_book = SingleSideLimitOrderBook::new(OrderSide::SELL);
+    single_side_limit_order_book.add_order(Order::new(OrderId(1), ticker_1(), OrderSide::SELL, fixed_point("101.0"), fixed_point("1.0"), source_exchange_1())).unwrap();
+    single_side_limit_order_book.add_order(Order::new(OrderId(2), ticker_1(), OrderSide::SELL, fixed_point("102.0"), fixed_point("1.0"), source_exchange_2())).unwrap();
+    single_side_limit_order_book.add_order(Order::new(OrderId(3), ticker_1(), OrderSide::SELL, fixed_point("103.0"), fixed_point("1.0"), source_exchange_1())).unwrap();
+    assert_eq!(single_side_limit_order_book.lowest_price(), Some(fixed_point("101.0")));
+    assert_eq!(single_side_limit_order_book.highest_price(), Some(fixed_point("103.0")));
+    assert_eq!(
+        single_side_limit_order_book.lowest_price_by_exchange(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("101.0")), (source_exchange_2(), fixed_point("102.0"))])
+    );
+    assert_eq!(
+        single_side_limit_order_book.highest_price_by_exchange(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("103.0")), (source_exchange_2(), fixed_point("102.0"))])
+    );
+
+    // Matching away the best price level moves the best price
+    let mut incoming_order = Order::new(OrderId(4), ticker_1(), OrderSide::BUY, fixed_point("101.0"), fixed_point("1.0"), source_exchange_2());
+    single_side_limit_order_book.match_order(&mut incoming_order).unwrap();
+    assert_eq!(single_side_limit_order_book.lowest_price(), Some(fixed_point("102.0")));
+    assert!(single_side_limit_order_book.crosses(&Order::new(OrderId(5), ticker_1(), OrderSide::BUY, fixed_point("102.0"), fixed_point("1.0"), source_exchange_1())));
+    assert_eq!(
+        single_side_limit_order_book.lowest_price_by_exchange(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("103.0")), (source_exchange_2(), fixed_point("102.0"))])
+    );
+
+    single_side_limit_order_book.reduce_order(OrderId(2), fixed_point("1.0")).unwrap();
+    single_side_limit_order_book.replace_order(OrderId(3), fixed_point("104.0"), fixed_point("2.0")).unwrap();
+    assert_eq!(
+        single_side_limit_order_book.lowest_price_by_exchange(),
+        BTreeMap::from([(source_exchange_1(), fixed_point("104.0"))])
+    );
+
+    let cancelled_orders = single_side_limit_order_book.cancel_orders_by_source_exchange(source_exchange_1()).unwrap();
+    assert_eq!(cancelled_orders.len(), 1);
+    assert_eq!(single_side_limit_order_book.lowest_price(), None);
+    assert!(single_side_limit_order_book.lowest_price_by_exchange().is_empty());
+}
+
+#[test]
+fn fixed_point_parse_and_display_test() {
+
+    let value = fixed_point("64000.01");
+    assert_eq!(value.mantissa(), 6400001);
+    assert_eq!(value.scale(), 2);
+    assert_eq!(value.to_string(), "64000.01");
+
+    assert_eq!(fixed_point("-0.05").to_string(), "-0.05");
+    assert_eq!(fixed_point("+12").to_string(), "12");
+    assert_eq!(fixed_point(".5").to_string(), "0.5");
+    assert_eq!(fixed_point("0.00012340").to_string(), "0.00012340");
+
+    assert!(FixedPoint::from_str("").is_err());
+    assert!(FixedPoint::from_str(".").is_err());
+    assert!(FixedPoint::from_str("1.2.3").is_err());
+    assert!(FixedPoint::from_str("1e5").is_err());
+    assert!(FixedPoint::from_str("NaN").is_err());
+    assert!(FixedPoint::from_str("0.0000000000000000001").is_err());
+
+    // Per instrument scale
+    let value = fixed_point("64000.1").with_scale(2).unwrap();
+    assert_eq!(value.scale(), 2);
+    assert_eq!(value.to_string(), "64000.10");
+    assert_eq!(fixed_point("64000.10000").with_scale(2).unwrap().to_string(), "64000.10");
+    assert_eq!(fixed_point("64000.001").with_scale(2), None);
+    assert_eq!(fixed_point("0.0100").significant_scale(), 2);
+    assert_eq!(fixed_point("25.000").significant_scale(), 0);
+    assert_eq!(FixedPoint::ZERO.significant_scale(), 0);
+
+    // Exponent notation, which some exchanges send for increments
+    assert_eq!(FixedPoint::from_str_with_exponent("1E-8").unwrap().to_string(), "0.00000001");
+    assert_eq!(FixedPoint::from_str_with_exponent("2.5e-3").unwrap().to_string(), "0.0025");
+    assert_eq!(FixedPoint::from_str_with_exponent("1.5E2").unwrap().to_string(), "150");
+    assert_eq!(FixedPoint::from_str_with_exponent("0.01").unwrap().to_string(), "0.01");
+    assert!(FixedPoint::from_str_with_exponent("1E-19").is_err());
+    assert!(FixedPoint::from_str_with_exponent("1E").is_err());
+}
+
+
+#[test]
+fn fixed_point_ordering_and_arithmetic_test() {
+
+    // Values with different scales are equal when they represent the same number
+    assert_eq!(fixed_point("100.5"), fixed_point("100.50000"));
+    assert!(fixed_point("100.49") < fixed_point("100.5"));
+    assert!(fixed_point("-1") < fixed_point("0.001"));
+    assert!(FixedPoint::MIN < fixed_point("-1000000.000001"));
+    assert!(FixedPoint::MAX > fixed_point("1000000.000001"));
+
+    let mut price_levels = BTreeMap::new();
+    *price_levels.entry(fixed_point("100.5")).or_insert(FixedPoint::ZERO) += fixed_point("1");
+    *price_levels.entry(fixed_point("100.50")).or_insert(FixedPoint::ZERO) += fixed_point("2");
+    assert_eq!(price_levels, BTreeMap::from([(fixed_point("100.5"), fixed_point("3"))]));
+
+    // Arithmetic is exact
+    assert_eq!(fixed_point("0.1") + fixed_point("0.2"), fixed_point("0.3"));
+    assert_eq!(fixed_point("0.3") - fixed_point("0.1"), fixed_point("0.2"));
+    assert_eq!((fixed_point("64000.01") * fixed_point("0.12345678")).to_string(), "7901.2351545678");
+    assert_eq!(-fixed_point("1.5"), fixed_point("-1.5"));
+    assert_eq!(
+        vec![fixed_point("0.1"); 10].into_iter().sum::<FixedPoint>(),
+        FixedPoint::from(1),
+    );
+
+    // Division is truncated towards zero at the requested scale
+    assert_eq!(fixed_point("10").checked_div_with_scale(fixed_point("4"), 2).unwrap().to_string(), "2.50");
+    assert_eq!(fixed_point("1").checked_div_with_scale(fixed_point("3"), 4).unwrap().to_string(), "0.3333");
+    assert_eq!(fixed_point("-2").checked_div_with_scale(fixed_point("3"), 2).unwrap().to_string(), "-0.66");
+    assert_eq!(fixed_point("7901.2351545678").checked_div_with_scale(fixed_point("0.12345678"), 2), Some(fixed_point("64000.01")));
+    assert_eq!(fixed_point("1").checked_div_with_scale(FixedPoint::ZERO, 2), None);
+
+    // Multiples of an increment, for example a tick size
+    assert!(fixed_point("64000.25").is_multiple_of(fixed_point("0.05")));
+    assert!(fixed_point("-0.3").is_multiple_of(fixed_point("0.1")));
+    assert!(fixed_point("100").is_multiple_of(fixed_point("0.00000001")));
+    assert!(!fixed_point("64000.26").is_multiple_of(fixed_point("0.05")));
+    assert!(FixedPoint::ZERO.is_multiple_of(FixedPoint::ZERO));
+    assert!(!fixed_point("1").is_multiple_of(FixedPoint::ZERO));
+
+    // Snapping to the nearest multiple in either direction
+    assert_eq!(fixed_point("64000.037").floor_to_multiple_of(fixed_point("0.01")), fixed_point("64000.03"));
+    assert_eq!(fixed_point("64000.031").ceil_to_multiple_of(fixed_point("0.01")), fixed_point("64000.04"));
+    assert_eq!(fixed_point("-0.15").floor_to_multiple_of(fixed_point("0.1")), fixed_point("-0.2"));
+    assert_eq!(fixed_point("-0.15").ceil_to_multiple_of(fixed_point("0.1")), fixed_point("-0.1"));
+    assert_eq!(fixed_point("0.3").floor_to_multiple_of(fixed_point("0.1")), fixed_point("0.3"));
+    assert_eq!(fixed_point("1.23").floor_to_multiple_of(FixedPoint::ZERO), fixed_point("1.23"));
+
+    // The least common multiple of two increments is on both grids
+    assert_eq!(fixed_point("0.01").checked_least_common_multiple(fixed_point("0.025")), Some(fixed_point("0.05")));
+    assert_eq!(fixed_point("0.1").checked_least_common_multiple(fixed_point("0.00000001")), Some(fixed_point("0.1")));
+    assert_eq!(fixed_point("0.04").checked_least_common_multiple(fixed_point("-0.06")), Some(fixed_point("0.12")));
+    assert_eq!(FixedPoint::ZERO.checked_least_common_multiple(fixed_point("0.5")), Some(fixed_point("0.5")));
+    assert_eq!(FixedPoint::MAX.checked_least_common_multiple(fixed_point("0.3")), None);
+
+    // Checked arithmetic returns None instead of panicking on overflow
+    assert_eq!(fixed_point("0.1").checked_add(fixed_point("0.2")), Some(fixed_point("0.3")));
+    assert_eq!(FixedPoint::MAX.checked_add(fixed_point("1")), None);
+    assert_eq!(FixedPoint::MIN.checked_sub(fixed_point("1")), None);
+    assert_eq!(FixedPoint::MAX.checked_mul(fixed_point("2")), None);
+    assert_eq!(FixedPoint::MIN.checked_neg(), None);
+    assert_eq!(FixedPoint::MAX.checked_is_multiple_of(fixed_point("0.01")), None);
+    assert_eq!(FixedPoint::MAX.checked_floor_to_multiple_of(fixed_point("0.01")), None);
+    assert_eq!(fixed_point("64000.031").checked_ceil_to_multiple_of(fixed_point("0.01")), Some(fixed_point("64000.04")));
+}
+
+
+#[test]
+fn symbol_and_exchange_id_interning_test() {
+
+    // Names which are only known at runtime can be interned
+    let name = format!("{}{}", "RUNTIME", "TICKER");
+    let symbol = Symbol::intern(&name);
+    drop(name);
+
+    assert_eq!(symbol, Symbol::intern("RUNTIMETICKER"));
+    assert_eq!(Symbol::lookup("RUNTIMETICKER"), Some(symbol));
+    assert_eq!(symbol.name(), "RUNTIMETICKER");
+    assert_eq!(symbol.to_string(), "RUNTIMETICKER");
+    assert!(Symbol::all().contains(&symbol));
+    assert_eq!(Symbol::lookup("NOT A REGISTERED TICKER"), None);
+
+    // Symbols and exchange ids have separate registries
+    let exchange_id = ExchangeId::intern("RUNTIMEEXCHANGE");
+    assert_eq!(ExchangeId::lookup("RUNTIMEEXCHANGE"), Some(exchange_id));
+    assert_eq!(ExchangeId::lookup("RUNTIMETICKER"), None);
+    assert_eq!(format!("{exchange_id:?}"), "\"RUNTIMEEXCHANGE\"");
+}
+
+
+#[test]
+fn limit_order_books_are_static_and_send_test() {
+
+    fn assert_static_and_send<T: Send + 'static>() {
+
+    }
+
+    assert_static_and_send::<Order>();
+    assert_static_and_send::<Fill>();
+    assert_static_and_send::<PriceLevel>();
+    assert_static_and_send::<SingleSideLimitOrderBook>();
+    assert_static_and_send::<DoubleSideLimitOrderBook>();
+    assert_static_and_send::<MultiTickerLimitOrderBook>();
+
+    // A book can be built on one thread and used on another
+    let mut multi_ticker_limit_order_book = MultiTickerLimitOrderBook::new();
+    add_some_orders(&mut multi_ticker_limit_order_book);
+    let spread = std::thread::spawn(
+        move || {
+            multi_ticker_limit_order_book.spread(ticker_1())
+        }
+    ).join().unwrap();
+    assert_eq!(spread, Ok(Some(fixed_point("20.0"))));
+}
+
+
 // NOTE: Simplified version of the same test above, useful for debugging
 // #[test]
 // fn multi_ticker_limit_order_book_total_volume_by_source_exchange_simple_test() {
@@ -547,36 +1663,36 @@ fn multi_ticker_limit_order_book_total_volume_by_source_exchange_test() {
 //     let source_exchange_1 = "SRCEX1";
 //     let source_exchange_2 = "SRCEX2";
 
-//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , 100.0, 20.0, source_exchange_1).unwrap());
-//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , 100.0, 10.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , 100.0,  5.0, source_exchange_1).unwrap());
-//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  98.0, 20.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  98.0,  5.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, 120.0, 20.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, 120.0, 10.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, 122.0, 10.0, source_exchange_1).unwrap());
+//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , fixed_point("100.0"), fixed_point("20.0"), source_exchange_1));
+//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , fixed_point("100.0"), fixed_point("10.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , fixed_point("100.0"),  fixed_point("5.0"), source_exchange_1));
+//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  fixed_point("98.0"), fixed_point("20.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  fixed_point("98.0"),  fixed_point("5.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, fixed_point("120.0"), fixed_point("20.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, fixed_point("120.0"), fixed_point("10.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, fixed_point("122.0"), fixed_point("10.0"), source_exchange_1));
     
-//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , 100.0, 10.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , 100.0,  5.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , 100.0,  2.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  99.0,  5.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  99.0,  5.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, 120.0, 10.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, 120.0, 10.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, 120.0,  1.0, source_exchange_2).unwrap());
-
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , 1000.0, 20.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , 1000.0,  5.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY ,  980.0,  5.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, 1200.0, 20.0, source_exchange_1).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, 1220.0, 10.0, source_exchange_1).unwrap());
+//     multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , fixed_point("100.0"), fixed_point("10.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , fixed_point("100.0"),  fixed_point("5.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY , fixed_point("100.0"),  fixed_point("2.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  fixed_point("99.0"),  fixed_point("5.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::BUY ,  fixed_point("99.0"),  fixed_point("5.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, fixed_point("120.0"), fixed_point("10.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, fixed_point("120.0"), fixed_point("10.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_1, OrderSide::SELL, fixed_point("120.0"),  fixed_point("1.0"), source_exchange_2));
+
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , fixed_point("1000.0"), fixed_point("20.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , fixed_point("1000.0"),  fixed_point("5.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY ,  fixed_point("980.0"),  fixed_point("5.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, fixed_point("1200.0"), fixed_point("20.0"), source_exchange_1));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, fixed_point("1220.0"), fixed_point("10.0"), source_exchange_1));
     
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , 1000.0, 10.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , 1000.0,  5.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY ,  990.0,  5.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY ,  990.0,  5.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, 1200.0, 10.0, source_exchange_2).unwrap());
-//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, 1200.0, 10.0, source_exchange_2).unwrap());
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , fixed_point("1000.0"), fixed_point("10.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY , fixed_point("1000.0"),  fixed_point("5.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY ,  fixed_point("990.0"),  fixed_point("5.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::BUY ,  fixed_point("990.0"),  fixed_point("5.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, fixed_point("1200.0"), fixed_point("10.0"), source_exchange_2));
+//     // multi_ticker_limit_order_book.add_order(Order::new(ticker_2, OrderSide::SELL, fixed_point("1200.0"), fixed_point("10.0"), source_exchange_2));
 
 //     multi_ticker_limit_order_book.debug_print();
 
@@ -585,8 +1701,8 @@ fn multi_ticker_limit_order_book_total_volume_by_source_exchange_test() {
 
 //     let expected_total_volume_by_source_exchange = BTreeMap::from(
 //         [
-//             (source_exchange_1, NotNan::new(50.0).unwrap()),
-//             (source_exchange_2, NotNan::new(10.0).unwrap()),
+//             (source_exchange_1, fixed_point("50.0")),
+//             (source_exchange_2, fixed_point("10.0")),
 //         ]
 //     );
 
@@ -0,0 +1,212 @@
+use super::FixedPoint;
+use super::Notional;
+use super::OrderBookError;
+use super::OrderId;
+use super::OrderSide;
+use super::OrderType;
+use super::Price;
+use super::Quantity;
+use super::fixed_point::MAX_SCALE;
+
+
+/// The rules an instrument's orders must follow, enforced by
+/// `MultiTickerLimitOrderBook::add_order` and `replace_order`.
+///
+/// A rule which is `None` is not checked. Market orders have no price, so only
+/// their volume is checked.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TradingRules {
+    /// Prices must be a multiple of the tick size.
+    pub tick_size: Option<Price>,
+    /// Volumes must be a multiple of the lot size.
+    pub lot_size: Option<Quantity>,
+    /// The smallest price times volume of an order.
+    pub minimum_notional: Option<Notional>,
+    /// Prices must be within this many basis points of the mid price. Not
+    /// checked while either side of the book is empty.
+    pub price_band_basis_points: Option<u32>,
+    /// Snap the prices and volumes of venue data to the tick and lot sizes,
+    /// instead of storing them as sent. Bids are rounded down and asks up to
+    /// the tick size, and volumes are rounded down to the lot size, so the
+    /// book never shows a better price or more volume than the venue.
+    pub snap_venue_data: bool,
+    /// The number of decimal places of the prices of the instrument. Venue
+    /// prices are stored with this scale, and a venue price with more
+    /// significant decimal places is rejected.
+    pub price_scale: Option<u32>,
+    /// The number of decimal places of the volumes of the instrument, used in
+    /// the same way as `price_scale`.
+    pub volume_scale: Option<u32>,
+}
+
+impl TradingRules {
+    pub fn new() -> TradingRules {
+        TradingRules::default()
+    }
+
+    /// The smallest volume which can be traded: the lot size, or one unit of
+    /// the volume scale if there is no lot size, or one unit of `MAX_SCALE` if
+    /// there is neither. Volumes calculated by division, such as the volume a
+    /// budget buys, are rounded to a multiple of it.
+    pub fn volume_increment(&self) -> Quantity {
+        match (self.lot_size.filter(|lot_size| lot_size.is_positive()), self.volume_scale) {
+            (Some(lot_size), _) => {
+                lot_size
+            },
+            (None, Some(volume_scale)) => {
+                FixedPoint::new(1, volume_scale)
+            },
+            (None, None) => {
+                FixedPoint::new(1, MAX_SCALE)
+            },
+        }
+    }
+
+    /// Check an order of `volume` at `price` against the rules, where
+    /// `mid_price` is the mid price of the book before the order is added.
+    pub fn check_order(
+        &self,
+        order_id: OrderId,
+        order_type: OrderType,
+        price: Price,
+        volume: Quantity,
+        mid_price: Option<Price>,
+    ) -> Result<(), OrderBookError> {
+        let overflow = || OrderBookError::OrderOverflow { order_id };
+
+        self.check_lot_size(order_id, volume)?;
+        if order_type == OrderType::Market {
+            return Ok(());
+        }
+
+        if !price.is_positive() {
+            return Err(
+                OrderBookError::NonPositivePrice {
+                    order_id,
+                    price,
+                }
+            );
+        }
+        if let Some(tick_size) = self.tick_size {
+            if !price.checked_is_multiple_of(tick_size).ok_or_else(overflow)? {
+                return Err(
+                    OrderBookError::PriceNotOnTick {
+                        order_id,
+                        price,
+                        tick_size,
+                    }
+                );
+            }
+        }
+        self.check_minimum_notional(order_id, price, volume)?;
+        if let (Some(basis_points), Some(mid_price)) = (self.price_band_basis_points, mid_price) {
+            let basis_points = i128::from(basis_points);
+            let lower_bound = mid_price.checked_mul(FixedPoint::new(10000 - basis_points, 4)).ok_or_else(overflow)?;
+            let upper_bound = mid_price.checked_mul(FixedPoint::new(10000 + basis_points, 4)).ok_or_else(overflow)?;
+            if price < lower_bound || price > upper_bound {
+                return Err(
+                    OrderBookError::PriceOutsideBand {
+                        order_id,
+                        price,
+                        lower_bound,
+                        upper_bound,
+                    }
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a new `volume` for an order which stays at `price`. Only the lot
+    /// size and minimum notional apply, since the price was checked when the
+    /// order was added and the mid price may have moved since.
+    pub fn check_volume_change(&self, order_id: OrderId, price: Price, volume: Quantity) -> Result<(), OrderBookError> {
+        self.check_lot_size(order_id, volume)?;
+        self.check_minimum_notional(order_id, price, volume)
+    }
+
+    fn check_lot_size(&self, order_id: OrderId, volume: Quantity) -> Result<(), OrderBookError> {
+        if let Some(lot_size) = self.lot_size {
+            if !volume.checked_is_multiple_of(lot_size).ok_or(OrderBookError::OrderOverflow { order_id })? {
+                return Err(
+                    OrderBookError::VolumeNotOnLot {
+                        order_id,
+                        volume,
+                        lot_size,
+                    }
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn check_minimum_notional(&self, order_id: OrderId, price: Price, volume: Quantity) -> Result<(), OrderBookError> {
+        let notional = price.checked_mul(volume).ok_or(OrderBookError::OrderOverflow { order_id })?;
+        if let Some(minimum_notional) = self.minimum_notional.filter(|minimum_notional| notional < *minimum_notional) {
+            return Err(
+                OrderBookError::BelowMinimumNotional {
+                    order_id,
+                    notional,
+                    minimum_notional,
+                }
+            );
+        }
+        Ok(())
+    }
+
+    /// The price and volume of one level of venue data, snapped to the tick
+    /// and lot sizes if `snap_venue_data` is set, and converted to the price
+    /// and volume scales.
+    pub fn normalize_venue_level(&self, order_side: OrderSide, price: Price, volume: Quantity)
+        -> Result<(Price, Quantity), OrderBookError>
+    {
+        let (snapped_price, snapped_volume) = if self.snap_venue_data {
+            self.snapped_venue_level(order_side, price, volume)
+                .ok_or(OrderBookError::PriceLevelOverflow { price, volume })?
+        } else {
+            (price, volume)
+        };
+        let scaled_price = match self.price_scale {
+            Some(price_scale) => {
+                snapped_price.with_scale(price_scale)
+            },
+            None => {
+                Some(snapped_price)
+            },
+        };
+        let scaled_volume = match self.volume_scale {
+            Some(volume_scale) => {
+                snapped_volume.with_scale(volume_scale)
+            },
+            None => {
+                Some(snapped_volume)
+            },
+        };
+        scaled_price
+            .zip(scaled_volume)
+            .ok_or(OrderBookError::PriceLevelScale { price, volume })
+    }
+
+    fn snapped_venue_level(&self, order_side: OrderSide, price: Price, volume: Quantity) -> Option<(Price, Quantity)> {
+        let snapped_price = match (self.tick_size, order_side) {
+            (Some(tick_size), OrderSide::BUY) => {
+                price.checked_floor_to_multiple_of(tick_size)
+            },
+            (Some(tick_size), OrderSide::SELL) => {
+                price.checked_ceil_to_multiple_of(tick_size)
+            },
+            (None, _) => {
+                Some(price)
+            },
+        };
+        let snapped_volume = match self.lot_size {
+            Some(lot_size) => {
+                volume.checked_floor_to_multiple_of(lot_size)
+            },
+            None => {
+                Some(volume)
+            },
+        };
+        snapped_price.zip(snapped_volume)
+    }
+}
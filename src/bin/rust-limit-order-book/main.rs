@@ -16,136 +16,545 @@
 // volume
 // source exchange (expect a maximum of 1 per price level)
 
-// NOTE: Why floats (`f64`) for price?
+// NOTE: Why fixed point for price?
 //
-// While it is true that we cannot expect to perform math operations on floating point
-// numbers and obtain exact results, eg
+// Prices and volumes are parsed directly from the strings sent by each exchange
+// into a fixed point decimal type (`FixedPoint`), which stores an integer
+// mantissa and the number of decimal places (the scale). Parsing is exact, so
+// prices can safely be used as keys of a `BTreeMap`, and sums and products of
+// prices and volumes are exact, so cost and profit totals are exact to the
+// smallest unit.
 //
-//     1.0 + 2.0 != 3.0
-//
-// we *can* expect to consistently parse string representations of floats and obtain
-// the same values.
-//
-// Since this entire codebase only performs string to floating point conversion
-// operations when working with prices, and the floating point values are always of
-// the same precision (`f64`) this is ok.
-//
-// NotNan<f64> provides a convenient way of recovering total ordering behaviour of
-// floats.
-//
-// The alternative would be to use a library which implements fixed point numbers
-// or Decimal types.
+// Each exchange may quote the same instrument with a different number of decimal
+// places. Values with different scales which represent the same number compare
+// equal, so "100.5" and "100.50000" are the same price level.
 
 
+pub mod exchange_adapter_lib;
 pub mod coinbase_lib;
 pub mod gemini_lib;
 pub mod kraken_lib;
+pub mod config_lib;
+pub mod instrument_lib;
 pub mod limit_order_book_lib;
-pub mod cost_functions;
+pub mod execution_lib;
+pub mod recorder_lib;
+pub mod websocket_feed_lib;
 
-use coinbase_lib::get_api_coinbase;
-use coinbase_lib::dump_coinbase_response_text_to_file;
-use coinbase_lib::load_coinbase_book;
+#[cfg(test)]
+pub mod test_support_lib;
 
-use gemini_lib::get_api_gemini;
-use gemini_lib::dump_gemini_response_text_to_file;
-use gemini_lib::load_gemini_book;
+use config_lib::Config;
+use config_lib::InstrumentConfig;
 
-use kraken_lib::get_api_kraken;
-use kraken_lib::dump_kraken_response_text_to_file;
-use kraken_lib::load_kraken_book;
+use exchange_adapter_lib::ExchangeAdapter;
+use exchange_adapter_lib::FetchedResponse;
+use exchange_adapter_lib::capture_directories;
+use exchange_adapter_lib::load_capture;
 
-use cost_functions::cost_function_buy;
-use cost_functions::profit_function_sell;
-use cost_functions::cost_function_buy_with_source_exchange;
-use cost_functions::profit_function_sell_with_source_exchange;
+use execution_lib::find_arbitrage_opportunities;
+use execution_lib::plan_fills;
+use execution_lib::plan_fills_for_notional;
+use execution_lib::FeeModel;
+use execution_lib::FeeSchedule;
+use execution_lib::FillSummary;
+use execution_lib::FillTarget;
+use execution_lib::ParentOrder;
+use execution_lib::VenueConstraints;
+use execution_lib::route_order;
+
+use instrument_lib::InstrumentCatalog;
+use instrument_lib::InstrumentRegistry;
+use instrument_lib::InstrumentSpec;
 
 use limit_order_book_lib::MultiTickerLimitOrderBook;
+use limit_order_book_lib::OrderBookError;
 use limit_order_book_lib::OrderSide;
+use limit_order_book_lib::ExchangeId;
+use limit_order_book_lib::Instrument;
+use limit_order_book_lib::TradingRules;
+
+use limit_order_book_lib::FixedPoint;
+
+use recorder_lib::Compression;
+use recorder_lib::Recorder;
+use recorder_lib::RecorderConfig;
+
+use websocket_feed_lib::FeedHandler;
+use websocket_feed_lib::run_feed;
 
-use ordered_float::NotNan;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::Duration;
 
 
 fn main() {
     println!("Program start");
 
     let mut limit_order_book = MultiTickerLimitOrderBook::new();
-    
-    #[allow(non_snake_case)]
-    let ticker_BTC_USD = "BTCUSD";
-    let source_exchange_coinbase = "COINBASE";
-    let source_exchange_gemini = "GEMINI";
-    let source_exchange_kraken = "KRAKEN";
-
-    let client = reqwest::blocking::Client::new();
-    let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/127.0.0.0 Safari/537.36";
-
-    let response_coinbase_text = get_api_coinbase(&client, user_agent);
-    dump_coinbase_response_text_to_file(&response_coinbase_text);
-    load_coinbase_book(
-        response_coinbase_text,
-        ticker_BTC_USD,
-        source_exchange_coinbase,
-        &mut limit_order_book,
-    );
 
-    let response_gemini_text = get_api_gemini(&client, user_agent);
-    dump_gemini_response_text_to_file(&response_gemini_text);
-    load_gemini_book(
-        response_gemini_text,
-        ticker_BTC_USD,
-        source_exchange_gemini,
-        &mut limit_order_book,
-    );
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_gemini = ExchangeId::intern("GEMINI");
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
 
-    let response_kraken_text = get_api_kraken(&client, user_agent);
-    dump_kraken_response_text_to_file(&response_kraken_text);
-    load_kraken_book(
-        response_kraken_text,
-        ticker_BTC_USD,
-        source_exchange_kraken,
-        &mut limit_order_book,
-    );
+    let fee_model = fee_model(source_exchange_coinbase, source_exchange_gemini, source_exchange_kraken);
+
+    let arguments: Vec<String> = std::env::args().collect();
+    let config_path = argument_value(&arguments, "--config");
+    let replay_directory = argument_value(&arguments, "--replay");
+    let record_directory = argument_value(&arguments, "--record");
+
+    let mut config = match config_path {
+        Some(config_path) => {
+            Config::load(Path::new(config_path))
+                .unwrap_or_else(|error| panic!("failed to load config {config_path}: {error}"))
+        },
+        None => {
+            Config::default()
+        },
+    };
+    let instrument_registry = config
+        .instrument_registry()
+        .unwrap_or_else(|error| panic!("invalid config: {error}"));
+
+    // `--record` overrides the directory of the recorder settings in the config
+    let recorder_config = match (record_directory, &config.recorder) {
+        (Some(record_directory), Some(recorder_settings)) => {
+            let mut recorder_config = recorder_settings.recorder_config();
+            recorder_config.directory = Path::new(record_directory).to_path_buf();
+            Some(recorder_config)
+        },
+        (Some(record_directory), None) => {
+            let mut recorder_config = RecorderConfig::new(Path::new(record_directory));
+            recorder_config.compression = Compression::Gzip;
+            Some(recorder_config)
+        },
+        (None, Some(recorder_settings)) => {
+            Some(recorder_settings.recorder_config())
+        },
+        (None, None) => {
+            None
+        },
+    };
 
-    let target_volume = NotNan::new(10.0).unwrap();
+    match replay_directory {
+        Some(replay_directory) => {
+            // Replays do not fetch the catalogs, so orders are not checked against them
+            let instrument_catalog = InstrumentCatalog::new();
+            replay(Path::new(replay_directory), &config, &instrument_registry, &instrument_catalog, &mut limit_order_book, &fee_model);
+        },
+        None => {
+            let client = reqwest::blocking::Client::new();
+            let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/127.0.0.0 Safari/537.36";
 
-    let total_cost_to_buy = cost_function_buy(&mut limit_order_book, ticker_BTC_USD, target_volume.clone());
-    println!("Total cost to BUY {target_volume} BTC: ${total_cost_to_buy}");
+            let instrument_catalog = fetch_instrument_catalog(&config, &client, user_agent);
+            for (instrument, venue) in config.disable_untradable_venues(&instrument_catalog) {
+                println!("skipping {instrument} on {venue:?}: not trading");
+            }
+            for instrument_config in &config.instruments {
+                if let Some(trading_rules) = trading_rules(&instrument_catalog, instrument_config) {
+                    limit_order_book.set_trading_rules(instrument_config.instrument(), trading_rules);
+                }
+            }
 
-    let total_profit_from_sell = profit_function_sell(&mut limit_order_book, ticker_BTC_USD, target_volume.clone());
-    println!("Total profit from SELL {target_volume} BTC: ${total_profit_from_sell}");
+            let mut fetched_responses: Vec<FetchedResponse> = Vec::new();
+            for instrument_config in &config.instruments {
+                for exchange_adapter in instrument_config.exchange_adapters() {
+                    // A venue which cannot be fetched is left out of the book and the capture
+                    let fetched_response = match exchange_adapter.fetch_response(&client, user_agent) {
+                        Ok(fetched_response) => {
+                            fetched_response
+                        },
+                        Err(error) => {
+                            let source_exchange = exchange_adapter.source_exchange();
+                            let symbol = exchange_adapter.symbol();
+                            println!("failed to fetch {source_exchange} {symbol} book: {error}");
+                            continue;
+                        },
+                    };
+                    if recorder_config.is_none() {
+                        exchange_adapter.dump_response_text_to_file(&fetched_response.response_text);
+                    }
+                    if let Err(error) = exchange_adapter.load_book(&fetched_response.response_text, &instrument_registry, &mut limit_order_book) {
+                        let source_exchange = exchange_adapter.source_exchange();
+                        let symbol = exchange_adapter.symbol();
+                        println!("failed to load {source_exchange} {symbol} book: {error}");
+                    }
+                    fetched_responses.push(fetched_response);
+                }
+            }
 
-    let total_cost_to_buy_by_source_exchange = 
-        cost_function_buy_with_source_exchange(&mut limit_order_book, ticker_BTC_USD, target_volume.clone());
-    println!("Total cost to BUY {target_volume} BTC by source exchange: {total_cost_to_buy_by_source_exchange:?}");
+            if let Some(recorder_config) = recorder_config {
+                match Recorder::new(recorder_config).record(&fetched_responses) {
+                    Ok(capture_directory) => {
+                        println!("Recorded capture {}", capture_directory.display());
+                    },
+                    Err(error) => {
+                        println!("failed to record capture: {error}");
+                    },
+                }
+            }
+
+            for instrument_config in &config.instruments {
+                if let Err(error) = print_analysis(&limit_order_book, instrument_config, &instrument_catalog, &fee_model) {
+                    println!("{error}");
+                }
+            }
+        },
+    }
+
+    if arguments.iter().any(|argument| argument == "--stream") {
+        stream(limit_order_book, &config);
+    }
+
+    println!("Program ends");
+}
+
+/// The argument which follows `name`, for example the directory in `--replay <directory>`.
+fn argument_value<'a>(arguments: &'a [String], name: &str) -> Option<&'a String> {
+    arguments
+        .iter()
+        .position(|argument| argument == name)
+        .and_then(|index| arguments.get(index + 1))
+}
+
+/// Fetch the catalog of each enabled venue of each instrument. Venues which
+/// share a catalog, such as every Coinbase product, are fetched once.
+fn fetch_instrument_catalog(
+    config: &Config,
+    client: &reqwest::blocking::Client,
+    user_agent: &str,
+) -> InstrumentCatalog {
+    let mut instrument_catalog = InstrumentCatalog::new();
+    let mut catalog_urls = BTreeSet::new();
+    for instrument_config in &config.instruments {
+        for exchange_adapter in instrument_config.exchange_adapters() {
+            if !catalog_urls.insert(exchange_adapter.catalog_url()) {
+                continue;
+            }
+            match exchange_adapter.fetch_catalog(client, user_agent) {
+                Ok(instrument_specs) => {
+                    for instrument_spec in instrument_specs {
+                        instrument_catalog.insert(instrument_spec);
+                    }
+                },
+                Err(error) => {
+                    let source_exchange = exchange_adapter.source_exchange();
+                    println!("failed to fetch {source_exchange} catalog: {error}");
+                },
+            }
+        }
+    }
+    instrument_catalog
+}
+
+/// The minimum order size and lot size of `ticker` on each exchange in
+/// `instrument_catalog`.
+fn venue_constraints(instrument_catalog: &InstrumentCatalog, ticker: Instrument) -> VenueConstraints {
+    let mut venue_constraints = VenueConstraints::new();
+    for instrument_spec in instrument_catalog.instrument_specs() {
+        if instrument_spec.instrument != ticker {
+            continue;
+        }
+        if let Some(minimum_order_size) = instrument_spec.minimum_order_size {
+            venue_constraints.set_minimum_order_volume(instrument_spec.source_exchange, minimum_order_size);
+        }
+        venue_constraints.set_lot_size(instrument_spec.source_exchange, instrument_spec.base_increment);
+    }
+    venue_constraints
+}
+
+/// The trading rules of the book of `instrument_config`, which holds the
+/// volume of each of its enabled venues: the least common multiple of the tick
+/// sizes and of the lot sizes of the rules of those venues in
+/// `instrument_catalog`, so that every order is on the grid of every venue, the
+/// largest minimum notional, and the largest price and volume scales, so that
+/// the data of every venue can be stored. `None` if none of them are in the
+/// catalog.
+fn trading_rules(instrument_catalog: &InstrumentCatalog, instrument_config: &InstrumentConfig) -> Option<TradingRules> {
+    let instrument = instrument_config.instrument();
+    let venue_trading_rules =
+        instrument_config.enabled_venues()
+            .filter_map(|venue_config| instrument_catalog.get(venue_config.venue.source_exchange(), instrument))
+            .map(InstrumentSpec::trading_rules)
+            .collect::<Vec<_>>();
+    if venue_trading_rules.is_empty() {
+        return None;
+    }
+    let mut trading_rules = TradingRules::new();
+    trading_rules.tick_size = least_common_multiple(venue_trading_rules.iter().filter_map(|venue_trading_rules| venue_trading_rules.tick_size));
+    trading_rules.lot_size = least_common_multiple(venue_trading_rules.iter().filter_map(|venue_trading_rules| venue_trading_rules.lot_size));
+    trading_rules.minimum_notional = venue_trading_rules.iter().filter_map(|venue_trading_rules| venue_trading_rules.minimum_notional).max();
+    trading_rules.price_scale = venue_trading_rules.iter().filter_map(|venue_trading_rules| venue_trading_rules.price_scale).max();
+    trading_rules.volume_scale = venue_trading_rules.iter().filter_map(|venue_trading_rules| venue_trading_rules.volume_scale).max();
+    trading_rules.snap_venue_data = instrument_config.snap_venue_data;
+    Some(trading_rules)
+}
+
+/// The least common multiple of `increments`, or `None` if there are none or
+/// it overflows, in which case the book has no such rule.
+fn least_common_multiple(increments: impl Iterator<Item = FixedPoint>) -> Option<FixedPoint> {
+    increments
+        .map(Some)
+        .reduce(|least_common_multiple, increment| least_common_multiple?.checked_least_common_multiple(increment?))
+        .flatten()
+}
 
-    let total_profit_from_sell_by_source_exchange = 
-        profit_function_sell_with_source_exchange(&mut limit_order_book, ticker_BTC_USD, target_volume.clone());
-    println!("Total profit from SELL {target_volume} BTC by source exchange: {total_profit_from_sell_by_source_exchange:?}");
+/// Rebuild the book from the responses dumped by previous runs, instead of
+/// fetching them, and print the analysis of each instrument after each capture.
+fn replay(
+    replay_directory: &Path,
+    config: &Config,
+    instrument_registry: &InstrumentRegistry,
+    instrument_catalog: &InstrumentCatalog,
+    limit_order_book: &mut MultiTickerLimitOrderBook,
+    fee_model: &FeeModel,
+) {
+    let capture_directories = capture_directories(replay_directory)
+        .unwrap_or_else(|error| panic!("failed to read replay directory {}: {error}", replay_directory.display()));
+    let exchange_adapters: Vec<Box<dyn ExchangeAdapter>> = config
+        .instruments
+        .iter()
+        .flat_map(|instrument_config| instrument_config.exchange_adapters())
+        .collect();
+
+    for capture_directory in capture_directories {
+        let capture = capture_directory.display();
+        match load_capture(&capture_directory, &exchange_adapters, instrument_registry, limit_order_book) {
+            Ok(source_exchanges) => {
+                println!("Replaying capture {capture}: {source_exchanges:?}");
+            },
+            Err(error) => {
+                println!("failed to load capture {capture}: {error}");
+                continue;
+            },
+        }
+        for instrument_config in &config.instruments {
+            if let Err(error) = print_analysis(limit_order_book, instrument_config, instrument_catalog, fee_model) {
+                println!("{error}");
+            }
+        }
+    }
+}
+
+fn print_analysis(
+    limit_order_book: &MultiTickerLimitOrderBook,
+    instrument_config: &InstrumentConfig,
+    instrument_catalog: &InstrumentCatalog,
+    fee_model: &FeeModel,
+) -> Result<(), OrderBookError> {
+    let ticker = instrument_config.instrument();
+    let base_asset = instrument_config.base_asset.as_str();
+    let target_volume = instrument_config.target_volume;
+
+    println!("{ticker}:");
+
+    let buy_fill_plan = plan_fills(limit_order_book, ticker, OrderSide::BUY, target_volume)?;
+    let sell_fill_plan = plan_fills(limit_order_book, ticker, OrderSide::SELL, target_volume)?;
+
+    let buy_fill_summary = buy_fill_plan.summary(fee_model);
+    let sell_fill_summary = sell_fill_plan.summary(fee_model);
+
+    if buy_fill_summary.is_completely_filled() {
+        let total_cost_to_buy = buy_fill_summary.total_notional;
+        let total_cost_to_buy_after_fees = buy_fill_summary.net_notional;
+        println!("Total cost to BUY {target_volume} {base_asset}: ${total_cost_to_buy} (${total_cost_to_buy_after_fees} after fees)");
+    } else {
+        print_insufficient_volume(&buy_fill_summary, base_asset);
+    }
+    println!("BUY {target_volume} {base_asset}: {buy_fill_summary:?}");
+
+    if sell_fill_summary.is_completely_filled() {
+        let total_profit_from_sell = sell_fill_summary.total_notional;
+        let total_profit_from_sell_after_fees = sell_fill_summary.net_notional;
+        println!("Total profit from SELL {target_volume} {base_asset}: ${total_profit_from_sell} (${total_profit_from_sell_after_fees} after fees)");
+    } else {
+        print_insufficient_volume(&sell_fill_summary, base_asset);
+    }
+    println!("SELL {target_volume} {base_asset}: {sell_fill_summary:?}");
+
+    let total_cost_to_buy_by_source_exchange = buy_fill_plan.notional_after_fees_by_source_exchange(fee_model);
+    println!("Total cost to BUY {target_volume} {base_asset} by source exchange: {total_cost_to_buy_by_source_exchange:?}");
+
+    let total_profit_from_sell_by_source_exchange = sell_fill_plan.notional_after_fees_by_source_exchange(fee_model);
+    println!("Total profit from SELL {target_volume} {base_asset} by source exchange: {total_profit_from_sell_by_source_exchange:?}");
+
+    let parent_order = ParentOrder::new(OrderSide::BUY, target_volume, FixedPoint::MAX);
+    let venue_constraints = venue_constraints(instrument_catalog, ticker);
+    let routing_plan = route_order(limit_order_book, ticker, &parent_order, fee_model, &venue_constraints)?;
+    println!("Child orders to BUY {target_volume} {base_asset} after fees:");
+    for child_order in &routing_plan.child_orders {
+        println!("{child_order:?}");
+        let source_exchange = child_order.source_exchange;
+        if instrument_catalog.contains_source_exchange(source_exchange) {
+            if let Err(error) = instrument_catalog.check_order(source_exchange, ticker, child_order.limit_price, child_order.volume) {
+                println!("Child order would be rejected: {error}");
+            }
+        }
+    }
+
+    let target_notional = instrument_config.target_notional;
+
+    let buy_notional_fill_plan = plan_fills_for_notional(limit_order_book, ticker, OrderSide::BUY, target_notional)?;
+    let buy_notional_fill_summary = buy_notional_fill_plan.summary(fee_model);
+    if buy_notional_fill_summary.is_completely_filled() {
+        let volume_bought = buy_notional_fill_summary.filled_volume;
+        println!("{base_asset} bought with ${target_notional}: {volume_bought}");
+    } else {
+        print_insufficient_volume(&buy_notional_fill_summary, base_asset);
+    }
+    let volume_bought_by_source_exchange = buy_notional_fill_plan.volume_by_source_exchange();
+    println!("{base_asset} bought with ${target_notional} by source exchange: {volume_bought_by_source_exchange:?}");
+
+    let sell_notional_fill_plan = plan_fills_for_notional(limit_order_book, ticker, OrderSide::SELL, target_notional)?;
+    let sell_notional_fill_summary = sell_notional_fill_plan.summary(fee_model);
+    if sell_notional_fill_summary.is_completely_filled() {
+        let volume_sold = sell_notional_fill_summary.filled_volume;
+        println!("{base_asset} sold to raise ${target_notional}: {volume_sold}");
+    } else {
+        print_insufficient_volume(&sell_notional_fill_summary, base_asset);
+    }
+    let volume_sold_by_source_exchange = sell_notional_fill_plan.volume_by_source_exchange();
+    println!("{base_asset} sold to raise ${target_notional} by source exchange: {volume_sold_by_source_exchange:?}");
 
     let total_volume_buy_by_source_exchange = 
-        limit_order_book.total_volume_by_source_exchange(ticker_BTC_USD, &OrderSide::BUY);
+        limit_order_book.total_volume_by_source_exchange(ticker, &OrderSide::BUY)?;
     println!("Total volume BUY by source exchange: {total_volume_buy_by_source_exchange:?}");
     
     let total_volume_sell_by_source_exchange = 
-        limit_order_book.total_volume_by_source_exchange(ticker_BTC_USD, &OrderSide::SELL);
+        limit_order_book.total_volume_by_source_exchange(ticker, &OrderSide::SELL)?;
     println!("Total volume SELL by source exchange: {total_volume_sell_by_source_exchange:?}");
 
-    #[allow(non_snake_case)]
-    let round_trip_10_BTC_cost = total_cost_to_buy - total_profit_from_sell;
-    println!("Round Trip Cost (10 BTC): {round_trip_10_BTC_cost}");
+    // A round trip cost is only meaningful if both sides can be completely filled
+    if buy_fill_summary.is_completely_filled() && sell_fill_summary.is_completely_filled() {
+        let round_trip_cost = buy_fill_summary.total_notional - sell_fill_summary.total_notional;
+        println!("Round Trip Cost ({target_volume} {base_asset}): {round_trip_cost}");
+    }
+
+    let quote = limit_order_book.quote(ticker)?;
+    println!("Consolidated quote: {quote:?}");
+    if quote.is_locked() || quote.is_crossed() {
+        let market_state = quote.market_state;
+        println!("Consolidated market is {market_state:?}");
+    }
 
-    let spread = limit_order_book.spread(ticker_BTC_USD);
+    let spread = limit_order_book.spread(ticker)?;
     println!("Spread (All Exchanges): {spread:?}");
 
-    let spreads = limit_order_book.spread_by_exchange(ticker_BTC_USD);
+    let spreads = limit_order_book.spread_by_exchange(ticker)?;
     println!("Spreads: {spreads:?}");
 
+    let bid_depth_by_source_exchange =
+        limit_order_book.depth_within_basis_points_by_source_exchange(ticker, &OrderSide::BUY, 10)?;
+    println!("BUY volume within 10 bps of mid by source exchange: {bid_depth_by_source_exchange:?}");
+    let ask_depth_by_source_exchange =
+        limit_order_book.depth_within_basis_points_by_source_exchange(ticker, &OrderSide::SELL, 10)?;
+    println!("SELL volume within 10 bps of mid by source exchange: {ask_depth_by_source_exchange:?}");
+
+    let arbitrage_opportunities =
+        find_arbitrage_opportunities(limit_order_book, ticker, fee_model, &venue_constraints)?
+            .into_iter()
+            .filter(|arbitrage_opportunity| arbitrage_opportunity.net_profit.is_positive())
+            .collect::<Vec<_>>();
+    println!("Arbitrage opportunities after fees: {arbitrage_opportunities:?}");
+
     println!("Highest Bid, Lowest Ask by Exchange:");
-    let highest_bids = limit_order_book.highest_bid_price_by_exchange(ticker_BTC_USD);
+    let highest_bids = limit_order_book.highest_bid_price_by_exchange(ticker)?;
     println!("Bids: {highest_bids:?}");
-    let lowest_asks = limit_order_book.lowest_ask_price_by_exchange(ticker_BTC_USD);
+    let lowest_asks = limit_order_book.lowest_ask_price_by_exchange(ticker)?;
     println!("Asks: {lowest_asks:?}");
 
-    println!("Program ends");
+    Ok(())
+}
+
+/// The taker and maker fees of the lowest volume tier of each exchange. These
+/// change from time to time, so check the fee schedule of each exchange before
+/// relying on them.
+fn fee_model(
+    source_exchange_coinbase: ExchangeId,
+    source_exchange_gemini: ExchangeId,
+    source_exchange_kraken: ExchangeId,
+) -> FeeModel {
+    let fee_rate = |s: &str| s.parse::<FixedPoint>().expect("invalid fee rate");
+
+    let mut fee_model = FeeModel::new();
+    fee_model.set_fee_schedule(source_exchange_coinbase, FeeSchedule::flat(fee_rate("0.004"), fee_rate("0.006")));
+    fee_model.set_fee_schedule(source_exchange_gemini, FeeSchedule::flat(fee_rate("0.002"), fee_rate("0.004")));
+    fee_model.set_fee_schedule(source_exchange_kraken, FeeSchedule::flat(fee_rate("0.0025"), fee_rate("0.004")));
+    fee_model
+}
+
+fn print_insufficient_volume(fill_summary: &FillSummary, base_asset: &str) {
+    let order_side = fill_summary.order_side;
+    let filled_volume = fill_summary.filled_volume;
+    let total_notional = fill_summary.total_notional;
+    let (target, unfilled) = match (fill_summary.target, fill_summary.unfilled) {
+        (FillTarget::Volume(target_volume), FillTarget::Volume(unfilled_volume)) => {
+            (format!("{target_volume} {base_asset}"), format!("{unfilled_volume} {base_asset}"))
+        },
+        (FillTarget::Notional(target_notional), FillTarget::Notional(unfilled_notional)) => {
+            (format!("${target_notional} of {base_asset}"), format!("${unfilled_notional}"))
+        },
+        (target, unfilled) => {
+            (format!("{target:?}"), format!("{unfilled:?}"))
+        },
+    };
+    println!(
+        "Insufficient volume to {order_side:?} {target}: \
+        only {filled_volume} {base_asset} can be filled, for ${total_notional}, leaving {unfilled} unfilled"
+    );
+}
+
+/// Keep the book up to date from the WebSocket feed of each enabled venue of
+/// each instrument, and print the best bid and ask of each exchange every few
+/// seconds. Runs until killed.
+fn stream(limit_order_book: MultiTickerLimitOrderBook, config: &Config) {
+    let limit_order_book = Arc::new(RwLock::new(limit_order_book));
+
+    let feed_handlers: Vec<Box<dyn FeedHandler + Send>> = config
+        .instruments
+        .iter()
+        .flat_map(|instrument_config| instrument_config.feed_handlers())
+        .collect();
+
+    for mut feed_handler in feed_handlers {
+        let limit_order_book = Arc::clone(&limit_order_book);
+        std::thread::spawn(
+            move || {
+                loop {
+                    let url = feed_handler.url();
+                    match run_feed(&url, feed_handler.as_mut(), &limit_order_book) {
+                        Ok(()) => {
+                            println!("{url}: connection closed, reconnecting");
+                        },
+                        Err(error) => {
+                            println!("{url}: {error}, reconnecting");
+                        },
+                    }
+                    std::thread::sleep(Duration::from_secs(1));
+                }
+            }
+        );
+    }
+
+    loop {
+        std::thread::sleep(Duration::from_secs(5));
+        let limit_order_book = limit_order_book.read().expect("order book lock poisoned");
+        for instrument_config in &config.instruments {
+            let ticker = instrument_config.instrument();
+            let highest_bids = limit_order_book.highest_bid_price_by_exchange(ticker);
+            let lowest_asks = limit_order_book.lowest_ask_price_by_exchange(ticker);
+            match (highest_bids, lowest_asks) {
+                (Ok(highest_bids), Ok(lowest_asks)) => {
+                    println!("{ticker} Bids: {highest_bids:?}");
+                    println!("{ticker} Asks: {lowest_asks:?}");
+                },
+                (Err(error), _) | (_, Err(error)) => {
+                    println!("{error}");
+                },
+            }
+        }
+    }
 }
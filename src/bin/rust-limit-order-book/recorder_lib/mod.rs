@@ -0,0 +1,268 @@
+use std::fmt;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde::Deserialize;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::exchange_adapter_lib::FetchedResponse;
+
+
+/// The file in each capture directory which lists the responses in it.
+pub const MANIFEST_FILENAME: &str = "manifest.json";
+
+/// Capture directories are named by the time of the capture, so that sorting
+/// them by name sorts them by time.
+const CAPTURE_DIRECTORY_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+}
+
+impl Compression {
+    /// The suffix added to the dump filename of each recorded response.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Compression::None => {
+                ""
+            },
+            Compression::Gzip => {
+                ".gz"
+            },
+        }
+    }
+}
+
+/// One recorded response and how it was fetched.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source_exchange: String,
+    pub symbol: String,
+    /// The file which holds the raw response, relative to the capture directory.
+    pub filename: String,
+    pub compression: Compression,
+    pub fetch_time: chrono::DateTime<chrono::offset::Utc>,
+    pub http_status: u16,
+    pub latency_milliseconds: u64,
+}
+
+impl ManifestEntry {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.http_status)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The time of the first fetch in the capture.
+    pub capture_time: chrono::DateTime<chrono::offset::Utc>,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug)]
+pub enum RecorderError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl std::error::Error for RecorderError {
+
+}
+
+impl fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecorderError::Io(error) => {
+                write!(f, "failed to read or write recording: {error}")
+            },
+            RecorderError::Json(error) => {
+                write!(f, "invalid manifest: {error}")
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for RecorderError {
+    fn from(error: std::io::Error) -> RecorderError {
+        RecorderError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RecorderError {
+    fn from(error: serde_json::Error) -> RecorderError {
+        RecorderError::Json(error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    /// The directory which holds one subdirectory per capture.
+    pub directory: PathBuf,
+    pub compression: Compression,
+    /// The number of captures to keep. The oldest captures are deleted first.
+    pub maximum_captures: Option<usize>,
+    /// Captures older than this are deleted.
+    pub maximum_age: Option<Duration>,
+}
+
+impl RecorderConfig {
+    /// Uncompressed captures which are kept forever.
+    pub fn new(directory: &Path) -> RecorderConfig {
+        RecorderConfig {
+            directory: directory.to_path_buf(),
+            compression: Compression::None,
+            maximum_captures: None,
+            maximum_age: None,
+        }
+    }
+}
+
+/// Stores the raw REST responses of each run in a new timestamped capture
+/// directory, with a manifest, so that previous runs are kept and can be
+/// replayed with `exchange_adapter_lib::load_capture`.
+#[derive(Debug)]
+pub struct Recorder {
+    config: RecorderConfig,
+}
+
+impl Recorder {
+    pub fn new(config: RecorderConfig) -> Recorder {
+        Recorder {
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &RecorderConfig {
+        &self.config
+    }
+
+    /// Write `fetched_responses` and their manifest to a new capture directory,
+    /// then delete the captures which are outside the retention limits.
+    /// Returns the new capture directory.
+    pub fn record(&self, fetched_responses: &[FetchedResponse]) -> Result<PathBuf, RecorderError> {
+        let capture_time = fetched_responses
+            .iter()
+            .map(|fetched_response| fetched_response.fetch_time)
+            .min()
+            .unwrap_or_else(chrono::offset::Utc::now);
+        let capture_directory = self.config.directory.join(capture_time.format(CAPTURE_DIRECTORY_FORMAT).to_string());
+        std::fs::create_dir_all(&capture_directory)?;
+
+        let compression = self.config.compression;
+        let mut entries = Vec::new();
+        for fetched_response in fetched_responses {
+            let filename = format!("{}{}", fetched_response.dump_filename, compression.file_extension());
+            write_response_text(&capture_directory.join(&filename), &fetched_response.response_text, compression)?;
+            entries.push(
+                ManifestEntry {
+                    source_exchange: String::from(fetched_response.source_exchange.name()),
+                    symbol: fetched_response.symbol.clone(),
+                    filename,
+                    compression,
+                    fetch_time: fetched_response.fetch_time,
+                    http_status: fetched_response.http_status,
+                    latency_milliseconds: u64::try_from(fetched_response.latency.as_millis()).unwrap_or(u64::MAX),
+                }
+            );
+        }
+
+        let manifest = Manifest {
+            capture_time,
+            entries,
+        };
+        let manifest_file = std::fs::File::create(capture_directory.join(MANIFEST_FILENAME))?;
+        serde_json::to_writer_pretty(manifest_file, &manifest)?;
+
+        self.enforce_retention(capture_time)?;
+        Ok(capture_directory)
+    }
+
+    /// Delete the oldest captures beyond `maximum_captures`, and the captures
+    /// which are older than `maximum_age` at time `now`. Only directories with
+    /// a manifest are treated as captures. Returns the deleted directories.
+    pub fn enforce_retention(&self, now: chrono::DateTime<chrono::offset::Utc>) -> Result<Vec<PathBuf>, RecorderError> {
+        let capture_directories = recorded_capture_directories(&self.config.directory)?;
+        let excess_captures = match self.config.maximum_captures {
+            Some(maximum_captures) => capture_directories.len().saturating_sub(maximum_captures),
+            None => 0,
+        };
+
+        let mut deleted_capture_directories = Vec::new();
+        for (index, capture_directory) in capture_directories.into_iter().enumerate() {
+            let too_old = match self.config.maximum_age {
+                Some(maximum_age) => {
+                    let capture_time = read_manifest(&capture_directory)?.capture_time;
+                    now.signed_duration_since(capture_time)
+                        .to_std()
+                        .is_ok_and(|age| age > maximum_age)
+                },
+                None => {
+                    false
+                },
+            };
+            if index < excess_captures || too_old {
+                std::fs::remove_dir_all(&capture_directory)?;
+                deleted_capture_directories.push(capture_directory);
+            }
+        }
+        Ok(deleted_capture_directories)
+    }
+}
+
+pub fn read_manifest(capture_directory: &Path) -> Result<Manifest, RecorderError> {
+    let manifest_file = std::fs::File::open(capture_directory.join(MANIFEST_FILENAME))?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(manifest_file))?)
+}
+
+/// Read a recorded or dumped response, decompressing files which end in `.gz`.
+pub fn read_response_text(path: &Path) -> Result<String, std::io::Error> {
+    if path.extension().is_some_and(|extension| extension == "gz") {
+        let mut response_text = String::new();
+        GzDecoder::new(std::fs::File::open(path)?).read_to_string(&mut response_text)?;
+        Ok(response_text)
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+fn write_response_text(path: &Path, response_text: &str, compression: Compression) -> Result<(), std::io::Error> {
+    let file = std::fs::File::create(path)?;
+    match compression {
+        Compression::None => {
+            let mut file = file;
+            file.write_all(response_text.as_bytes())
+        },
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+            encoder.write_all(response_text.as_bytes())?;
+            encoder.finish()?;
+            Ok(())
+        },
+    }
+}
+
+/// The subdirectories of `directory` which hold a manifest, oldest first.
+fn recorded_capture_directories(directory: &Path) -> Result<Vec<PathBuf>, RecorderError> {
+    let mut capture_directories = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() && path.join(MANIFEST_FILENAME).is_file() {
+            capture_directories.push(path);
+        }
+    }
+    capture_directories.sort();
+    Ok(capture_directories)
+}
+
+
+#[cfg(test)]
+mod tests;
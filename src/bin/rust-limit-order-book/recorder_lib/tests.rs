@@ -0,0 +1,145 @@
+
+use super::*;
+
+use std::collections::BTreeMap;
+
+use crate::coinbase_lib::CoinbaseAdapter;
+use crate::exchange_adapter_lib::ExchangeAdapter;
+use crate::exchange_adapter_lib::load_capture;
+use crate::exchange_adapter_lib::capture_directories;
+use crate::gemini_lib::GeminiAdapter;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::Instrument;
+use crate::test_support_lib::fixed_point;
+use crate::test_support_lib::instrument_registry;
+
+
+const COINBASE_RESPONSE: &str = r#"{"bids":[["64000.01","0.5",3]],"asks":[["64001.00","0.75",2]],"sequence":81234567890,"auction_mode":false,"auction":null,"time":"2024-08-01T12:00:00.123456Z"}"#;
+const GEMINI_RESPONSE: &str = r#"{"message":"rate limited"}"#;
+
+fn fetch_time(s: &str) -> chrono::DateTime<chrono::offset::Utc> {
+    s.parse().unwrap()
+}
+
+fn fetched_response(
+    exchange_adapter: &dyn ExchangeAdapter,
+    fetch_time: chrono::DateTime<chrono::offset::Utc>,
+    http_status: u16,
+    response_text: &str,
+) -> FetchedResponse {
+    FetchedResponse {
+        source_exchange: exchange_adapter.source_exchange(),
+        symbol: exchange_adapter.symbol(),
+        dump_filename: exchange_adapter.dump_filename(),
+        fetch_time,
+        http_status,
+        latency: Duration::from_millis(120),
+        response_text: String::from(response_text),
+    }
+}
+
+fn recorder_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+    directory
+}
+
+
+#[test]
+fn record_and_replay_test() {
+
+    let ticker = Instrument::intern("BTC", "USD");
+    let source_exchange_coinbase = ExchangeId::intern("COINBASE");
+    let source_exchange_gemini = ExchangeId::intern("GEMINI");
+
+    let exchange_adapters: Vec<Box<dyn ExchangeAdapter>> = vec![
+        Box::new(CoinbaseAdapter::new("BTC-USD", source_exchange_coinbase)),
+        Box::new(GeminiAdapter::new("btcusd", source_exchange_gemini)),
+    ];
+
+    let instrument_registry = instrument_registry();
+
+    let directory = recorder_directory("record_and_replay_test");
+    let mut recorder_config = RecorderConfig::new(&directory);
+    recorder_config.compression = Compression::Gzip;
+    let recorder = Recorder::new(recorder_config);
+
+    let fetched_responses = vec![
+        fetched_response(exchange_adapters[0].as_ref(), fetch_time("2024-08-01T12:00:00.250Z"), 200, COINBASE_RESPONSE),
+        fetched_response(exchange_adapters[1].as_ref(), fetch_time("2024-08-01T12:00:00.500Z"), 429, GEMINI_RESPONSE),
+    ];
+    let capture_directory = recorder.record(&fetched_responses).unwrap();
+    assert_eq!(capture_directory, directory.join("20240801T120000.250Z"));
+
+    let manifest = read_manifest(&capture_directory).unwrap();
+    assert_eq!(manifest.capture_time, fetch_time("2024-08-01T12:00:00.250Z"));
+    let expected_manifest_entry = ManifestEntry {
+        source_exchange: String::from("COINBASE"),
+        symbol: String::from("BTC-USD"),
+        filename: String::from("coinbase_BTC-USD.json.gz"),
+        compression: Compression::Gzip,
+        fetch_time: fetch_time("2024-08-01T12:00:00.250Z"),
+        http_status: 200,
+        latency_milliseconds: 120,
+    };
+    assert_eq!(manifest.entries[0], expected_manifest_entry);
+    assert!(!manifest.entries[1].is_success());
+
+    // Responses are stored compressed and read back unchanged
+    let path = capture_directory.join("coinbase_BTC-USD.json.gz");
+    assert_ne!(std::fs::read(&path).unwrap(), COINBASE_RESPONSE.as_bytes());
+    assert_eq!(read_response_text(&path).unwrap(), COINBASE_RESPONSE);
+
+    // The recorder directory can be replayed directly, skipping the response
+    // which was recorded with an error status
+    assert_eq!(capture_directories(&directory).unwrap(), vec![capture_directory.clone()]);
+    let mut limit_order_book = MultiTickerLimitOrderBook::new();
+    let source_exchanges = load_capture(&capture_directory, &exchange_adapters, &instrument_registry, &mut limit_order_book).unwrap();
+    assert_eq!(source_exchanges, vec![source_exchange_coinbase]);
+    assert_eq!(
+        limit_order_book.total_volume_by_source_exchange(ticker, &OrderSide::SELL),
+        Ok(BTreeMap::from([(source_exchange_coinbase, fixed_point("0.75"))]))
+    );
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+
+#[test]
+fn retention_test() {
+
+    let exchange_adapter = CoinbaseAdapter::new("BTC-USD", ExchangeId::intern("COINBASE"));
+
+    let directory = recorder_directory("retention_test");
+    let mut recorder_config = RecorderConfig::new(&directory);
+    recorder_config.maximum_captures = Some(2);
+    let recorder = Recorder::new(recorder_config);
+
+    for fetch_time_text in ["2024-08-01T12:00:00Z", "2024-08-01T12:05:00Z", "2024-08-01T12:10:00Z"] {
+        let fetched_response = fetched_response(&exchange_adapter, fetch_time(fetch_time_text), 200, COINBASE_RESPONSE);
+        recorder.record(&[fetched_response]).unwrap();
+    }
+    // Only the newest captures are kept
+    let expected_capture_directories = vec![
+        directory.join("20240801T120500.000Z"),
+        directory.join("20240801T121000.000Z"),
+    ];
+    assert_eq!(capture_directories(&directory).unwrap(), expected_capture_directories);
+
+    // Captures older than the maximum age are deleted, and other directories
+    // are left alone
+    std::fs::create_dir_all(directory.join("notes")).unwrap();
+    let mut recorder_config = RecorderConfig::new(&directory);
+    recorder_config.maximum_age = Some(Duration::from_secs(600));
+    let recorder = Recorder::new(recorder_config);
+    let deleted_capture_directories = recorder.enforce_retention(fetch_time("2024-08-01T12:18:00Z")).unwrap();
+    assert_eq!(deleted_capture_directories, vec![directory.join("20240801T120500.000Z")]);
+    assert!(directory.join("20240801T121000.000Z").is_dir());
+    assert!(directory.join("notes").is_dir());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,48 @@
+//! Fixtures shared by the tests of every module.
+
+use std::str::FromStr;
+
+use crate::instrument_lib::InstrumentRegistry;
+use crate::kraken_lib::KRAKEN_ASSET_CODES;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::Instrument;
+
+
+pub fn fixed_point(s: &str) -> FixedPoint {
+    FixedPoint::from_str(s).unwrap()
+}
+
+pub fn ticker_1() -> Instrument {
+    Instrument::intern("EXAMPLE1", "USD")
+}
+
+pub fn ticker_2() -> Instrument {
+    Instrument::intern("EXAMPLE2", "USD")
+}
+
+pub fn source_exchange_1() -> ExchangeId {
+    ExchangeId::intern("SRCEX1")
+}
+
+pub fn source_exchange_2() -> ExchangeId {
+    ExchangeId::intern("SRCEX2")
+}
+
+/// BTC-USD on Coinbase, Gemini and Kraken, and ETH-USD and ETH-BTC on Kraken,
+/// with Kraken's asset codes.
+pub fn instrument_registry() -> InstrumentRegistry {
+    let source_exchange_kraken = ExchangeId::intern("KRAKEN");
+    let mut instrument_registry = InstrumentRegistry::new();
+    instrument_registry.add_asset_aliases(source_exchange_kraken, KRAKEN_ASSET_CODES);
+
+    let instrument = Instrument::intern("BTC", "USD");
+    instrument_registry.add_instrument(instrument, ExchangeId::intern("COINBASE"), "BTC-USD").unwrap();
+    instrument_registry.add_instrument(instrument, ExchangeId::intern("GEMINI"), "btcusd").unwrap();
+    instrument_registry.add_instrument(instrument, source_exchange_kraken, "XXBTZUSD").unwrap();
+    instrument_registry.add_symbol_alias(instrument, source_exchange_kraken, "XBT/USD").unwrap();
+
+    instrument_registry.add_instrument(Instrument::intern("ETH", "USD"), source_exchange_kraken, "XETHZUSD").unwrap();
+    instrument_registry.add_instrument(Instrument::intern("ETH", "BTC"), source_exchange_kraken, "XETHXXBT").unwrap();
+    instrument_registry
+}
@@ -0,0 +1,166 @@
+
+use serde::Deserialize;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+
+use super::FeedError;
+use super::FeedHandler;
+use super::SequenceTracker;
+use super::parse_fixed_point;
+
+
+#[derive(Deserialize, Debug)]
+struct CoinbaseMessage {
+    channel: String,
+    sequence_num: u64,
+    #[serde(default)]
+    events: Vec<CoinbaseEvent>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinbaseEvent {
+    #[serde(rename="type", default)]
+    event_type: String,
+    #[serde(default)]
+    product_id: String,
+    #[serde(default)]
+    updates: Vec<CoinbaseUpdate>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinbaseUpdate {
+    side: String,
+    price_level: String,
+    new_quantity: String,
+}
+
+/// Coinbase Advanced Trade `level2` channel.
+///
+/// Every message on the connection, from any channel, carries a
+/// `sequence_num` which increases by one, so a gap means a message was missed.
+#[derive(Debug)]
+pub struct CoinbaseFeedHandler {
+    product_id: String,
+    ticker: Instrument,
+    source_exchange: ExchangeId,
+    sequence_tracker: SequenceTracker,
+    snapshot_received: bool,
+}
+
+impl CoinbaseFeedHandler {
+    pub fn new(product_id: &str, ticker: Instrument, source_exchange: ExchangeId) -> CoinbaseFeedHandler {
+        CoinbaseFeedHandler {
+            product_id: String::from(product_id),
+            ticker,
+            source_exchange,
+            sequence_tracker: SequenceTracker::new(),
+            snapshot_received: false,
+        }
+    }
+
+    fn apply_snapshot(&mut self, updates: &[CoinbaseUpdate], limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+        for update in updates {
+            let (order_side, price, volume) = parse_update(update)?;
+            match order_side {
+                OrderSide::BUY => {
+                    bids.push((price, volume))
+                },
+                OrderSide::SELL => {
+                    asks.push((price, volume))
+                },
+            }
+        }
+        limit_order_book.replace_exchange_snapshot(self.ticker, self.source_exchange, &bids, &asks)?;
+        self.snapshot_received = true;
+        Ok(())
+    }
+
+    fn apply_updates(&mut self, updates: &[CoinbaseUpdate], limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        if !self.snapshot_received {
+            return Err(FeedError::UpdateBeforeSnapshot);
+        }
+        for update in updates {
+            let (order_side, price, volume) = parse_update(update)?;
+            limit_order_book.set_price_level_volume(self.ticker, order_side, price, self.source_exchange, volume)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_update(update: &CoinbaseUpdate) -> Result<(OrderSide, Price, Quantity), FeedError> {
+    let order_side = match update.side.as_str() {
+        "bid" => {
+            OrderSide::BUY
+        },
+        "offer" => {
+            OrderSide::SELL
+        },
+        side => {
+            return Err(FeedError::InvalidMessage(format!("unknown side {side}")));
+        },
+    };
+    let price = parse_fixed_point(&update.price_level)?;
+    let volume = parse_fixed_point(&update.new_quantity)?;
+    Ok((order_side, price, volume))
+}
+
+impl FeedHandler for CoinbaseFeedHandler {
+    fn url(&self) -> String {
+        String::from("wss://advanced-trade-ws.coinbase.com")
+    }
+
+    fn subscribe_messages(&self) -> Vec<String> {
+        let product_id = self.product_id.as_str();
+        vec![
+            format!(r#"{{"type":"subscribe","product_ids":["{product_id}"],"channel":"level2"}}"#),
+            format!(r#"{{"type":"subscribe","product_ids":["{product_id}"],"channel":"heartbeats"}}"#),
+        ]
+    }
+
+    fn handle_message(&mut self, message: &str, limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        let message = serde_json::from_str::<CoinbaseMessage>(message)?;
+        self.sequence_tracker.check(message.sequence_num)?;
+
+        if message.channel != "l2_data" {
+            return Ok(());
+        }
+
+        for event in &message.events {
+            if event.product_id != self.product_id {
+                continue;
+            }
+            match event.event_type.as_str() {
+                "snapshot" => {
+                    self.apply_snapshot(&event.updates, limit_order_book)?;
+                },
+                "update" => {
+                    self.apply_updates(&event.updates, limit_order_book)?;
+                },
+                event_type => {
+                    return Err(FeedError::InvalidMessage(format!("unknown event type {event_type}")));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self, limit_order_book: &mut MultiTickerLimitOrderBook) -> Result<(), FeedError> {
+        self.sequence_tracker.reset();
+        self.snapshot_received = false;
+        limit_order_book.replace_exchange_snapshot(self.ticker, self.source_exchange, &[], &[])?;
+        Ok(())
+    }
+}
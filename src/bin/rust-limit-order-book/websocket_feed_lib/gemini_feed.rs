@@ -0,0 +1,146 @@
+
+use serde::Deserialize;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+
+use super::FeedError;
+use super::FeedHandler;
+use super::SequenceTracker;
+use super::parse_fixed_point;
+
+
+#[derive(Deserialize, Debug)]
+struct GeminiMessage {
+    #[serde(rename="type")]
+    message_type: String,
+    socket_sequence: u64,
+    #[serde(default)]
+    events: Vec<GeminiEvent>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GeminiEvent {
+    #[serde(rename="type")]
+    event_type: String,
+    #[serde(default)]
+    reason: String,
+    #[serde(default)]
+    side: String,
+    #[serde(default)]
+    price: String,
+    #[serde(default)]
+    remaining: String,
+}
+
+/// Gemini v1 market data feed for one symbol.
+///
+/// The first message lists every price level with reason `initial`. Each
+/// later `change` event gives the new total volume at one price level. Every
+/// message carries a `socket_sequence` which increases by one.
+#[derive(Debug)]
+pub struct GeminiFeedHandler {
+    symbol: String,
+    ticker: Instrument,
+    source_exchange: ExchangeId,
+    sequence_tracker: SequenceTracker,
+    snapshot_received: bool,
+}
+
+impl GeminiFeedHandler {
+    pub fn new(symbol: &str, ticker: Instrument, source_exchange: ExchangeId) -> GeminiFeedHandler {
+        GeminiFeedHandler {
+            symbol: String::from(symbol),
+            ticker,
+            source_exchange,
+            sequence_tracker: SequenceTracker::new(),
+            snapshot_received: false,
+        }
+    }
+}
+
+fn parse_change(event: &GeminiEvent) -> Result<(OrderSide, Price, Quantity), FeedError> {
+    let order_side = match event.side.as_str() {
+        "bid" => {
+            OrderSide::BUY
+        },
+        "ask" => {
+            OrderSide::SELL
+        },
+        side => {
+            return Err(FeedError::InvalidMessage(format!("unknown side {side}")));
+        },
+    };
+    let price = parse_fixed_point(&event.price)?;
+    let volume = parse_fixed_point(&event.remaining)?;
+    Ok((order_side, price, volume))
+}
+
+impl FeedHandler for GeminiFeedHandler {
+    fn url(&self) -> String {
+        let symbol = self.symbol.as_str();
+        format!("wss://api.gemini.com/v1/marketdata/{symbol}?top_of_book=false&bids=true&offers=true&trades=false&auctions=false&heartbeat=true")
+    }
+
+    fn subscribe_messages(&self) -> Vec<String> {
+        // The symbol is part of the URL
+        Vec::new()
+    }
+
+    fn handle_message(&mut self, message: &str, limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        let message = serde_json::from_str::<GeminiMessage>(message)?;
+        self.sequence_tracker.check(message.socket_sequence)?;
+
+        if message.message_type != "update" {
+            return Ok(());
+        }
+
+        let changes =
+            message.events
+                .iter()
+                .filter(|event| event.event_type == "change")
+                .collect::<Vec<&GeminiEvent>>();
+
+        let is_snapshot = changes.iter().any(|event| event.reason == "initial");
+        if is_snapshot {
+            let mut bids = Vec::new();
+            let mut asks = Vec::new();
+            for event in changes {
+                let (order_side, price, volume) = parse_change(event)?;
+                match order_side {
+                    OrderSide::BUY => {
+                        bids.push((price, volume))
+                    },
+                    OrderSide::SELL => {
+                        asks.push((price, volume))
+                    },
+                }
+            }
+            limit_order_book.replace_exchange_snapshot(self.ticker, self.source_exchange, &bids, &asks)?;
+            self.snapshot_received = true;
+            return Ok(());
+        }
+
+        if !self.snapshot_received {
+            return Err(FeedError::UpdateBeforeSnapshot);
+        }
+        for event in changes {
+            let (order_side, price, volume) = parse_change(event)?;
+            limit_order_book.set_price_level_volume(self.ticker, order_side, price, self.source_exchange, volume)?;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self, limit_order_book: &mut MultiTickerLimitOrderBook) -> Result<(), FeedError> {
+        self.sequence_tracker.reset();
+        self.snapshot_received = false;
+        limit_order_book.replace_exchange_snapshot(self.ticker, self.source_exchange, &[], &[])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,255 @@
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::Instrument;
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+
+use super::FeedError;
+use super::FeedHandler;
+use super::parse_fixed_point;
+
+
+/// The number of price levels on each side which the Kraken checksum covers.
+const CHECKSUM_DEPTH: usize = 10;
+
+/// Kraken v1 `book` channel for one pair.
+///
+/// Kraken messages have no sequence numbers. Instead each update carries a
+/// CRC32 checksum of the top ten price levels on each side, which is compared
+/// with the book built from the messages received so far. Kraken sends a book
+/// of fixed depth and does not send deletes for price levels which fall out
+/// of it, so the handler keeps its own copy of the book of the exchange in
+/// order to truncate it.
+#[derive(Debug)]
+pub struct KrakenFeedHandler {
+    pair: String,
+    depth: usize,
+    ticker: Instrument,
+    source_exchange: ExchangeId,
+    bids: BTreeMap<Price, Quantity>,
+    asks: BTreeMap<Price, Quantity>,
+    snapshot_received: bool,
+}
+
+impl KrakenFeedHandler {
+    pub fn new(pair: &str, depth: usize, ticker: Instrument, source_exchange: ExchangeId) -> KrakenFeedHandler {
+        KrakenFeedHandler {
+            pair: String::from(pair),
+            depth,
+            ticker,
+            source_exchange,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            snapshot_received: false,
+        }
+    }
+
+    fn price_levels_mut(&mut self, order_side: OrderSide) -> &mut BTreeMap<Price, Quantity> {
+        match order_side {
+            OrderSide::BUY => {
+                &mut self.bids
+            },
+            OrderSide::SELL => {
+                &mut self.asks
+            },
+        }
+    }
+
+    /// Remove the price levels which are deeper than the subscribed depth.
+    /// Returns the prices of the removed price levels.
+    fn truncate(&mut self) -> Vec<(OrderSide, Price)> {
+        let mut removed_price_levels = Vec::new();
+        while self.bids.len() > self.depth {
+            let (price, _volume) = self.bids.pop_first().expect("bids are not empty");
+            removed_price_levels.push((OrderSide::BUY, price));
+        }
+        while self.asks.len() > self.depth {
+            let (price, _volume) = self.asks.pop_last().expect("asks are not empty");
+            removed_price_levels.push((OrderSide::SELL, price));
+        }
+        removed_price_levels
+    }
+
+    fn checksum(&self) -> u32 {
+        let mut checksum_input = String::new();
+        for (price, volume) in self.asks.iter().take(CHECKSUM_DEPTH) {
+            checksum_input.push_str(&checksum_string(*price));
+            checksum_input.push_str(&checksum_string(*volume));
+        }
+        for (price, volume) in self.bids.iter().rev().take(CHECKSUM_DEPTH) {
+            checksum_input.push_str(&checksum_string(*price));
+            checksum_input.push_str(&checksum_string(*volume));
+        }
+        crc32fast::hash(checksum_input.as_bytes())
+    }
+
+    fn apply_snapshot(&mut self, payload: &serde_json::Map<String, Value>, limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        self.bids = parse_price_levels(payload.get("bs"))?.into_iter().collect();
+        self.asks = parse_price_levels(payload.get("as"))?.into_iter().collect();
+        self.truncate();
+
+        let bids = self.bids.iter().map(|(price, volume)| (*price, *volume)).collect::<Vec<(Price, Quantity)>>();
+        let asks = self.asks.iter().map(|(price, volume)| (*price, *volume)).collect::<Vec<(Price, Quantity)>>();
+        limit_order_book.replace_exchange_snapshot(self.ticker, self.source_exchange, &bids, &asks)?;
+        self.snapshot_received = true;
+        Ok(())
+    }
+
+    fn apply_updates(&mut self, payloads: &[&serde_json::Map<String, Value>], limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        if !self.snapshot_received {
+            return Err(FeedError::UpdateBeforeSnapshot);
+        }
+
+        let mut changed_price_levels = Vec::new();
+        let mut expected_checksum = None;
+
+        for payload in payloads {
+            for (key, order_side) in [("b", OrderSide::BUY), ("a", OrderSide::SELL)] {
+                for (price, volume) in parse_price_levels(payload.get(key))? {
+                    let price_levels = self.price_levels_mut(order_side);
+                    if volume.is_positive() {
+                        price_levels.insert(price, volume);
+                    } else {
+                        price_levels.remove(&price);
+                    }
+                    changed_price_levels.push((order_side, price));
+                }
+            }
+            if let Some(checksum) = payload.get("c") {
+                let checksum = checksum
+                    .as_str()
+                    .and_then(|checksum| checksum.parse::<u32>().ok())
+                    .ok_or_else(|| FeedError::InvalidMessage(format!("invalid checksum {checksum}")))?;
+                expected_checksum = Some(checksum);
+            }
+        }
+        changed_price_levels.extend(self.truncate());
+
+        if let Some(expected) = expected_checksum {
+            let calculated = self.checksum();
+            if calculated != expected {
+                return Err(
+                    FeedError::ChecksumMismatch {
+                        expected,
+                        calculated,
+                    }
+                );
+            }
+        }
+
+        for (order_side, price) in changed_price_levels {
+            let price_levels = self.price_levels_mut(order_side);
+            let volume = price_levels.get(&price).copied().unwrap_or(Quantity::ZERO);
+            limit_order_book.set_price_level_volume(self.ticker, order_side, price, self.source_exchange, volume)?;
+        }
+        Ok(())
+    }
+}
+
+/// Kraken checksums use the price and volume strings as sent, with the decimal
+/// point and any leading zeros removed.
+fn checksum_string(value: FixedPoint) -> String {
+    let digits = value.to_string().replace('.', "");
+    String::from(digits.trim_start_matches('0'))
+}
+
+/// Parse a list of `[price, volume, timestamp]` price levels. Updates may have
+/// a fourth element, which is ignored.
+fn parse_price_levels(price_levels: Option<&Value>) -> Result<Vec<(Price, Quantity)>, FeedError> {
+    let price_levels = match price_levels {
+        Some(Value::Array(price_levels)) => price_levels,
+        Some(price_levels) => return Err(FeedError::InvalidMessage(format!("invalid price levels {price_levels}"))),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut parsed_price_levels = Vec::new();
+    for price_level in price_levels {
+        let invalid_price_level = || FeedError::InvalidMessage(format!("invalid price level {price_level}"));
+        let price = price_level.get(0).and_then(Value::as_str).ok_or_else(invalid_price_level)?;
+        let volume = price_level.get(1).and_then(Value::as_str).ok_or_else(invalid_price_level)?;
+        parsed_price_levels.push((parse_fixed_point(price)?, parse_fixed_point(volume)?));
+    }
+    Ok(parsed_price_levels)
+}
+
+impl FeedHandler for KrakenFeedHandler {
+    fn url(&self) -> String {
+        String::from("wss://ws.kraken.com")
+    }
+
+    fn subscribe_messages(&self) -> Vec<String> {
+        let pair = self.pair.as_str();
+        let depth = self.depth;
+        vec![
+            format!(r#"{{"event":"subscribe","pair":["{pair}"],"subscription":{{"name":"book","depth":{depth}}}}}"#),
+        ]
+    }
+
+    fn handle_message(&mut self, message: &str, limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>
+    {
+        let message = serde_json::from_str::<Value>(message)?;
+
+        // Book messages are arrays. Objects are events such as heartbeats and
+        // subscription status messages.
+        let elements = match message {
+            Value::Array(elements) => {
+                elements
+            },
+            Value::Object(event) => {
+                if event.get("status").and_then(Value::as_str) == Some("error") {
+                    let error_message = event.get("errorMessage").and_then(Value::as_str).unwrap_or("unknown error");
+                    return Err(FeedError::InvalidMessage(String::from(error_message)));
+                }
+                return Ok(());
+            },
+            message => {
+                return Err(FeedError::InvalidMessage(message.to_string()));
+            },
+        };
+
+        // [channel id, payload, (payload,) channel name, pair]
+        if elements.len() < 4 {
+            return Err(FeedError::InvalidMessage(format!("{elements:?}")));
+        }
+        if elements[elements.len() - 1].as_str() != Some(self.pair.as_str()) {
+            return Ok(());
+        }
+
+        let mut payloads = Vec::new();
+        for payload in &elements[1..elements.len() - 2] {
+            let payload = payload
+                .as_object()
+                .ok_or_else(|| FeedError::InvalidMessage(format!("invalid payload {payload}")))?;
+            payloads.push(payload);
+        }
+
+        match payloads.first() {
+            Some(payload) if payload.contains_key("as") || payload.contains_key("bs") => {
+                self.apply_snapshot(payload, limit_order_book)
+            },
+            _ => {
+                self.apply_updates(&payloads, limit_order_book)
+            },
+        }
+    }
+
+    fn reset(&mut self, limit_order_book: &mut MultiTickerLimitOrderBook) -> Result<(), FeedError> {
+        self.bids.clear();
+        self.asks.clear();
+        self.snapshot_received = false;
+        limit_order_book.replace_exchange_snapshot(self.ticker, self.source_exchange, &[], &[])?;
+        Ok(())
+    }
+}
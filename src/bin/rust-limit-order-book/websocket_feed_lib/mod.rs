@@ -0,0 +1,198 @@
+
+mod coinbase_feed;
+mod gemini_feed;
+mod kraken_feed;
+
+pub use coinbase_feed::CoinbaseFeedHandler;
+pub use gemini_feed::GeminiFeedHandler;
+pub use kraken_feed::KrakenFeedHandler;
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use tungstenite::Message;
+
+use crate::limit_order_book_lib::FixedPoint;
+use crate::limit_order_book_lib::MultiTickerLimitOrderBook;
+use crate::limit_order_book_lib::OrderBookError;
+
+
+/// Errors which stop a feed.
+///
+/// After any of these the volume of the exchange in the book can no longer be
+/// trusted. The feed should be restarted, which begins with a new snapshot.
+#[derive(Debug)]
+pub enum FeedError {
+    WebSocket(tungstenite::Error),
+    Json(serde_json::Error),
+    /// A message which is valid JSON but which could not be understood.
+    InvalidMessage(String),
+    /// An incremental update was received before the initial snapshot.
+    UpdateBeforeSnapshot,
+    /// One or more messages were missed.
+    SequenceGap {
+        expected: u64,
+        received: u64,
+    },
+    /// The book calculated from the messages received does not match the
+    /// checksum sent by the exchange.
+    ChecksumMismatch {
+        expected: u32,
+        calculated: u32,
+    },
+    OrderBook(OrderBookError),
+}
+
+impl std::error::Error for FeedError {
+
+}
+
+impl fmt::Display for FeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedError::WebSocket(error) => {
+                write!(f, "websocket error: {error}")
+            },
+            FeedError::Json(error) => {
+                write!(f, "failed to deserialize message: {error}")
+            },
+            FeedError::InvalidMessage(message) => {
+                write!(f, "invalid message: {message}")
+            },
+            FeedError::UpdateBeforeSnapshot => {
+                write!(f, "update received before snapshot")
+            },
+            FeedError::SequenceGap { expected, received } => {
+                write!(f, "sequence gap: expected {expected}, received {received}")
+            },
+            FeedError::ChecksumMismatch { expected, calculated } => {
+                write!(f, "checksum mismatch: expected {expected}, calculated {calculated}")
+            },
+            FeedError::OrderBook(error) => {
+                write!(f, "order book error: {error}")
+            },
+        }
+    }
+}
+
+impl From<tungstenite::Error> for FeedError {
+    fn from(error: tungstenite::Error) -> FeedError {
+        FeedError::WebSocket(error)
+    }
+}
+
+impl From<serde_json::Error> for FeedError {
+    fn from(error: serde_json::Error) -> FeedError {
+        FeedError::Json(error)
+    }
+}
+
+impl From<OrderBookError> for FeedError {
+    fn from(error: OrderBookError) -> FeedError {
+        FeedError::OrderBook(error)
+    }
+}
+
+/// Handles the messages of the L2 channel of one exchange, for one ticker.
+pub trait FeedHandler {
+    /// The URL of the WebSocket endpoint of the exchange.
+    fn url(&self) -> String;
+
+    /// The messages to send after connecting, to subscribe to the L2 channel.
+    fn subscribe_messages(&self) -> Vec<String>;
+
+    /// Apply one message to the book.
+    fn handle_message(&mut self, message: &str, limit_order_book: &mut MultiTickerLimitOrderBook)
+        -> Result<(), FeedError>;
+
+    /// Forget the state of the connection and remove the volume of the exchange
+    /// from the book. Called before connecting and when the feed stops with an
+    /// error.
+    fn reset(&mut self, limit_order_book: &mut MultiTickerLimitOrderBook) -> Result<(), FeedError>;
+}
+
+/// Checks that the sequence numbers of the messages on one connection have no gaps.
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    last_sequence: Option<u64>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> SequenceTracker {
+        SequenceTracker {
+            last_sequence: None,
+        }
+    }
+
+    pub fn check(&mut self, sequence: u64) -> Result<(), FeedError> {
+        if let Some(last_sequence) = self.last_sequence {
+            let expected = last_sequence + 1;
+            if sequence != expected {
+                return Err(
+                    FeedError::SequenceGap {
+                        expected,
+                        received: sequence,
+                    }
+                );
+            }
+        }
+        self.last_sequence = Some(sequence);
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.last_sequence = None;
+    }
+}
+
+/// Connect to `url`, subscribe, and apply each message to the book until the
+/// connection is closed or an error occurs.
+///
+/// Returns `Ok` when the server closes the connection. The volume of the
+/// exchange is removed from the book if the feed stops with an error.
+pub fn run_feed(
+    url: &str,
+    feed_handler: &mut dyn FeedHandler,
+    limit_order_book: &RwLock<MultiTickerLimitOrderBook>,
+) -> Result<(), FeedError> {
+    feed_handler.reset(&mut limit_order_book.write().expect("order book lock poisoned"))?;
+
+    let result = read_messages(url, feed_handler, limit_order_book);
+    if result.is_err() {
+        feed_handler.reset(&mut limit_order_book.write().expect("order book lock poisoned"))?;
+    }
+    result
+}
+
+fn read_messages(
+    url: &str,
+    feed_handler: &mut dyn FeedHandler,
+    limit_order_book: &RwLock<MultiTickerLimitOrderBook>,
+) -> Result<(), FeedError> {
+    let (mut websocket, _response) = tungstenite::connect(url)?;
+
+    for subscribe_message in feed_handler.subscribe_messages() {
+        websocket.send(Message::text(subscribe_message))?;
+    }
+
+    loop {
+        let message = match websocket.read() {
+            Ok(message) => message,
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(error) => return Err(FeedError::from(error)),
+        };
+        if let Message::Text(text) = message {
+            let mut limit_order_book = limit_order_book.write().expect("order book lock poisoned");
+            feed_handler.handle_message(text.as_str(), &mut limit_order_book)?;
+        }
+    }
+}
+
+fn parse_fixed_point(s: &str) -> Result<FixedPoint, FeedError> {
+    FixedPoint::from_str(s).map_err(|error| FeedError::InvalidMessage(error.to_string()))
+}
+
+
+#[cfg(test)]
+mod tests;
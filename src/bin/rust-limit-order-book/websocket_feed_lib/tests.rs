@@ -0,0 +1,242 @@
+
+use super::*;
+
+use std::collections::BTreeMap;
+use std::net::TcpListener;
+use std::thread;
+use std::thread::JoinHandle;
+
+use crate::limit_order_book_lib::ExchangeId;
+use crate::limit_order_book_lib::OrderSide;
+use crate::limit_order_book_lib::Price;
+use crate::limit_order_book_lib::Quantity;
+use crate::limit_order_book_lib::Instrument;
+use crate::test_support_lib::fixed_point;
+
+
+const COINBASE_MESSAGES: &str = include_str!("test_data/coinbase_level2.jsonl");
+const GEMINI_MESSAGES: &str = include_str!("test_data/gemini_btcusd.jsonl");
+const KRAKEN_MESSAGES: &str = include_str!("test_data/kraken_xbtusd.jsonl");
+
+fn ticker() -> Instrument {
+    Instrument::intern("BTC", "USD")
+}
+
+fn recorded_messages(messages: &str) -> Vec<String> {
+    messages.lines().map(String::from).collect()
+}
+
+/// Start a WebSocket server which accepts one connection, reads the expected
+/// number of subscribe messages, replays `messages` and then closes the
+/// connection. The server thread returns the subscribe messages it received.
+fn mock_server(messages: Vec<String>, subscribe_message_count: usize) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let url = format!("ws://{address}");
+
+    let join_handle = thread::spawn(
+        move || {
+            let (stream, _address) = listener.accept().unwrap();
+            let mut websocket = tungstenite::accept(stream).unwrap();
+
+            let mut subscribe_messages = Vec::new();
+            while subscribe_messages.len() < subscribe_message_count {
+                if let Message::Text(text) = websocket.read().unwrap() {
+                    subscribe_messages.push(String::from(text.as_str()));
+                }
+            }
+
+            // The client disconnects early if it finds an error in the messages
+            for message in messages {
+                if websocket.send(Message::text(message)).is_err() {
+                    return subscribe_messages;
+                }
+            }
+            if websocket.close(None).is_err() {
+                return subscribe_messages;
+            }
+
+            // Wait for the client to acknowledge the close
+            while websocket.read().is_ok() {
+
+            }
+            subscribe_messages
+        }
+    );
+
+    (url, join_handle)
+}
+
+fn volume_by_price_level(
+    limit_order_book: &RwLock<MultiTickerLimitOrderBook>,
+    order_side: OrderSide,
+    source_exchange: ExchangeId,
+) -> BTreeMap<Price, Quantity> {
+    let limit_order_book = limit_order_book.read().unwrap();
+    limit_order_book
+        .total_volume_by_price_level_and_source_exchange(ticker(), &order_side)
+        .unwrap()
+        .into_iter()
+        .filter_map(
+            |(price, total_volume_by_source_exchange)| {
+                total_volume_by_source_exchange.get(&source_exchange).map(|volume| (price, *volume))
+            }
+        )
+        .collect()
+}
+
+
+#[test]
+fn coinbase_feed_test() {
+
+    let source_exchange = ExchangeId::intern("COINBASE");
+    let mut feed_handler = CoinbaseFeedHandler::new("BTC-USD", ticker(), source_exchange);
+    let limit_order_book = RwLock::new(MultiTickerLimitOrderBook::new());
+
+    let (url, join_handle) = mock_server(recorded_messages(COINBASE_MESSAGES), 2);
+    run_feed(&url, &mut feed_handler, &limit_order_book).unwrap();
+
+    let subscribe_messages = join_handle.join().unwrap();
+    assert_eq!(subscribe_messages, feed_handler.subscribe_messages());
+
+    let expected_bids = BTreeMap::from(
+        [
+            (fixed_point("63999.50"), fixed_point("1.25")),
+            (fixed_point("64000.01"), fixed_point("0.3")),
+            (fixed_point("64000.50"), fixed_point("0.1")),
+        ]
+    );
+    let expected_asks = BTreeMap::from(
+        [
+            (fixed_point("64002.50"), fixed_point("2.0")),
+        ]
+    );
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::BUY, source_exchange), expected_bids);
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::SELL, source_exchange), expected_asks);
+}
+
+
+#[test]
+fn coinbase_feed_sequence_gap_test() {
+
+    let source_exchange = ExchangeId::intern("COINBASE_SEQUENCE_GAP");
+    let mut feed_handler = CoinbaseFeedHandler::new("BTC-USD", ticker(), source_exchange);
+    let limit_order_book = RwLock::new(MultiTickerLimitOrderBook::new());
+
+    // Drop the heartbeat with sequence number 2
+    let mut messages = recorded_messages(COINBASE_MESSAGES);
+    messages.remove(2);
+
+    let (url, join_handle) = mock_server(messages, 2);
+    let result = run_feed(&url, &mut feed_handler, &limit_order_book);
+    assert!(matches!(result, Err(FeedError::SequenceGap { expected: 2, received: 3 })));
+    join_handle.join().unwrap();
+
+    // The snapshot which was applied before the gap has been removed
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::BUY, source_exchange), BTreeMap::new());
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::SELL, source_exchange), BTreeMap::new());
+}
+
+
+#[test]
+fn gemini_feed_test() {
+
+    let source_exchange = ExchangeId::intern("GEMINI");
+    let mut feed_handler = GeminiFeedHandler::new("BTCUSD", ticker(), source_exchange);
+    let limit_order_book = RwLock::new(MultiTickerLimitOrderBook::new());
+
+    let (url, join_handle) = mock_server(recorded_messages(GEMINI_MESSAGES), 0);
+    run_feed(&url, &mut feed_handler, &limit_order_book).unwrap();
+    join_handle.join().unwrap();
+
+    let expected_bids = BTreeMap::from(
+        [
+            (fixed_point("63980.00"), fixed_point("2.0")),
+            (fixed_point("63990.00"), fixed_point("1.0")),
+            (fixed_point("63995.00"), fixed_point("0.2")),
+        ]
+    );
+    let expected_asks = BTreeMap::from(
+        [
+            (fixed_point("64020.00"), fixed_point("1.5")),
+        ]
+    );
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::BUY, source_exchange), expected_bids);
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::SELL, source_exchange), expected_asks);
+
+    // An update without a snapshot
+    let mut feed_handler = GeminiFeedHandler::new("BTCUSD", ticker(), source_exchange);
+    let mut messages = recorded_messages(GEMINI_MESSAGES);
+    messages.remove(0);
+    let mut limit_order_book = limit_order_book.write().unwrap();
+    assert!(feed_handler.handle_message(&messages[0], &mut limit_order_book).is_ok());
+    assert!(matches!(feed_handler.handle_message(&messages[1], &mut limit_order_book), Err(FeedError::UpdateBeforeSnapshot)));
+}
+
+
+#[test]
+fn kraken_feed_test() {
+
+    let source_exchange = ExchangeId::intern("KRAKEN");
+    let mut feed_handler = KrakenFeedHandler::new("XBT/USD", 3, ticker(), source_exchange);
+    let limit_order_book = RwLock::new(MultiTickerLimitOrderBook::new());
+
+    let (url, join_handle) = mock_server(recorded_messages(KRAKEN_MESSAGES), 1);
+    run_feed(&url, &mut feed_handler, &limit_order_book).unwrap();
+
+    let subscribe_messages = join_handle.join().unwrap();
+    assert_eq!(subscribe_messages, vec![String::from(r#"{"event":"subscribe","pair":["XBT/USD"],"subscription":{"name":"book","depth":3}}"#)]);
+
+    // The bid at 64003.0 fell out of the top 3 price levels
+    let expected_bids = BTreeMap::from(
+        [
+            (fixed_point("64004.0"), fixed_point("1.2")),
+            (fixed_point("64004.9"), fixed_point("0.25")),
+            (fixed_point("64004.95"), fixed_point("0.1")),
+        ]
+    );
+    let expected_asks = BTreeMap::from(
+        [
+            (fixed_point("64006.1"), fixed_point("1.0")),
+            (fixed_point("64007.0"), fixed_point("2.0")),
+        ]
+    );
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::BUY, source_exchange), expected_bids);
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::SELL, source_exchange), expected_asks);
+}
+
+
+#[test]
+fn kraken_feed_checksum_mismatch_test() {
+
+    let source_exchange = ExchangeId::intern("KRAKEN_CHECKSUM_MISMATCH");
+    let mut feed_handler = KrakenFeedHandler::new("XBT/USD", 3, ticker(), source_exchange);
+    let limit_order_book = RwLock::new(MultiTickerLimitOrderBook::new());
+
+    // Change the volume of an update, so that the checksum no longer matches
+    let messages =
+        recorded_messages(KRAKEN_MESSAGES)
+            .into_iter()
+            .map(|message| message.replace(r#"["64004.95000","0.10000000""#, r#"["64004.95000","0.20000000""#))
+            .collect();
+
+    let (url, join_handle) = mock_server(messages, 1);
+    let result = run_feed(&url, &mut feed_handler, &limit_order_book);
+    assert!(matches!(result, Err(FeedError::ChecksumMismatch { expected: 3263421392, .. })));
+    join_handle.join().unwrap();
+
+    assert_eq!(volume_by_price_level(&limit_order_book, OrderSide::BUY, source_exchange), BTreeMap::new());
+}
+
+
+#[test]
+fn sequence_tracker_test() {
+
+    let mut sequence_tracker = SequenceTracker::new();
+    assert!(sequence_tracker.check(5).is_ok());
+    assert!(sequence_tracker.check(6).is_ok());
+    assert!(matches!(sequence_tracker.check(6), Err(FeedError::SequenceGap { expected: 7, received: 6 })));
+
+    sequence_tracker.reset();
+    assert!(sequence_tracker.check(0).is_ok());
+}